@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS recipe_revisions (
+    recipe_id UUID NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    editor_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    ingredients TEXT[] NOT NULL,
+    instructions TEXT[] NOT NULL,
+    prep_time_minutes INTEGER,
+    cook_time_minutes INTEGER,
+    servings INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (recipe_id, revision)
+);
+
+INSERT INTO recipe_revisions (recipe_id, revision, editor_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at)
+SELECT id, 1, owner_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at
+FROM recipes
+ON CONFLICT (recipe_id, revision) DO NOTHING;
@@ -0,0 +1 @@
+ALTER TABLE recipes ADD COLUMN IF NOT EXISTS parsed_ingredients JSONB NOT NULL DEFAULT '[]'::jsonb;
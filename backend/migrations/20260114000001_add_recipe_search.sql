@@ -0,0 +1,12 @@
+-- array_to_string is only STABLE, and generated columns need IMMUTABLE expressions.
+CREATE OR REPLACE FUNCTION recipe_lines_to_text(lines TEXT[]) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    AS $$ SELECT array_to_string(lines, ' ') $$;
+
+ALTER TABLE recipes ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') ||
+    setweight(to_tsvector('english', recipe_lines_to_text(ingredients)), 'B') ||
+    setweight(to_tsvector('english', recipe_lines_to_text(instructions)), 'C')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_recipes_search_vector ON recipes USING GIN (search_vector);
@@ -0,0 +1,4 @@
+-- Keyset pagination over a user's recipes, one index per sort order
+CREATE INDEX IF NOT EXISTS idx_recipes_owner_created ON recipes(owner_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_recipes_owner_title ON recipes(owner_id, lower(title), id);
+CREATE INDEX IF NOT EXISTS idx_recipe_shares_user_created ON recipe_shares(user_id, created_at DESC);
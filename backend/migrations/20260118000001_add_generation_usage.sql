@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS generation_usage (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- `user:<id>` for signed-in users, `ip:<address>` for anonymous visitors.
+    subject TEXT NOT NULL,
+    prompt_tokens BIGINT NOT NULL,
+    completion_tokens BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_generation_usage_subject_created_at
+    ON generation_usage(subject, created_at);
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS generations (
+    id UUID PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    ingredients TEXT[] NOT NULL,
+    dietary_restrictions TEXT[],
+    constraints JSONB NOT NULL,
+    model TEXT,
+    prompt_version TEXT,
+    raw_output TEXT,
+    recipes JSONB NOT NULL,
+    latency_ms BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_generations_user_created ON generations(user_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_generations_created_at ON generations(created_at);
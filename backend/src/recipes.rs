@@ -14,12 +14,41 @@ pub fn create_router(config: &AppConfig, db_pool: PgPool) -> Router {
 
     adapters::create_router(
         dependencies.generate_use_case,
+        dependencies.stream_use_case,
+        dependencies.candidates_use_case,
+        dependencies.refine_use_case,
+        dependencies.substitutes_use_case,
+        dependencies.usage_use_case,
+        dependencies.record_usage_use_case,
+        dependencies.record_generation_use_case,
+        dependencies.list_generations_use_case,
+        dependencies.get_generation_use_case,
         dependencies.save_use_case,
         dependencies.get_use_case,
+        dependencies.update_use_case,
+        dependencies.set_tags_use_case,
+        dependencies.delete_use_case,
+        dependencies.list_revisions_use_case,
+        dependencies.get_revision_use_case,
+        dependencies.diff_revisions_use_case,
+        dependencies.restore_revision_use_case,
         dependencies.list_owned_use_case,
         dependencies.list_shared_use_case,
+        dependencies.search_use_case,
         dependencies.list_recipe_shares_use_case,
         dependencies.create_share_use_case,
         dependencies.delete_share_use_case,
+        dependencies.create_collection_use_case,
+        dependencies.rename_collection_use_case,
+        dependencies.delete_collection_use_case,
+        dependencies.list_collections_use_case,
+        dependencies.list_shared_collections_use_case,
+        dependencies.get_collection_use_case,
+        dependencies.add_collection_recipe_use_case,
+        dependencies.remove_collection_recipe_use_case,
+        dependencies.list_collection_shares_use_case,
+        dependencies.create_collection_share_use_case,
+        dependencies.delete_collection_share_use_case,
+        dependencies.cursor_codec,
     )
 }
@@ -1,3 +1,4 @@
+mod cursor;
 mod dto;
 mod extractors;
 mod handlers;
@@ -5,4 +6,5 @@ mod openapi;
 mod routes;
 mod state;
 
+pub use cursor::CursorCodec;
 pub use routes::create_router;
@@ -0,0 +1,169 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::DateTime;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::recipes::domain::{RecipeCursor, RecipeSort, SortKey};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Turns page cursors into opaque tokens and back. Tokens are signed, so a
+/// client can hand one back but cannot forge or edit it, and a token is only
+/// accepted for the list and user it was issued for.
+pub struct CursorCodec {
+    key: Vec<u8>,
+}
+
+impl CursorCodec {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            key: secret.to_vec(),
+        }
+    }
+
+    /// A codec with a key that only lives as long as the process, so cursors
+    /// stop working after a restart.
+    pub fn random() -> Self {
+        let key = [Uuid::new_v4().into_bytes(), Uuid::new_v4().into_bytes()].concat();
+        Self { key }
+    }
+
+    pub fn encode(&self, cursor: &RecipeCursor, list: &str, user_id: &str) -> String {
+        let payload = match &cursor.key {
+            SortKey::CreatedAt(at) => format!("c|{}|{}", cursor.id, at.timestamp_micros()),
+            SortKey::Title(title) => format!("t|{}|{}", cursor.id, title),
+            SortKey::TotalTime(minutes) => format!("m|{}|{}", cursor.id, minutes),
+        };
+        let signature = self.mac(list, user_id, payload.as_bytes()).finalize();
+
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature.into_bytes())
+        )
+    }
+
+    /// The cursor in `token`, or `None` if the token is malformed, was tampered
+    /// with, was issued for another list or user, or is for a different sort.
+    pub fn decode(
+        &self,
+        token: &str,
+        list: &str,
+        user_id: &str,
+        sort: RecipeSort,
+    ) -> Option<RecipeCursor> {
+        let (payload, signature) = token.split_once('.')?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        self.mac(list, user_id, &payload)
+            .verify_slice(&signature)
+            .ok()?;
+
+        let payload = String::from_utf8(payload).ok()?;
+        let mut parts = payload.splitn(3, '|');
+        let (kind, id, value) = (parts.next()?, parts.next()?, parts.next()?);
+        let key = match kind {
+            "c" => SortKey::CreatedAt(DateTime::from_timestamp_micros(value.parse().ok()?)?),
+            "t" => SortKey::Title(value.to_string()),
+            "m" => SortKey::TotalTime(value.parse().ok()?),
+            _ => return None,
+        };
+        if key.sort() != sort {
+            return None;
+        }
+
+        Some(RecipeCursor {
+            key,
+            id: id.parse().ok()?,
+        })
+    }
+
+    fn mac(&self, list: &str, user_id: &str, payload: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        for part in [list.as_bytes(), user_id.as_bytes(), payload] {
+            mac.update(&(part.len() as u64).to_be_bytes());
+            mac.update(part);
+        }
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn cursor(key: SortKey) -> RecipeCursor {
+        RecipeCursor {
+            key,
+            id: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn round_trips_every_sort_key() {
+        let codec = CursorCodec::new(b"secret");
+        let now = DateTime::from_timestamp_micros(Utc::now().timestamp_micros()).unwrap();
+
+        for (sort, key) in [
+            (RecipeSort::CreatedAt, SortKey::CreatedAt(now)),
+            (
+                RecipeSort::Title,
+                SortKey::Title("mac | cheese".to_string()),
+            ),
+            (RecipeSort::TotalTime, SortKey::TotalTime(45)),
+        ] {
+            let original = cursor(key);
+            let token = codec.encode(&original, "owned", "user_1");
+            assert_eq!(
+                codec.decode(&token, "owned", "user_1", sort),
+                Some(original)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_tampered_tokens() {
+        let codec = CursorCodec::new(b"secret");
+        let token = codec.encode(&cursor(SortKey::TotalTime(45)), "owned", "user_1");
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = format!(
+            "{}.{signature}",
+            URL_SAFE_NO_PAD.encode(format!("m|{}|0", Uuid::new_v4()))
+        );
+
+        assert_eq!(
+            codec.decode(&forged, "owned", "user_1", RecipeSort::TotalTime),
+            None
+        );
+        assert_eq!(
+            codec.decode("not-a-cursor", "owned", "user_1", RecipeSort::TotalTime),
+            None
+        );
+        assert_eq!(
+            CursorCodec::new(b"other").decode(&token, "owned", "user_1", RecipeSort::TotalTime),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_cursors_from_another_list_user_or_sort() {
+        let codec = CursorCodec::new(b"secret");
+        let token = codec.encode(&cursor(SortKey::TotalTime(45)), "owned", "user_1");
+
+        assert_eq!(
+            codec.decode(&token, "shared", "user_1", RecipeSort::TotalTime),
+            None
+        );
+        assert_eq!(
+            codec.decode(&token, "owned", "user_2", RecipeSort::TotalTime),
+            None
+        );
+        assert_eq!(
+            codec.decode(&token, "owned", "user_1", RecipeSort::Title),
+            None
+        );
+    }
+}
@@ -1,11 +1,19 @@
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::recipes::domain::{GeneratedRecipe, Recipe};
+use crate::recipes::domain::{
+    Allergen, Collection, Difficulty, Equipment, FieldChange, GeneratedRecipe, Generation,
+    LineChange, LineChangeKind, MAX_PROMPT_INGREDIENTS, MAX_PROMPT_RESTRICTIONS, NutritionFacts,
+    NutritionReport, PageRequest, ParsedIngredient, Recipe, RecipeConstraints, RecipeCursor,
+    RecipeDiff, RecipeRevision, RecipeSearch, RecipeSort, RecipeViolation, Scaling, SearchScope,
+    Substitute, SubstituteSource, Substitutions, UnitSystem, UsageSummary, UsageTotals,
+    check_prompt_text, clean_prompt_text, day_start, month_start,
+};
 
 fn validate_ingredients(ingredients: &[String]) -> Result<(), validator::ValidationError> {
     let valid_count = ingredients.iter().filter(|s| !s.trim().is_empty()).count();
@@ -17,18 +25,378 @@ fn validate_ingredients(ingredients: &[String]) -> Result<(), validator::Validat
     Ok(())
 }
 
+fn clean_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(|text| clean_prompt_text(&text)))
+}
+
+fn clean_items<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|item| clean_prompt_text(item))
+        .collect())
+}
+
+fn clean_optional_items<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    Ok(Option::<Vec<String>>::deserialize(deserializer)?
+        .map(|items| items.iter().map(|item| clean_prompt_text(item)).collect()))
+}
+
+/// Rejects more than `max` items, or any item that should not go into a
+/// prompt, saying which one and why.
+fn validate_prompt_items(
+    items: &[String],
+    max: usize,
+    item: &str,
+) -> Result<(), validator::ValidationError> {
+    if items.len() > max {
+        return Err(validator::ValidationError::new("too_many_items")
+            .with_message(Cow::Owned(format!("at most {} allowed", max))));
+    }
+    for (index, text) in items.iter().enumerate() {
+        check_prompt_text(text).map_err(|e| {
+            validator::ValidationError::new("unsafe_prompt_input")
+                .with_message(Cow::Owned(format!("{} {} {}", item, index + 1, e)))
+        })?;
+    }
+    Ok(())
+}
+
+fn validate_generation_ingredients(
+    ingredients: &[String],
+) -> Result<(), validator::ValidationError> {
+    validate_ingredients(ingredients)?;
+    validate_prompt_items(ingredients, MAX_PROMPT_INGREDIENTS, "ingredient")
+}
+
+fn validate_dietary_restrictions(
+    restrictions: &[String],
+) -> Result<(), validator::ValidationError> {
+    validate_prompt_items(restrictions, MAX_PROMPT_RESTRICTIONS, "restriction")
+}
+
+const MAX_EXCLUDED_INGREDIENTS: usize = 20;
+
+fn validate_excluded_ingredients(ingredients: &[String]) -> Result<(), validator::ValidationError> {
+    validate_prompt_items(ingredients, MAX_EXCLUDED_INGREDIENTS, "excluded ingredient")
+}
+
+fn validate_cuisine(cuisine: &str) -> Result<(), validator::ValidationError> {
+    if cuisine.is_empty() || cuisine.chars().count() > 50 {
+        return Err(validator::ValidationError::new("length")
+            .with_message(Cow::Borrowed("must be 1 to 50 characters")));
+    }
+    check_prompt_text(cuisine).map_err(|e| {
+        validator::ValidationError::new("unsafe_prompt_input")
+            .with_message(Cow::Owned(format!("cuisine {}", e)))
+    })
+}
+
+/// Text fields are cleaned of control and invisible characters, then
+/// rejected when too long, too many, or written to steer the AI instead of
+/// describing food.
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 #[schema(example = json!({
     "ingredients": ["chicken breast", "rice", "garlic", "onion"],
-    "dietaryRestrictions": ["gluten-free"]
+    "dietaryRestrictions": ["gluten-free"],
+    "cuisine": "Thai",
+    "maxTotalMinutes": 30,
+    "equipment": ["no_oven"]
 }))]
 pub struct GenerateRecipeRequest {
-    #[validate(custom(function = "validate_ingredients"))]
-    #[schema(example = json!(["chicken breast", "rice", "garlic", "onion"]))]
+    /// At most 30 ingredients of at most 100 characters each
+    #[serde(deserialize_with = "clean_items")]
+    #[validate(custom(function = "validate_generation_ingredients"))]
+    #[schema(example = json!(["chicken breast", "rice", "garlic", "onion"]), max_items = 30)]
     pub ingredients: Vec<String>,
-    #[schema(example = json!(["gluten-free", "dairy-free"]))]
+    /// At most 10 restrictions of at most 100 characters each
+    #[serde(default, deserialize_with = "clean_optional_items")]
+    #[validate(custom(function = "validate_dietary_restrictions"))]
+    #[schema(example = json!(["gluten-free", "dairy-free"]), max_items = 10)]
     pub dietary_restrictions: Option<Vec<String>>,
+    /// Ask for this many different recipes, answered as a list. Capped by the
+    /// server's configured maximum; fewer are returned when the AI cannot
+    /// come up with enough distinct, valid ones.
+    #[validate(range(min = 1))]
+    #[schema(example = 3, minimum = 1)]
+    pub count: Option<u32>,
+    #[serde(default, deserialize_with = "clean_text")]
+    #[validate(custom(function = "validate_cuisine"))]
+    #[schema(example = "Thai", min_length = 1, max_length = 50)]
+    pub cuisine: Option<String>,
+    /// Longest the recipe may take, prep and cooking together
+    #[validate(range(min = 1, max = 1440))]
+    #[schema(example = 30)]
+    pub max_total_minutes: Option<i32>,
+    /// Limits on the kitchen equipment the recipe may call for
+    #[serde(default)]
+    pub equipment: Vec<EquipmentParam>,
+    pub difficulty: Option<DifficultyParam>,
+    /// Number of servings the recipe should be written for. Unlike the
+    /// `servings` query parameter, which rescales the finished recipe, this
+    /// is asked of the AI and checked on its answer.
+    #[validate(range(min = 1, max = 100))]
+    #[schema(example = 2)]
+    pub servings: Option<i32>,
+    /// Ingredients the recipe must not use, not even as staples
+    #[serde(default, deserialize_with = "clean_items")]
+    #[validate(custom(function = "validate_excluded_ingredients"))]
+    #[schema(example = json!(["cilantro"]), max_items = 20)]
+    pub excluded_ingredients: Vec<String>,
+}
+
+impl GenerateRecipeRequest {
+    pub fn constraints(&self) -> RecipeConstraints {
+        RecipeConstraints {
+            cuisine: self.cuisine.clone(),
+            max_total_minutes: self.max_total_minutes,
+            equipment: self.equipment.iter().map(|&e| e.into()).collect(),
+            difficulty: self.difficulty.map(Into::into),
+            servings: self.servings,
+            excluded_ingredients: self
+                .excluded_ingredients
+                .iter()
+                .filter(|name| !name.trim().is_empty())
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EquipmentParam {
+    NoOven,
+    AirFryerOnly,
+    OnePot,
+}
+
+impl From<EquipmentParam> for Equipment {
+    fn from(equipment: EquipmentParam) -> Self {
+        match equipment {
+            EquipmentParam::NoOven => Equipment::NoOven,
+            EquipmentParam::AirFryerOnly => Equipment::AirFryerOnly,
+            EquipmentParam::OnePot => Equipment::OnePot,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DifficultyParam {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl From<DifficultyParam> for Difficulty {
+    fn from(difficulty: DifficultyParam) -> Self {
+        match difficulty {
+            DifficultyParam::Easy => Difficulty::Easy,
+            DifficultyParam::Medium => Difficulty::Medium,
+            DifficultyParam::Hard => Difficulty::Hard,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystemParam {
+    Metric,
+    Imperial,
+}
+
+impl From<UnitSystemParam> for UnitSystem {
+    fn from(units: UnitSystemParam) -> Self {
+        match units {
+            UnitSystemParam::Metric => UnitSystem::Metric,
+            UnitSystemParam::Imperial => UnitSystem::Imperial,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UnitsQuery {
+    /// Convert ingredient measures and oven temperatures to this unit system
+    #[param(inline)]
+    pub units: Option<UnitSystemParam>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScopeParam {
+    All,
+    Owned,
+    Shared,
+}
+
+impl From<SearchScopeParam> for SearchScope {
+    fn from(scope: SearchScopeParam) -> Self {
+        match scope {
+            SearchScopeParam::All => SearchScope::All,
+            SearchScopeParam::Owned => SearchScope::Owned,
+            SearchScopeParam::Shared => SearchScope::Shared,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeSortParam {
+    CreatedAt,
+    Title,
+    TotalTime,
+}
+
+impl From<RecipeSortParam> for RecipeSort {
+    fn from(sort: RecipeSortParam) -> Self {
+        match sort {
+            RecipeSortParam::CreatedAt => RecipeSort::CreatedAt,
+            RecipeSortParam::Title => RecipeSort::Title,
+            RecipeSortParam::TotalTime => RecipeSort::TotalTime,
+        }
+    }
+}
+
+const DEFAULT_PAGE_LIMIT: i64 = 20;
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListRecipesQuery {
+    /// Maximum number of recipes per page (default 20)
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    /// `nextCursor` from the previous page; omit for the first page
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
+    /// Sort order (default: created_at, newest first). A cursor only works
+    /// with the sort it was issued for.
+    #[param(inline)]
+    pub sort: Option<RecipeSortParam>,
+    /// Convert ingredient measures and oven temperatures to this unit system
+    #[param(inline)]
+    pub units: Option<UnitSystemParam>,
+}
+
+impl ListRecipesQuery {
+    pub fn sort(&self) -> RecipeSort {
+        self.sort.map(Into::into).unwrap_or_default()
+    }
+
+    pub fn page_request(&self, after: Option<RecipeCursor>) -> PageRequest {
+        PageRequest {
+            sort: self.sort(),
+            limit: self.limit.unwrap_or(DEFAULT_PAGE_LIMIT),
+            after,
+        }
+    }
+}
+
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query, rename_all = "camelCase")]
+pub struct SearchRecipesQuery {
+    /// Search terms matched against title, ingredients and instructions.
+    /// Supports "quoted phrases", `or` and `-excluded` words.
+    #[validate(length(max = 200))]
+    pub q: Option<String>,
+    /// Which recipes to search (default: all)
+    #[param(inline)]
+    pub scope: Option<SearchScopeParam>,
+    /// Only recipes whose prep + cook time is at most this many minutes
+    #[validate(range(min = 1))]
+    pub max_total_minutes: Option<i32>,
+    /// Comma-separated ingredients that must all appear, e.g. `chicken,rice`
+    pub include: Option<String>,
+    /// Comma-separated ingredients that must not appear, e.g. `nuts,shrimp`
+    pub exclude: Option<String>,
+    /// Comma-separated tags the recipe must all carry, e.g. `weeknight,vegetarian`
+    pub tag: Option<String>,
+    /// Maximum number of results (default 20)
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    /// Convert ingredient measures and oven temperatures to this unit system
+    #[param(inline)]
+    pub units: Option<UnitSystemParam>,
+}
+
+fn comma_separated(value: Option<String>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+impl SearchRecipesQuery {
+    pub fn into_search(self, user_id: String) -> RecipeSearch {
+        RecipeSearch {
+            user_id,
+            text: self.q.unwrap_or_default(),
+            scope: self.scope.map(Into::into).unwrap_or_default(),
+            max_total_minutes: self.max_total_minutes,
+            include_ingredients: comma_separated(self.include),
+            exclude_ingredients: comma_separated(self.exclude),
+            tags: comma_separated(self.tag),
+            limit: self.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GenerateRecipeQuery {
+    /// Rescale the generated recipe to this many servings
+    #[validate(range(min = 1, max = 100))]
+    pub servings: Option<i32>,
+    /// Convert ingredient measures and oven temperatures to this unit system
+    #[param(inline)]
+    pub units: Option<UnitSystemParam>,
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ScaleRecipeQuery {
+    /// Number of servings to scale the recipe to
+    #[validate(range(min = 1, max = 100))]
+    pub servings: i32,
+    /// Convert ingredient measures and oven temperatures to this unit system
+    #[param(inline)]
+    pub units: Option<UnitSystemParam>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "originalServings": 4,
+    "scaleFactor": 2.5,
+    "unscaledIngredients": ["salt and pepper to taste"]
+}))]
+pub struct ScalingResponse {
+    #[schema(example = 4)]
+    pub original_servings: i32,
+    #[schema(example = 2.5)]
+    pub scale_factor: f64,
+    /// Ingredient lines left unchanged because no quantity could be read from them
+    #[schema(example = json!(["salt and pepper to taste"]))]
+    pub unscaled_ingredients: Vec<String>,
+}
+
+impl From<Scaling> for ScalingResponse {
+    fn from(scaling: Scaling) -> Self {
+        Self {
+            original_servings: scaling.original_servings,
+            scale_factor: scaling.factor,
+            unscaled_ingredients: scaling.unscaled_ingredients,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -39,7 +407,8 @@ pub struct GenerateRecipeRequest {
     "instructions": ["Cook chicken", "Add rice", "Season with garlic"],
     "prepTimeMinutes": 15,
     "cookTimeMinutes": 30,
-    "servings": 4
+    "servings": 4,
+    "allergens": []
 }))]
 pub struct GeneratedRecipeResponse {
     #[schema(example = "Garlic Chicken and Rice")]
@@ -54,17 +423,135 @@ pub struct GeneratedRecipeResponse {
     pub cook_time_minutes: Option<i32>,
     #[schema(example = 4, minimum = 1, maximum = 50)]
     pub servings: Option<i32>,
+    /// Major allergens detected in the ingredients
+    #[schema(example = json!(["dairy"]))]
+    pub allergens: Vec<String>,
+    /// Present when the recipe was rescaled to a requested number of servings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scaling: Option<ScalingResponse>,
+}
+
+fn allergen_names(allergens: Vec<Allergen>) -> Vec<String> {
+    allergens.iter().map(|a| a.as_str().to_string()).collect()
 }
 
 impl From<GeneratedRecipe> for GeneratedRecipeResponse {
     fn from(recipe: GeneratedRecipe) -> Self {
         Self {
+            allergens: allergen_names(recipe.allergens()),
             title: recipe.title,
             ingredients: recipe.ingredients,
             instructions: recipe.instructions,
             prep_time_minutes: recipe.prep_time_minutes,
             cook_time_minutes: recipe.cook_time_minutes,
             servings: recipe.servings,
+            scaling: None,
+        }
+    }
+}
+
+impl GeneratedRecipeResponse {
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = Some(scaling.into());
+        self
+    }
+}
+
+/// Several distinct recipes generated from the same ingredients
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GeneratedRecipesResponse {
+    pub recipes: Vec<GeneratedRecipeResponse>,
+}
+
+/// A single recipe, or a list of them when `count` was given
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum GenerateRecipeResponse {
+    Recipe(GeneratedRecipeResponse),
+    Recipes(GeneratedRecipesResponse),
+}
+
+/// Data of the `title` event sent while a recipe is streamed
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StreamTitleEvent {
+    #[schema(example = "Garlic Chicken and Rice")]
+    pub title: String,
+}
+
+/// Data of the `ingredient` and `instruction` events sent while a recipe is streamed
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StreamItemEvent {
+    /// Position of the item in its list, starting at 0
+    #[schema(example = 0)]
+    pub index: usize,
+    #[schema(example = "2 chicken breasts")]
+    pub text: String,
+}
+
+/// Data of the `error` event that ends a failed stream
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StreamErrorEvent {
+    /// HTTP status the non-streaming endpoint would have responded with
+    #[schema(example = 502)]
+    pub status: u16,
+    #[schema(example = "Failed to reach AI service. Please try again later.")]
+    pub error: String,
+    /// What was wrong with the generated recipe, for a failed validation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violations: Option<Vec<RecipeViolationResponse>>,
+}
+
+/// A problem found in a generated recipe
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecipeViolationResponse {
+    /// Recipe field the problem is in
+    #[schema(example = "ingredients")]
+    pub field: String,
+    #[schema(example = "\"200 g chorizo\" is neither a provided ingredient nor a basic staple")]
+    pub message: String,
+}
+
+impl From<&RecipeViolation> for RecipeViolationResponse {
+    fn from(violation: &RecipeViolation) -> Self {
+        Self {
+            field: violation.field().to_string(),
+            message: violation.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "quantity": 1.5,
+    "unit": "cup",
+    "item": "rice",
+    "preparation": "rinsed"
+}))]
+pub struct ParsedIngredientResponse {
+    /// Amount, or the lower bound when the line gives a range like "2-3"
+    #[schema(example = 1.5)]
+    pub quantity: Option<f64>,
+    /// Upper bound of a range; absent for exact amounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = json!(null))]
+    pub quantity_max: Option<f64>,
+    #[schema(example = "cup")]
+    pub unit: Option<String>,
+    #[schema(example = "rice")]
+    pub item: String,
+    #[schema(example = "rinsed")]
+    pub preparation: Option<String>,
+}
+
+impl From<ParsedIngredient> for ParsedIngredientResponse {
+    fn from(ingredient: ParsedIngredient) -> Self {
+        Self {
+            quantity: ingredient.quantity.map(|q| q.min()),
+            quantity_max: ingredient.quantity.and_then(|q| q.max()),
+            unit: ingredient.unit.map(|u| u.as_str().to_string()),
+            item: ingredient.item,
+            preparation: ingredient.preparation,
         }
     }
 }
@@ -77,11 +564,17 @@ impl From<GeneratedRecipe> for GeneratedRecipeResponse {
     "ownerEmail": "user@example.com",
     "title": "Garlic Chicken and Rice",
     "ingredients": ["2 chicken breasts", "1 cup rice"],
+    "parsedIngredients": [
+        {"quantity": 2, "unit": null, "item": "chicken breasts", "preparation": null},
+        {"quantity": 1, "unit": "cup", "item": "rice", "preparation": null}
+    ],
     "instructions": ["Cook chicken", "Add rice"],
     "prepTimeMinutes": 15,
     "cookTimeMinutes": 30,
     "servings": 4,
-    "createdAt": "2024-01-12T10:00:00Z"
+    "tags": ["weeknight"],
+    "createdAt": "2024-01-12T10:00:00Z",
+    "allergens": []
 }))]
 pub struct RecipeResponse {
     #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
@@ -95,6 +588,8 @@ pub struct RecipeResponse {
     pub title: String,
     #[schema(example = json!(["2 chicken breasts", "1 cup rice"]))]
     pub ingredients: Vec<String>,
+    /// Structured form of each entry in `ingredients`, in the same order
+    pub parsed_ingredients: Vec<ParsedIngredientResponse>,
     #[schema(example = json!(["Cook chicken", "Add rice"]))]
     pub instructions: Vec<String>,
     #[schema(example = 15, minimum = 0, maximum = 300)]
@@ -103,13 +598,27 @@ pub struct RecipeResponse {
     pub cook_time_minutes: Option<i32>,
     #[schema(example = 4, minimum = 1, maximum = 50)]
     pub servings: Option<i32>,
+    #[schema(example = json!(["weeknight"]))]
+    pub tags: Vec<String>,
     #[schema(example = "2024-01-12T10:00:00Z")]
     pub created_at: DateTime<Utc>,
+    /// Major allergens detected in the ingredients
+    #[schema(example = json!(["dairy"]))]
+    pub allergens: Vec<String>,
+    /// Present when the recipe was rescaled to a requested number of servings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scaling: Option<ScalingResponse>,
 }
 
 impl From<Recipe> for RecipeResponse {
     fn from(recipe: Recipe) -> Self {
         Self {
+            allergens: allergen_names(recipe.allergens()),
+            parsed_ingredients: recipe
+                .structured_ingredients()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
             id: recipe.id,
             owner_id: recipe.owner_id,
             owner_email: None,
@@ -119,7 +628,9 @@ impl From<Recipe> for RecipeResponse {
             prep_time_minutes: recipe.prep_time_minutes,
             cook_time_minutes: recipe.cook_time_minutes,
             servings: recipe.servings,
+            tags: recipe.tags,
             created_at: recipe.created_at,
+            scaling: None,
         }
     }
 }
@@ -129,6 +640,19 @@ impl RecipeResponse {
         self.owner_email = email;
         self
     }
+
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = Some(scaling.into());
+        self
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecipePageResponse {
+    pub items: Vec<RecipeResponse>,
+    /// Pass as `cursor` to fetch the next page; null on the last page
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
@@ -172,6 +696,204 @@ impl From<SaveRecipeRequest> for GeneratedRecipe {
     }
 }
 
+/// Longest refinement instruction accepted, in characters.
+const MAX_INSTRUCTION_LENGTH: usize = 500;
+
+fn validate_instruction(instruction: &str) -> Result<(), validator::ValidationError> {
+    if instruction.trim().is_empty() {
+        return Err(validator::ValidationError::new(
+            "instruction_must_not_be_blank",
+        ));
+    }
+    if instruction.chars().count() > MAX_INSTRUCTION_LENGTH {
+        return Err(validator::ValidationError::new("instruction_too_long"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "recipe": {
+        "title": "Garlic Chicken and Rice",
+        "ingredients": ["2 chicken breasts", "1 cup rice", "3 cloves garlic"],
+        "instructions": ["Cook chicken", "Add rice", "Season"],
+        "prepTimeMinutes": 15,
+        "cookTimeMinutes": 30,
+        "servings": 4
+    },
+    "instruction": "make it spicier"
+}))]
+pub struct RefineRecipeRequest {
+    /// The recipe to revise, as returned by generation
+    #[validate(nested)]
+    pub recipe: SaveRecipeRequest,
+    /// What to change, in plain words (at most 500 characters)
+    #[validate(custom(function = "validate_instruction"))]
+    #[schema(example = "make it spicier", max_length = 500)]
+    pub instruction: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({ "instruction": "no oven" }))]
+pub struct RefineSavedRecipeRequest {
+    /// What to change, in plain words (at most 500 characters)
+    #[validate(custom(function = "validate_instruction"))]
+    #[schema(example = "no oven", max_length = 500)]
+    pub instruction: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "title": "Garlic Chicken and Rice",
+    "ingredients": ["2 chicken breasts", "1 cup rice", "3 cloves garlic"],
+    "instructions": ["Cook chicken", "Add rice", "Season"],
+    "prepTimeMinutes": 15,
+    "cookTimeMinutes": 30,
+    "servings": 4
+}))]
+pub struct UpdateRecipeRequest {
+    #[validate(length(min = 1))]
+    #[schema(example = "Garlic Chicken and Rice")]
+    pub title: String,
+    #[validate(custom(function = "validate_ingredients"))]
+    #[schema(example = json!(["2 chicken breasts", "1 cup rice"]))]
+    pub ingredients: Vec<String>,
+    #[validate(length(min = 1))]
+    #[schema(example = json!(["Cook chicken", "Add rice"]))]
+    pub instructions: Vec<String>,
+    #[validate(range(min = 0, max = 300))]
+    #[schema(example = 15, minimum = 0, maximum = 300)]
+    pub prep_time_minutes: Option<i32>,
+    #[validate(range(min = 0, max = 600))]
+    #[schema(example = 30, minimum = 0, maximum = 600)]
+    pub cook_time_minutes: Option<i32>,
+    #[validate(range(min = 1, max = 50))]
+    #[schema(example = 4, minimum = 1, maximum = 50)]
+    pub servings: Option<i32>,
+}
+
+impl From<UpdateRecipeRequest> for GeneratedRecipe {
+    fn from(request: UpdateRecipeRequest) -> Self {
+        Self {
+            title: request.title,
+            ingredients: request.ingredients,
+            instructions: request.instructions,
+            prep_time_minutes: request.prep_time_minutes,
+            cook_time_minutes: request.cook_time_minutes,
+            servings: request.servings,
+        }
+    }
+}
+
+const MAX_TAGS: usize = 20;
+const MAX_TAG_LENGTH: usize = 40;
+
+fn validate_tags(tags: &[String]) -> Result<(), validator::ValidationError> {
+    if tags.len() > MAX_TAGS {
+        return Err(validator::ValidationError::new("too_many_tags"));
+    }
+    if tags
+        .iter()
+        .any(|t| t.trim().chars().count() > MAX_TAG_LENGTH)
+    {
+        return Err(validator::ValidationError::new("tag_too_long"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "tags": ["weeknight", "vegetarian"]
+}))]
+pub struct SetTagsRequest {
+    /// Replaces the recipe's tags. Tags are trimmed, lowercased and deduplicated.
+    #[validate(custom(function = "validate_tags"))]
+    #[schema(example = json!(["weeknight", "vegetarian"]))]
+    pub tags: Vec<String>,
+}
+
+fn validate_collection_name(name: &str) -> Result<(), validator::ValidationError> {
+    if name.trim().is_empty() {
+        return Err(validator::ValidationError::new("blank_name"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "name": "Weeknight"
+}))]
+pub struct CollectionRequest {
+    #[validate(length(max = 100), custom(function = "validate_collection_name"))]
+    #[schema(example = "Weeknight")]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "recipeId": "123e4567-e89b-12d3-a456-426614174000"
+}))]
+pub struct AddCollectionRecipeRequest {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub recipe_id: Uuid,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "id": "0f8fad5b-d9cb-469f-a165-70867728950e",
+    "ownerId": "user_123",
+    "name": "Weeknight",
+    "createdAt": "2024-01-12T10:00:00Z"
+}))]
+pub struct CollectionResponse {
+    #[schema(example = "0f8fad5b-d9cb-469f-a165-70867728950e")]
+    pub id: Uuid,
+    #[schema(example = "user_123")]
+    pub owner_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "user@example.com")]
+    pub owner_email: Option<String>,
+    #[schema(example = "Weeknight")]
+    pub name: String,
+    #[schema(example = "2024-01-12T10:00:00Z")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Collection> for CollectionResponse {
+    fn from(collection: Collection) -> Self {
+        Self {
+            id: collection.id,
+            owner_id: collection.owner_id,
+            owner_email: None,
+            name: collection.name,
+            created_at: collection.created_at,
+        }
+    }
+}
+
+impl CollectionResponse {
+    pub fn with_owner_email(mut self, email: Option<String>) -> Self {
+        self.owner_email = email;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionDetailResponse {
+    #[serde(flatten)]
+    pub collection: CollectionResponse,
+    /// The recipes in the collection that the caller can see, most recently added first
+    pub recipes: Vec<RecipeResponse>,
+}
+
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 #[schema(example = json!({
@@ -198,3 +920,531 @@ pub struct ShareResponse {
     #[schema(example = "2024-01-12T10:00:00Z")]
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "revision": 2,
+    "editorId": "user_123",
+    "title": "Garlic Chicken and Rice",
+    "createdAt": "2024-01-13T09:30:00Z"
+}))]
+pub struct RecipeRevisionSummaryResponse {
+    #[schema(example = 2)]
+    pub revision: i32,
+    #[schema(example = "user_123")]
+    pub editor_id: String,
+    #[schema(example = "Garlic Chicken and Rice")]
+    pub title: String,
+    #[schema(example = "2024-01-13T09:30:00Z")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RecipeRevision> for RecipeRevisionSummaryResponse {
+    fn from(revision: RecipeRevision) -> Self {
+        Self {
+            revision: revision.revision,
+            editor_id: revision.editor_id,
+            title: revision.title,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "recipeId": "123e4567-e89b-12d3-a456-426614174000",
+    "revision": 2,
+    "editorId": "user_123",
+    "title": "Garlic Chicken and Rice",
+    "ingredients": ["2 chicken breasts", "1 cup rice"],
+    "instructions": ["Cook chicken", "Add rice"],
+    "prepTimeMinutes": 15,
+    "cookTimeMinutes": 30,
+    "servings": 4,
+    "createdAt": "2024-01-13T09:30:00Z"
+}))]
+pub struct RecipeRevisionResponse {
+    #[schema(example = "123e4567-e89b-12d3-a456-426614174000")]
+    pub recipe_id: Uuid,
+    #[schema(example = 2)]
+    pub revision: i32,
+    #[schema(example = "user_123")]
+    pub editor_id: String,
+    #[schema(example = "Garlic Chicken and Rice")]
+    pub title: String,
+    #[schema(example = json!(["2 chicken breasts", "1 cup rice"]))]
+    pub ingredients: Vec<String>,
+    #[schema(example = json!(["Cook chicken", "Add rice"]))]
+    pub instructions: Vec<String>,
+    #[schema(example = 15, minimum = 0, maximum = 300)]
+    pub prep_time_minutes: Option<i32>,
+    #[schema(example = 30, minimum = 0, maximum = 600)]
+    pub cook_time_minutes: Option<i32>,
+    #[schema(example = 4, minimum = 1, maximum = 50)]
+    pub servings: Option<i32>,
+    #[schema(example = "2024-01-13T09:30:00Z")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RecipeRevision> for RecipeRevisionResponse {
+    fn from(revision: RecipeRevision) -> Self {
+        Self {
+            recipe_id: revision.recipe_id,
+            revision: revision.revision,
+            editor_id: revision.editor_id,
+            title: revision.title,
+            ingredients: revision.ingredients,
+            instructions: revision.instructions,
+            prep_time_minutes: revision.prep_time_minutes,
+            cook_time_minutes: revision.cook_time_minutes,
+            servings: revision.servings,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecipeDiffQuery {
+    /// Revision to compare from (the older one)
+    #[validate(range(min = 1))]
+    pub from: i32,
+    /// Revision to compare to (the newer one)
+    #[validate(range(min = 1))]
+    pub to: i32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TextChangeResponse {
+    #[schema(example = "Garlic Chiken")]
+    pub from: String,
+    #[schema(example = "Garlic Chicken")]
+    pub to: String,
+}
+
+impl From<FieldChange<String>> for TextChangeResponse {
+    fn from(change: FieldChange<String>) -> Self {
+        Self {
+            from: change.from,
+            to: change.to,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NumberChangeResponse {
+    #[schema(example = 4)]
+    pub from: Option<i32>,
+    #[schema(example = 6)]
+    pub to: Option<i32>,
+}
+
+impl From<FieldChange<Option<i32>>> for NumberChangeResponse {
+    fn from(change: FieldChange<Option<i32>>) -> Self {
+        Self {
+            from: change.from,
+            to: change.to,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LineChangeKindResponse {
+    Added,
+    Removed,
+    Unchanged,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LineChangeResponse {
+    pub change: LineChangeKindResponse,
+    #[schema(example = "1 cup rice")]
+    pub line: String,
+}
+
+impl From<LineChange> for LineChangeResponse {
+    fn from(change: LineChange) -> Self {
+        let kind = match change.kind {
+            LineChangeKind::Added => LineChangeKindResponse::Added,
+            LineChangeKind::Removed => LineChangeKindResponse::Removed,
+            LineChangeKind::Unchanged => LineChangeKindResponse::Unchanged,
+        };
+        Self {
+            change: kind,
+            line: change.line,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "fromRevision": 1,
+    "toRevision": 2,
+    "hasChanges": true,
+    "title": {"from": "Garlic Chiken", "to": "Garlic Chicken"},
+    "ingredients": [
+        {"change": "unchanged", "line": "2 chicken breasts"},
+        {"change": "removed", "line": "1 cup rice"},
+        {"change": "added", "line": "1 1/2 cups rice"}
+    ],
+    "instructions": [{"change": "unchanged", "line": "Cook chicken"}],
+    "servings": {"from": 4, "to": 6}
+}))]
+pub struct RecipeDiffResponse {
+    #[schema(example = 1)]
+    pub from_revision: i32,
+    #[schema(example = 2)]
+    pub to_revision: i32,
+    #[schema(example = true)]
+    pub has_changes: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<TextChangeResponse>,
+    pub ingredients: Vec<LineChangeResponse>,
+    pub instructions: Vec<LineChangeResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prep_time_minutes: Option<NumberChangeResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cook_time_minutes: Option<NumberChangeResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servings: Option<NumberChangeResponse>,
+}
+
+impl From<RecipeDiff> for RecipeDiffResponse {
+    fn from(diff: RecipeDiff) -> Self {
+        Self {
+            from_revision: diff.from_revision,
+            to_revision: diff.to_revision,
+            has_changes: diff.has_changes(),
+            title: diff.title.map(Into::into),
+            ingredients: diff.ingredients.into_iter().map(Into::into).collect(),
+            instructions: diff.instructions.into_iter().map(Into::into).collect(),
+            prep_time_minutes: diff.prep_time_minutes.map(Into::into),
+            cook_time_minutes: diff.cook_time_minutes.map(Into::into),
+            servings: diff.servings.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NutritionFactsResponse {
+    #[schema(example = 512.0)]
+    pub calories: f64,
+    #[schema(example = 38.5)]
+    pub protein_g: f64,
+    #[schema(example = 14.2)]
+    pub fat_g: f64,
+    #[schema(example = 55.0)]
+    pub carbohydrates_g: f64,
+    #[schema(example = 3.1)]
+    pub fibre_g: f64,
+    #[schema(example = 620.0)]
+    pub sodium_mg: f64,
+}
+
+fn one_decimal(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+impl From<NutritionFacts> for NutritionFactsResponse {
+    fn from(facts: NutritionFacts) -> Self {
+        Self {
+            calories: facts.calories.round(),
+            protein_g: one_decimal(facts.protein_g),
+            fat_g: one_decimal(facts.fat_g),
+            carbohydrates_g: one_decimal(facts.carbohydrates_g),
+            fibre_g: one_decimal(facts.fibre_g),
+            sodium_mg: facts.sodium_mg.round(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NutritionResponse {
+    pub recipe_id: Uuid,
+    #[schema(example = 4)]
+    pub servings: Option<i32>,
+    /// Values per serving; omitted when the recipe has no serving count
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_serving: Option<NutritionFactsResponse>,
+    pub total: NutritionFactsResponse,
+    /// Ingredient lines that could not be matched to the nutrient table and are
+    /// not included in the totals
+    #[schema(example = json!(["salt and pepper to taste"]))]
+    pub unmatched_ingredients: Vec<String>,
+}
+
+impl NutritionResponse {
+    pub fn new(recipe_id: Uuid, report: NutritionReport) -> Self {
+        Self {
+            recipe_id,
+            servings: report.servings,
+            per_serving: report.per_serving.map(Into::into),
+            total: report.total.into(),
+            unmatched_ingredients: report.unmatched_ingredients,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SubstitutionsQuery {
+    /// Ingredient to replace, e.g. `buttermilk`; matched against the recipe's ingredient lines
+    #[validate(length(min = 1, max = 100))]
+    pub ingredient: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SubstituteSourceResponse {
+    Curated,
+    Ai,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubstituteResponse {
+    /// Ingredient lines to use instead, sized to the amount being replaced
+    #[schema(example = json!(["1 cup milk", "1 tbsp lemon juice"]))]
+    pub ingredients: Vec<String>,
+    #[schema(example = "Let it stand for 5 minutes before using.")]
+    pub note: Option<String>,
+    /// `curated` for the built-in substitution table, `ai` for AI suggestions
+    pub source: SubstituteSourceResponse,
+    /// Ways the substitute breaks the recipe's dietary restrictions; empty when it fits them
+    #[schema(example = json!([]))]
+    pub violations: Vec<String>,
+}
+
+impl From<Substitute> for SubstituteResponse {
+    fn from(substitute: Substitute) -> Self {
+        let source = match substitute.source {
+            SubstituteSource::Curated => SubstituteSourceResponse::Curated,
+            SubstituteSource::Ai => SubstituteSourceResponse::Ai,
+        };
+        Self {
+            ingredients: substitute.ingredients,
+            note: substitute.note,
+            source,
+            violations: substitute
+                .violations
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubstitutionsResponse {
+    pub recipe_id: Uuid,
+    /// The recipe's ingredient line being replaced
+    #[schema(example = "1 cup buttermilk")]
+    pub ingredient: String,
+    /// Dietary restrictions taken from the recipe's tags that the substitutes were checked against
+    #[schema(example = json!(["vegetarian"]))]
+    pub restrictions: Vec<String>,
+    pub substitutes: Vec<SubstituteResponse>,
+}
+
+impl SubstitutionsResponse {
+    pub fn new(recipe_id: Uuid, substitutions: Substitutions) -> Self {
+        Self {
+            recipe_id,
+            ingredient: substitutions.ingredient,
+            restrictions: substitutions
+                .restrictions
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            substitutes: substitutions
+                .substitutes
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+/// Consumption in one quota period.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsagePeriodResponse {
+    /// Requests that called the AI; answers from the cache are not counted
+    #[schema(example = 3)]
+    pub generations: i64,
+    #[schema(example = 1250)]
+    pub prompt_tokens: i64,
+    #[schema(example = 2100)]
+    pub completion_tokens: i64,
+    #[schema(example = 3350)]
+    pub total_tokens: i64,
+    /// Estimated provider cost in US dollars, from the configured token prices
+    #[schema(example = 0.0014475)]
+    pub estimated_cost_usd: f64,
+    /// Generations allowed in the period; absent when unlimited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 50)]
+    pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 47)]
+    pub remaining: Option<i64>,
+    /// When the period ends and its quota starts over
+    pub resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageResponse {
+    /// The current UTC day
+    pub today: UsagePeriodResponse,
+    /// The current UTC month
+    pub this_month: UsagePeriodResponse,
+}
+
+impl UsageResponse {
+    pub fn new(summary: &UsageSummary, now: DateTime<Utc>) -> Self {
+        let period = |totals: &UsageTotals, limit, remaining, resets_at| UsagePeriodResponse {
+            generations: totals.generations,
+            prompt_tokens: totals.tokens.prompt_tokens,
+            completion_tokens: totals.tokens.completion_tokens,
+            total_tokens: totals.tokens.total_tokens(),
+            estimated_cost_usd: summary.prices.cost(&totals.tokens),
+            limit,
+            remaining,
+            resets_at,
+        };
+        let tomorrow = day_start(now) + chrono::Duration::days(1);
+        let next_month = month_start(month_start(now) + chrono::Duration::days(32));
+
+        Self {
+            today: period(
+                &summary.today,
+                summary.limits.daily,
+                summary.remaining_today(),
+                tomorrow,
+            ),
+            this_month: period(
+                &summary.this_month,
+                summary.limits.monthly,
+                summary.remaining_this_month(),
+                next_month,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListGenerationsQuery {
+    /// Maximum number of generations per page (default 20)
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    /// `nextCursor` from the previous page; omit for the first page
+    #[validate(length(max = 512))]
+    pub cursor: Option<String>,
+}
+
+impl ListGenerationsQuery {
+    pub fn page_request(&self, after: Option<RecipeCursor>) -> PageRequest {
+        PageRequest {
+            sort: RecipeSort::CreatedAt,
+            limit: self.limit.unwrap_or(DEFAULT_PAGE_LIMIT),
+            after,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationConstraintsResponse {
+    pub cuisine: Option<String>,
+    pub max_total_minutes: Option<i32>,
+    #[schema(example = json!(["no_oven"]))]
+    pub equipment: Vec<String>,
+    #[schema(example = "easy")]
+    pub difficulty: Option<String>,
+    pub servings: Option<i32>,
+    pub excluded_ingredients: Vec<String>,
+}
+
+impl From<RecipeConstraints> for GenerationConstraintsResponse {
+    fn from(constraints: RecipeConstraints) -> Self {
+        Self {
+            cuisine: constraints.cuisine,
+            max_total_minutes: constraints.max_total_minutes,
+            equipment: constraints
+                .equipment
+                .iter()
+                .map(|e| e.as_str().to_string())
+                .collect(),
+            difficulty: constraints.difficulty.map(|d| d.as_str().to_string()),
+            servings: constraints.servings,
+            excluded_ingredients: constraints.excluded_ingredients,
+        }
+    }
+}
+
+/// One past generation request and the recipes it produced.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationResponse {
+    pub id: Uuid,
+    #[schema(example = json!(["chicken breast", "rice", "garlic"]))]
+    pub ingredients: Vec<String>,
+    pub dietary_restrictions: Option<Vec<String>>,
+    pub constraints: GenerationConstraintsResponse,
+    /// Provider and model that wrote the recipes; null when they were served
+    /// from the generation cache
+    #[schema(example = "openai/gpt-4o-mini")]
+    pub model: Option<String>,
+    #[schema(example = "1")]
+    pub prompt_version: Option<String>,
+    /// The model's reply before it was parsed; null when served from the cache
+    pub raw_output: Option<String>,
+    /// The recipes generated, as first returned; save one by its index
+    pub recipes: Vec<GeneratedRecipeResponse>,
+    /// How long the generation took
+    #[schema(example = 2350)]
+    pub latency_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Generation> for GenerationResponse {
+    fn from(generation: Generation) -> Self {
+        Self {
+            id: generation.id,
+            ingredients: generation.ingredients,
+            dietary_restrictions: generation.dietary_restrictions,
+            constraints: generation.constraints.into(),
+            model: generation.model,
+            prompt_version: generation.prompt_version,
+            raw_output: generation.raw_output,
+            recipes: generation.recipes.into_iter().map(Into::into).collect(),
+            latency_ms: generation.latency_ms,
+            created_at: generation.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationPageResponse {
+    pub items: Vec<GenerationResponse>,
+    /// Pass as `cursor` to fetch the next page; null on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SaveGenerationQuery {
+    /// Which of the generation's recipes to save, counting from 0 (default 0)
+    #[validate(range(max = 99))]
+    pub index: Option<usize>,
+}
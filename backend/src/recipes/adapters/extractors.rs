@@ -1,12 +1,16 @@
 use axum::{
     Json,
-    extract::{FromRequest, Request, rejection::JsonRejection},
-    http::StatusCode,
+    extract::{FromRequest, FromRequestParts, Query, Request, rejection::JsonRejection},
+    http::{StatusCode, request::Parts},
 };
 use serde::de::DeserializeOwned;
+use std::convert::Infallible;
 use validator::Validate;
 
 use super::handlers::ErrorResponse;
+use crate::recipes::domain::Requester;
+use crate::shared::auth::AuthenticatedUser;
+use crate::shared::middleware::client_ip::client_ip;
 
 pub struct ValidatedJson<T>(pub T);
 
@@ -24,6 +28,7 @@ where
                 StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
                     error: e.to_string(),
+                    violations: None,
                 }),
             )
         })?;
@@ -33,6 +38,7 @@ where
                 StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
                     error: e.to_string(),
+                    violations: None,
                 }),
             )
         })?;
@@ -40,3 +46,59 @@ where
         Ok(ValidatedJson(value))
     }
 }
+
+pub struct ValidatedQuery<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ValidatedQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = (StatusCode, Json<ErrorResponse>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: e.to_string(),
+                        violations: None,
+                    }),
+                )
+            })?;
+
+        value.validate().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                    violations: None,
+                }),
+            )
+        })?;
+
+        Ok(ValidatedQuery(value))
+    }
+}
+
+/// The signed-in user when there is one, otherwise an anonymous visitor
+/// identified by their IP address. Without a recorded address, which only
+/// happens when serving without connection info, visitors share one quota.
+impl<S> FromRequestParts<S> for Requester
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Ok(user) = AuthenticatedUser::from_request_parts(parts, state).await {
+            return Ok(Requester::User(user.user_id));
+        }
+
+        Ok(Requester::Anonymous(
+            client_ip(&parts.extensions).map_or_else(|| "unknown".to_string(), |ip| ip.to_string()),
+        ))
+    }
+}
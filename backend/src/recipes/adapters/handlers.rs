@@ -1,32 +1,58 @@
 use axum::{
     Json,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::sse::{Event, KeepAlive, Sse},
 };
+use chrono::Utc;
 use serde::Serialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::{Stream, StreamExt};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::recipes::application::{GetUsageUseCase, RecordGenerationUseCase, RecordUsageUseCase};
 use crate::recipes::domain::{
-    LlmError, LlmService, Recipe, RecipeRepository, RecipeShareRepository, RepositoryError,
+    CollectionRepository, GeneratedRecipe, Generation, GenerationCache, GenerationRepository,
+    LlmError, LlmService, Recipe, RecipeConstraints, RecipeCursor, RecipeRepository,
+    RecipeShareRepository, RecipeSort, RecipeStreamEvent, RepositoryError, Requester, ScalingError,
+    UnitSystem, UsageMeter, UsageRepository, UsageSummary,
 };
 use crate::shared::auth::AuthenticatedUser;
 
+use super::cursor::CursorCodec;
 use super::dto::{
-    CreateShareRequest, GenerateRecipeRequest, GeneratedRecipeResponse, RecipeResponse,
-    SaveRecipeRequest, ShareResponse,
+    AddCollectionRecipeRequest, CollectionDetailResponse, CollectionRequest, CollectionResponse,
+    CreateShareRequest, GenerateRecipeQuery, GenerateRecipeRequest, GenerateRecipeResponse,
+    GeneratedRecipeResponse, GeneratedRecipesResponse, GenerationPageResponse,
+    ListGenerationsQuery, ListRecipesQuery, NutritionResponse, RecipeDiffQuery, RecipeDiffResponse,
+    RecipePageResponse, RecipeResponse, RecipeRevisionResponse, RecipeRevisionSummaryResponse,
+    RecipeViolationResponse, RefineRecipeRequest, RefineSavedRecipeRequest, SaveGenerationQuery,
+    SaveRecipeRequest, ScaleRecipeQuery, SearchRecipesQuery, SetTagsRequest, ShareResponse,
+    StreamErrorEvent, StreamItemEvent, StreamTitleEvent, SubstitutionsQuery, SubstitutionsResponse,
+    UnitSystemParam, UnitsQuery, UpdateRecipeRequest, UsageResponse,
 };
-use super::extractors::ValidatedJson;
+use super::extractors::{ValidatedJson, ValidatedQuery};
 use super::state::AppState;
 
 #[derive(Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
+    /// What was wrong with a generated recipe, when that is why the request failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub violations: Option<Vec<RecipeViolationResponse>>,
 }
 
 fn map_repo_error(e: RepositoryError) -> (StatusCode, Json<ErrorResponse>) {
     let (status, message) = match e {
         RepositoryError::NotFound => (StatusCode::NOT_FOUND, "Recipe not found"),
+        RepositoryError::RevisionNotFound => (StatusCode::NOT_FOUND, "Revision not found"),
+        RepositoryError::CollectionNotFound => (StatusCode::NOT_FOUND, "Collection not found"),
+        RepositoryError::GenerationNotFound => (StatusCode::NOT_FOUND, "Generation not found"),
         RepositoryError::AccessDenied => (StatusCode::FORBIDDEN, "Access denied"),
         RepositoryError::DatabaseError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
     };
@@ -34,10 +60,47 @@ fn map_repo_error(e: RepositoryError) -> (StatusCode, Json<ErrorResponse>) {
         status,
         Json(ErrorResponse {
             error: message.to_string(),
+            violations: None,
         }),
     )
 }
 
+/// Cursor tokens are bound to the list they page through.
+const OWNED_LIST: &str = "owned";
+const SHARED_LIST: &str = "shared";
+const GENERATIONS_LIST: &str = "generations";
+
+fn decode_cursor(
+    codec: &CursorCodec,
+    cursor: Option<&str>,
+    sort: RecipeSort,
+    list: &str,
+    user_id: &str,
+) -> Result<Option<RecipeCursor>, (StatusCode, Json<ErrorResponse>)> {
+    let Some(token) = cursor else {
+        return Ok(None);
+    };
+
+    codec
+        .decode(token, list, user_id, sort)
+        .map(Some)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "Invalid cursor".to_string(),
+                    violations: None,
+                }),
+            )
+        })
+}
+
+impl From<LlmError> for (StatusCode, Json<ErrorResponse>) {
+    fn from(e: LlmError) -> Self {
+        map_llm_error(e)
+    }
+}
+
 fn map_llm_error(e: LlmError) -> (StatusCode, Json<ErrorResponse>) {
     let (status, user_message, log_message) = match &e {
         LlmError::ApiError(msg) => (
@@ -50,22 +113,278 @@ fn map_llm_error(e: LlmError) -> (StatusCode, Json<ErrorResponse>) {
             "Failed to process AI response. Please try again.",
             format!("AI response parse error: {}", msg),
         ),
+        LlmError::Timeout(msg) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            "The AI service took too long to respond. Please try again.",
+            format!("AI API timeout: {}", msg),
+        ),
+        LlmError::RateLimited(msg) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            "The AI service is busy. Please try again in a moment.",
+            format!("AI API rate limited: {}", msg),
+        ),
+        LlmError::Unavailable(msg) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The AI service is unavailable. Please try again later.",
+            format!("AI API unavailable: {}", msg),
+        ),
+        LlmError::DietaryViolation(msg) => {
+            eprintln!(
+                "Error: Generated recipe violated dietary restrictions: {}",
+                msg
+            );
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Could not generate a recipe that meets the dietary restrictions: {}",
+                        msg
+                    ),
+                    violations: None,
+                }),
+            );
+        }
+        LlmError::InvalidRecipe(violations) => {
+            let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+            eprintln!(
+                "Error: Generated recipe failed validation: {}",
+                details.join("; ")
+            );
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: "Could not generate a valid recipe from these ingredients".to_string(),
+                    violations: Some(violations.iter().map(Into::into).collect()),
+                }),
+            );
+        }
     };
     eprintln!("Error: {}", log_message);
     (
         status,
         Json(ErrorResponse {
             error: user_message.to_string(),
+            violations: None,
+        }),
+    )
+}
+
+fn map_scaling_error(e: ScalingError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        ScalingError::UnknownServings => StatusCode::UNPROCESSABLE_ENTITY,
+        ScalingError::InvalidServings => StatusCode::BAD_REQUEST,
+    };
+    (
+        status,
+        Json(ErrorResponse {
+            error: e.to_string(),
+            violations: None,
         }),
     )
 }
 
+/// Recipes whose measures can be given in another unit system.
+trait ConvertUnits: Sized {
+    fn convert_units(&self, system: UnitSystem) -> Self;
+}
+
+impl ConvertUnits for Recipe {
+    fn convert_units(&self, system: UnitSystem) -> Self {
+        Recipe::convert_units(self, system)
+    }
+}
+
+impl ConvertUnits for GeneratedRecipe {
+    fn convert_units(&self, system: UnitSystem) -> Self {
+        GeneratedRecipe::convert_units(self, system)
+    }
+}
+
+fn in_units<T: ConvertUnits>(recipe: T, units: Option<UnitSystemParam>) -> T {
+    match units {
+        Some(units) => recipe.convert_units(units.into()),
+        None => recipe,
+    }
+}
+
+/// Looks up what `requester` has used so far and reserves the generation
+/// about to be made, refusing with 429 once either of their generation quotas
+/// is used up.
+async fn check_quota<U: UsageRepository>(
+    usage_use_case: &GetUsageUseCase<U>,
+    record_usage_use_case: &RecordUsageUseCase<U>,
+    requester: &Requester,
+) -> Result<(UsageSummary, Uuid), (StatusCode, Json<ErrorResponse>)> {
+    let mut summary = usage_use_case
+        .execute(requester)
+        .await
+        .map_err(map_repo_error)?;
+    let reservation = reserve_generation(
+        usage_use_case,
+        record_usage_use_case,
+        requester,
+        &mut summary,
+    )
+    .await?;
+    Ok((summary, reservation))
+}
+
+/// Reserves a generation for `requester`, whose usage so far is `summary`,
+/// right before the model is called, refusing with 429 once either of their
+/// generation quotas is used up. Requests that may be answered from the cache
+/// or the curated tables call this only once they know they need the model.
+async fn reserve_generation<U: UsageRepository>(
+    usage_use_case: &GetUsageUseCase<U>,
+    record_usage_use_case: &RecordUsageUseCase<U>,
+    requester: &Requester,
+    summary: &mut UsageSummary,
+) -> Result<Uuid, (StatusCode, Json<ErrorResponse>)> {
+    if !summary.exhausted() {
+        match record_usage_use_case
+            .reserve(requester)
+            .await
+            .map_err(map_repo_error)?
+        {
+            Some(reservation) => return Ok(reservation),
+            // Concurrent requests took what was left since it was looked up.
+            None => {
+                *summary = usage_use_case
+                    .execute(requester)
+                    .await
+                    .map_err(map_repo_error)?
+            }
+        }
+    }
+
+    let period = if summary.remaining_today() == Some(0) {
+        "daily"
+    } else {
+        "monthly"
+    };
+    let hint = match requester {
+        Requester::User(_) => "",
+        Requester::Anonymous(_) => " Sign in for a larger quota.",
+    };
+    Err((
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(ErrorResponse {
+            error: format!("You have used up your {} generation quota.{}", period, hint),
+            violations: None,
+        }),
+    ))
+}
+
+/// Settles the reserved generation, if one was made, with the usage metered
+/// by `usage`, or gives it back if the model was not called, and returns
+/// headers with the quota left afterwards. The generation has already
+/// happened, so failing to record it is logged rather than failing the
+/// request.
+async fn record_usage<U: UsageRepository>(
+    use_case: &RecordUsageUseCase<U>,
+    reservation: Option<Uuid>,
+    mut summary: UsageSummary,
+    usage: &UsageMeter,
+) -> HeaderMap {
+    if let Some(reservation) = reservation {
+        match use_case.execute(reservation, usage).await {
+            Ok(true) => summary.add_generation(usage.usage()),
+            Ok(false) => {}
+            Err(e) => eprintln!("Error: Failed to record generation usage: {}", e),
+        }
+    }
+    quota_headers(&summary)
+}
+
+const QUOTA_DAILY_LIMIT: HeaderName = HeaderName::from_static("x-quota-daily-limit");
+const QUOTA_DAILY_REMAINING: HeaderName = HeaderName::from_static("x-quota-daily-remaining");
+const QUOTA_MONTHLY_LIMIT: HeaderName = HeaderName::from_static("x-quota-monthly-limit");
+const QUOTA_MONTHLY_REMAINING: HeaderName = HeaderName::from_static("x-quota-monthly-remaining");
+
+/// The limit and remaining count of each quota that applies.
+fn quota_headers(summary: &UsageSummary) -> HeaderMap {
+    let quotas = [
+        (
+            QUOTA_DAILY_LIMIT,
+            QUOTA_DAILY_REMAINING,
+            summary.limits.daily,
+            summary.remaining_today(),
+        ),
+        (
+            QUOTA_MONTHLY_LIMIT,
+            QUOTA_MONTHLY_REMAINING,
+            summary.limits.monthly,
+            summary.remaining_this_month(),
+        ),
+    ];
+
+    let mut headers = HeaderMap::new();
+    for (limit_header, remaining_header, limit, remaining) in quotas {
+        if let (Some(limit), Some(remaining)) = (limit, remaining) {
+            headers.insert(limit_header, HeaderValue::from(limit));
+            headers.insert(remaining_header, HeaderValue::from(remaining));
+        }
+    }
+    headers
+}
+
+/// Keeps a signed-in user's successful generation in their history and
+/// returns its id. Anonymous generations are not kept. Like usage, failing to
+/// record it is only logged.
+async fn record_generation<H: GenerationRepository>(
+    use_case: &RecordGenerationUseCase<H>,
+    requester: &Requester,
+    request: GenerationRequest,
+    recipes: &[GeneratedRecipe],
+    usage: &UsageMeter,
+    started: Instant,
+) -> Option<Uuid> {
+    let Requester::User(user_id) = requester else {
+        return None;
+    };
+    let generation = Generation::new(
+        user_id.clone(),
+        request.ingredients,
+        request.dietary_restrictions,
+        request.constraints,
+        recipes.to_vec(),
+        usage.output(),
+        started.elapsed(),
+    );
+    match use_case.execute(&generation).await {
+        Ok(()) => Some(generation.id),
+        Err(e) => {
+            eprintln!("Error: Failed to record generation: {}", e);
+            None
+        }
+    }
+}
+
+/// What a generation was asked for, kept for its history entry.
+struct GenerationRequest {
+    ingredients: Vec<String>,
+    dietary_restrictions: Option<Vec<String>>,
+    constraints: RecipeConstraints,
+}
+
+impl From<&GenerateRecipeRequest> for GenerationRequest {
+    fn from(request: &GenerateRecipeRequest) -> Self {
+        Self {
+            ingredients: request.ingredients.clone(),
+            dietary_restrictions: request.dietary_restrictions.clone(),
+            constraints: request.constraints(),
+        }
+    }
+}
+
+const GENERATION_ID: HeaderName = HeaderName::from_static("x-generation-id");
+
 fn map_auth_lookup_error(e: String) -> (StatusCode, Json<ErrorResponse>) {
     eprintln!("Failed to lookup user by email: {}", e);
     (
         StatusCode::INTERNAL_SERVER_ERROR,
         Json(ErrorResponse {
             error: "Failed to lookup user".to_string(),
+            violations: None,
         }),
     )
 }
@@ -74,159 +393,1624 @@ fn map_auth_lookup_error(e: String) -> (StatusCode, Json<ErrorResponse>) {
     post,
     path = "/api/recipes/generate",
     summary = "Generate a recipe using AI",
-    description = "Generates a recipe based on provided ingredients and optional dietary restrictions. Uses AI to create a complete recipe with instructions, prep time, cook time, and serving size.",
+    description = "Generates a recipe based on provided ingredients and optional dietary restrictions. Uses AI to create a complete recipe with instructions, prep time, cook time, and serving size. Pass `servings` to rescale the generated ingredient quantities and `units` to convert measures and oven temperatures to metric or imperial. Pass `count` to get several distinct recipes at once as `{\"recipes\": [...]}`; the count is capped by the server configuration, and near-duplicate recipes are dropped. When the generation cache is enabled, an identical request made recently is answered from the cache; send `Cache-Control: no-cache` to generate afresh. Each request that calls the AI counts against the caller's daily and monthly generation quotas, which are stricter for anonymous callers; the `X-Quota-*` response headers report what is left. For signed-in users the generation is kept in their history (see `GET /api/generations`), and its id is returned in `X-Generation-Id`.",
+    params(
+        GenerateRecipeQuery,
+        ("Cache-Control" = Option<String>, Header, description = "`no-cache` skips cached recipes and generates new ones")
+    ),
     request_body = GenerateRecipeRequest,
     responses(
-        (status = 200, description = "Recipe generated successfully", body = GeneratedRecipeResponse),
+        (status = 200, description = "Recipe generated successfully; a list of recipes when `count` was given", body = GenerateRecipeResponse, headers(
+            ("X-Quota-Daily-Limit" = i64, description = "Generations allowed per UTC day; absent when unlimited"),
+            ("X-Quota-Daily-Remaining" = i64, description = "Generations left today"),
+            ("X-Quota-Monthly-Limit" = i64, description = "Generations allowed per UTC month; absent when unlimited"),
+            ("X-Quota-Monthly-Remaining" = i64, description = "Generations left this month"),
+            ("X-Generation-Id" = Uuid, description = "The generation's id in the signed-in user's history; absent for anonymous callers"),
+        )),
         (status = 400, description = "Invalid request - ingredients list is empty or invalid", body = ErrorResponse),
+        (status = 422, description = "Generated recipe has no serving count to scale from, or no valid recipe meeting the dietary restrictions could be generated; `violations` lists what was wrong", body = ErrorResponse),
+        (status = 429, description = "AI service rate limit reached, or the caller's daily or monthly generation quota is used up", body = ErrorResponse),
         (status = 502, description = "AI service error - failed to reach or process AI service", body = ErrorResponse),
+        (status = 503, description = "AI service unavailable, or failing repeatedly and temporarily not called", body = ErrorResponse),
+        (status = 504, description = "AI service did not respond in time", body = ErrorResponse),
+    ),
+    tag = "Recipes"
+)]
+pub async fn generate_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    requester: Requester,
+    ValidatedQuery(query): ValidatedQuery<GenerateRecipeQuery>,
+    headers: HeaderMap,
+    ValidatedJson(request): ValidatedJson<GenerateRecipeRequest>,
+) -> Result<(HeaderMap, Json<GenerateRecipeResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let mut summary = state
+        .usage_use_case
+        .execute(&requester)
+        .await
+        .map_err(map_repo_error)?;
+    let mut reservation = None;
+    let reserve = async || {
+        reserve_generation(
+            &state.usage_use_case,
+            &state.record_usage_use_case,
+            &requester,
+            &mut summary,
+        )
+        .await
+        .map(|id| reservation = Some(id))
+    };
+    let usage = UsageMeter::default();
+    let started = Instant::now();
+    let history = GenerationRequest::from(&request);
+    let constraints = request.constraints();
+    let fresh = skips_cache(&headers);
+    let count = request.count;
+    let generated = match count {
+        Some(count) => {
+            state
+                .candidates_use_case
+                .execute(
+                    request.ingredients,
+                    request.dietary_restrictions,
+                    constraints,
+                    count as usize,
+                    fresh,
+                    &usage,
+                    reserve,
+                )
+                .await
+        }
+        None => state
+            .generate_use_case
+            .execute(
+                request.ingredients,
+                request.dietary_restrictions,
+                constraints,
+                fresh,
+                &usage,
+                reserve,
+            )
+            .await
+            .map(|recipe| vec![recipe]),
+    };
+    let mut response_headers =
+        record_usage(&state.record_usage_use_case, reservation, summary, &usage).await;
+
+    let generated = generated?;
+    if let Some(id) = record_generation(
+        &state.record_generation_use_case,
+        &requester,
+        history,
+        &generated,
+        &usage,
+        started,
+    )
+    .await
+    {
+        response_headers.insert(
+            GENERATION_ID,
+            HeaderValue::from_str(&id.to_string()).expect("UUIDs are valid header values"),
+        );
+    }
+
+    let mut recipes = generated
+        .into_iter()
+        .map(|recipe| generated_recipe_response(recipe, &query))
+        .collect::<Result<Vec<_>, _>>()?;
+    let response = match count {
+        Some(_) => GenerateRecipeResponse::Recipes(GeneratedRecipesResponse { recipes }),
+        None => GenerateRecipeResponse::Recipe(recipes.remove(0)),
+    };
+    Ok((response_headers, Json(response)))
+}
+
+/// Whether the request asks for a fresh generation with `Cache-Control: no-cache`.
+fn skips_cache(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-cache"))
+}
+
+/// Applies the requested serving count and unit system to a generated recipe.
+fn generated_recipe_response(
+    recipe: GeneratedRecipe,
+    query: &GenerateRecipeQuery,
+) -> Result<GeneratedRecipeResponse, (StatusCode, Json<ErrorResponse>)> {
+    let (recipe, scaling) = match query.servings {
+        Some(servings) => {
+            let scaled = recipe.scale_to(servings).map_err(map_scaling_error)?;
+            (scaled.recipe, Some(scaled.scaling))
+        }
+        None => (recipe, None),
+    };
+
+    let mut response = GeneratedRecipeResponse::from(in_units(recipe, query.units));
+    if let Some(scaling) = scaling {
+        response = response.with_scaling(scaling);
+    }
+
+    Ok(response)
+}
+
+fn stream_event(event: RecipeStreamEvent) -> Event {
+    let (name, data) = match event {
+        RecipeStreamEvent::Title(title) => (
+            "title",
+            Event::default().json_data(StreamTitleEvent { title }),
+        ),
+        RecipeStreamEvent::Ingredient { index, text } => (
+            "ingredient",
+            Event::default().json_data(StreamItemEvent { index, text }),
+        ),
+        RecipeStreamEvent::Instruction { index, text } => (
+            "instruction",
+            Event::default().json_data(StreamItemEvent { index, text }),
+        ),
+    };
+    data.expect("stream events serialize to JSON").event(name)
+}
+
+fn stream_error_event((status, Json(body)): (StatusCode, Json<ErrorResponse>)) -> Event {
+    Event::default()
+        .event("error")
+        .json_data(StreamErrorEvent {
+            status: status.as_u16(),
+            error: body.error,
+            violations: body.violations,
+        })
+        .expect("stream events serialize to JSON")
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/recipes/generate/stream",
+    summary = "Generate a recipe using AI, streaming it as it is written",
+    description = "Streaming variant of `POST /api/recipes/generate`, answered with Server-Sent Events. A `title` event (`StreamTitleEvent`) and one `ingredient` or `instruction` event (`StreamItemEvent`) per item are sent as the model writes them; these carry the model's raw text, before scaling or unit conversion. The stream ends with either a `recipe` event holding the validated `GeneratedRecipeResponse`, or an `error` event (`StreamErrorEvent`) with the status and message the non-streaming endpoint would have returned. A recipe that breaks a dietary restriction is reported as an error rather than regenerated. Only one recipe can be streamed, so `count` is rejected. A cached recipe is sent all at once unless `Cache-Control: no-cache` is given. Generation quotas apply as for the non-streaming endpoint, but a used-up quota is reported as an `error` event with status 429, as it is only known once the cache has been checked; the stream starts before that, so the `X-Quota-*` headers count this request as a generation. A recipe streamed to a signed-in user is kept in their generation history.",
+    params(
+        GenerateRecipeQuery,
+        ("Cache-Control" = Option<String>, Header, description = "`no-cache` skips cached recipes and generates a new one")
+    ),
+    request_body = GenerateRecipeRequest,
+    responses(
+        (status = 200, description = "Event stream of the recipe being generated", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Invalid request - ingredients list is empty or invalid, or `count` was given", body = ErrorResponse),
     ),
     tag = "Recipes"
 )]
-pub async fn generate_recipe<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn generate_recipe_stream<
+    T: LlmService + 'static,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    requester: Requester,
+    ValidatedQuery(query): ValidatedQuery<GenerateRecipeQuery>,
+    headers: HeaderMap,
     ValidatedJson(request): ValidatedJson<GenerateRecipeRequest>,
-) -> Result<Json<GeneratedRecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<
+    (
+        HeaderMap,
+        Sse<impl Stream<Item = Result<Event, Infallible>>>,
+    ),
+    (StatusCode, Json<ErrorResponse>),
+> {
+    if request.count.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "count is not supported when streaming".to_string(),
+                violations: None,
+            }),
+        ));
+    }
+
+    let mut summary = state
+        .usage_use_case
+        .execute(&requester)
+        .await
+        .map_err(map_repo_error)?;
+    let mut expected = summary;
+    expected.add_generation(Default::default());
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let use_case = Arc::clone(&state.stream_use_case);
+    let usage_use_case = Arc::clone(&state.usage_use_case);
+    let record_usage_use_case = Arc::clone(&state.record_usage_use_case);
+    let record_generation_use_case = Arc::clone(&state.record_generation_use_case);
+
+    let fresh = skips_cache(&headers);
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let history = GenerationRequest::from(&request);
+        let constraints = request.constraints();
+        let usage = UsageMeter::default();
+        let mut reservation = None;
+        let result = use_case
+            .execute(
+                request.ingredients,
+                request.dietary_restrictions,
+                constraints,
+                fresh,
+                &usage,
+                async || {
+                    reserve_generation(
+                        &usage_use_case,
+                        &record_usage_use_case,
+                        &requester,
+                        &mut summary,
+                    )
+                    .await
+                    .map(|id| reservation = Some(id))
+                },
+                |event| {
+                    // A closed channel means the client went away; the
+                    // generation still finishes, but nobody hears about it.
+                    let _ = sender.send(stream_event(event));
+                },
+            )
+            .await;
+        record_usage(&record_usage_use_case, reservation, summary, &usage).await;
+        if let Ok(recipe) = &result {
+            record_generation(
+                &record_generation_use_case,
+                &requester,
+                history,
+                std::slice::from_ref(recipe),
+                &usage,
+                started,
+            )
+            .await;
+        }
+        let result = result.and_then(|recipe| generated_recipe_response(recipe, &query));
+
+        let last = match result {
+            Ok(response) => Event::default()
+                .event("recipe")
+                .json_data(response)
+                .expect("stream events serialize to JSON"),
+            Err(error) => stream_error_event(error),
+        };
+        let _ = sender.send(last);
+    });
+
+    Ok((
+        quota_headers(&expected),
+        Sse::new(UnboundedReceiverStream::new(receiver).map(Ok)).keep_alive(KeepAlive::default()),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/recipes/refine",
+    summary = "Refine a generated recipe",
+    description = "Asks the AI to revise an unsaved recipe following a free-text instruction such as \"make it spicier\" or \"no oven\". The instruction may add ingredients, so only the revised recipe's title, ingredients, instructions, servings and times are checked; invalid revisions are retried. Accepts the same `servings` and `units` query parameters as generation. Counts against the caller's generation quotas like generation does.",
+    params(GenerateRecipeQuery),
+    request_body = RefineRecipeRequest,
+    responses(
+        (status = 200, description = "Revised recipe", body = GeneratedRecipeResponse),
+        (status = 400, description = "Invalid request - recipe incomplete, or instruction blank or too long", body = ErrorResponse),
+        (status = 422, description = "No valid revision could be generated; `violations` lists what was wrong", body = ErrorResponse),
+        (status = 429, description = "AI service rate limit reached, or the caller's daily or monthly generation quota is used up", body = ErrorResponse),
+        (status = 502, description = "AI service error - failed to reach or process AI service", body = ErrorResponse),
+        (status = 503, description = "AI service unavailable, or failing repeatedly and temporarily not called", body = ErrorResponse),
+        (status = 504, description = "AI service did not respond in time", body = ErrorResponse),
+    ),
+    tag = "Recipes"
+)]
+pub async fn refine_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    requester: Requester,
+    ValidatedQuery(query): ValidatedQuery<GenerateRecipeQuery>,
+    ValidatedJson(request): ValidatedJson<RefineRecipeRequest>,
+) -> Result<(HeaderMap, Json<GeneratedRecipeResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let (summary, reservation) = check_quota(
+        &state.usage_use_case,
+        &state.record_usage_use_case,
+        &requester,
+    )
+    .await?;
+    let usage = UsageMeter::default();
+    let refined = state
+        .refine_use_case
+        .execute(request.recipe.into(), request.instruction, &usage)
+        .await;
+    let quota_headers = record_usage(
+        &state.record_usage_use_case,
+        Some(reservation),
+        summary,
+        &usage,
+    )
+    .await;
+
+    let response = generated_recipe_response(refined.map_err(map_llm_error)?, &query)?;
+    Ok((quota_headers, Json(response)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/recipes/{id}/refine",
+    summary = "Refine a saved recipe",
+    description = "Asks the AI to revise a saved recipe following a free-text instruction such as \"make it spicier\" or \"no oven\". The revision is saved as a new recipe owned by the caller; the original is left unchanged. The user must be the owner of the recipe or have the recipe shared with them. Counts against the caller's generation quotas.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID")
+    ),
+    request_body = RefineSavedRecipeRequest,
+    responses(
+        (status = 200, description = "Revised recipe, saved as a new recipe", body = RecipeResponse),
+        (status = 400, description = "Invalid request - instruction blank or too long", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 422, description = "No valid revision could be generated; `violations` lists what was wrong", body = ErrorResponse),
+        (status = 429, description = "AI service rate limit reached, or the caller's daily or monthly generation quota is used up", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to save the revised recipe", body = ErrorResponse),
+        (status = 502, description = "AI service error - failed to reach or process AI service", body = ErrorResponse),
+        (status = 503, description = "AI service unavailable, or failing repeatedly and temporarily not called", body = ErrorResponse),
+        (status = 504, description = "AI service did not respond in time", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn refine_saved_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<RefineSavedRecipeRequest>,
+) -> Result<(HeaderMap, Json<RecipeResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let original = state
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let requester = Requester::User(user.user_id.clone());
+    let (summary, reservation) = check_quota(
+        &state.usage_use_case,
+        &state.record_usage_use_case,
+        &requester,
+    )
+    .await?;
+    let usage = UsageMeter::default();
+    let refined = state
+        .refine_use_case
+        .execute(original.content(), request.instruction, &usage)
+        .await;
+    let quota_headers = record_usage(
+        &state.record_usage_use_case,
+        Some(reservation),
+        summary,
+        &usage,
+    )
+    .await;
+    let refined = refined.map_err(map_llm_error)?;
+
+    let recipe = Recipe::from_generated(refined, user.user_id);
+    state
+        .save_use_case
+        .execute(recipe.clone())
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok((quota_headers, Json(recipe.into())))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/recipes",
+    summary = "Save a recipe",
+    description = "Saves a recipe to the user's collection. The recipe will be associated with the authenticated user and visible in 'My Recipes'.",
+    request_body = SaveRecipeRequest,
+    responses(
+        (status = 200, description = "Recipe saved successfully", body = RecipeResponse),
+        (status = 400, description = "Invalid request - missing required fields or invalid data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 500, description = "Database error - failed to save recipe", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn save_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    ValidatedJson(request): ValidatedJson<SaveRecipeRequest>,
+) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let recipe = Recipe::from_generated(request.into(), user.user_id);
+
+    state
+        .save_use_case
+        .execute(recipe.clone())
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(recipe.into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}",
+    summary = "Get a recipe by ID",
+    description = "Retrieves a recipe by its ID. The user must be the owner of the recipe or have the recipe shared with them.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID"),
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Recipe retrieved successfully", body = RecipeResponse),
+        (status = 400, description = "Invalid request - unknown unit system", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn get_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<UnitsQuery>,
+) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
     let recipe = state
-        .generate_use_case
-        .execute(request.ingredients, request.dietary_restrictions)
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let mut response: RecipeResponse = in_units(recipe, query.units).into();
+
+    if response.owner_id != user.user_id
+        && let Ok(Some(email)) = crate::shared::auth::get_user_email_by_id(&response.owner_id).await
+    {
+        response = response.with_owner_email(Some(email));
+    }
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/scaled",
+    summary = "Get a recipe scaled to a number of servings",
+    description = "Returns the recipe with ingredient quantities rescaled from its serving count to the requested one and rounded to kitchen-friendly amounts. Lines without a readable quantity are returned unchanged and listed in `scaling.unscaledIngredients`. The user must be the owner of the recipe or have the recipe shared with them.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID"),
+        ScaleRecipeQuery
+    ),
+    responses(
+        (status = 200, description = "Scaled recipe", body = RecipeResponse),
+        (status = 400, description = "Invalid request - servings missing or out of range, or unknown unit system", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 422, description = "Recipe has no serving count to scale from", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn get_scaled_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<ScaleRecipeQuery>,
+) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let recipe = state
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let scaled = recipe.scale_to(query.servings).map_err(map_scaling_error)?;
+    let recipe = in_units(scaled.recipe, query.units);
+
+    Ok(Json(
+        RecipeResponse::from(recipe).with_scaling(scaled.scaling),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/nutrition",
+    summary = "Get nutrition facts for a recipe",
+    description = "Estimates calories, protein, fat, carbohydrates, fibre and sodium from the recipe's ingredients using a bundled nutrient table. Values are given per serving when the recipe has a serving count. Ingredient lines that could not be matched are listed in `unmatchedIngredients` and left out of the totals. The user must be the owner of the recipe or have the recipe shared with them.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID")
+    ),
+    responses(
+        (status = 200, description = "Nutrition estimate", body = NutritionResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn get_recipe_nutrition<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<NutritionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let recipe = state
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(NutritionResponse::new(recipe.id, recipe.nutrition())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/substitutions",
+    summary = "Suggest substitutes for an ingredient",
+    description = "Suggests replacements for one of the recipe's ingredients, sized to the amount the recipe uses. Common ingredients come from a built-in substitution table; for anything else the AI is asked. Each substitute lists the ways it breaks the dietary restrictions found in the recipe's tags. The user must be the owner of the recipe or have the recipe shared with them. Asking the AI counts against the caller's generation quotas.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID"),
+        SubstitutionsQuery
+    ),
+    responses(
+        (status = 200, description = "Substitutes for the ingredient", body = SubstitutionsResponse),
+        (status = 400, description = "Invalid request - ingredient missing or too long", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found, or the recipe does not use the ingredient", body = ErrorResponse),
+        (status = 429, description = "AI service rate limit reached, or the caller's daily or monthly generation quota is used up", body = ErrorResponse),
+        (status = 502, description = "AI service error - failed to reach or process AI service", body = ErrorResponse),
+        (status = 503, description = "AI service unavailable, or failing repeatedly and temporarily not called", body = ErrorResponse),
+        (status = 504, description = "AI service did not respond in time", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn get_recipe_substitutions<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<SubstitutionsQuery>,
+) -> Result<(HeaderMap, Json<SubstitutionsResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let recipe = state
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let requester = Requester::User(user.user_id);
+    let mut summary = state
+        .usage_use_case
+        .execute(&requester)
+        .await
+        .map_err(map_repo_error)?;
+    let mut reservation = None;
+    let usage = UsageMeter::default();
+    let substitutions = state
+        .substitutes_use_case
+        .execute(&recipe, &query.ingredient, &usage, async || {
+            reserve_generation(
+                &state.usage_use_case,
+                &state.record_usage_use_case,
+                &requester,
+                &mut summary,
+            )
+            .await
+            .map(|id| reservation = Some(id))
+        })
+        .await;
+    let quota_headers =
+        record_usage(&state.record_usage_use_case, reservation, summary, &usage).await;
+
+    let substitutions = substitutions?.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "Ingredient not found in recipe".to_string(),
+                violations: None,
+            }),
+        )
+    })?;
+
+    Ok((
+        quota_headers,
+        Json(SubstitutionsResponse::new(recipe.id, substitutions)),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/usage",
+    summary = "Get your AI usage",
+    description = "Reports how many AI generations the authenticated user has made today and this month (UTC), the tokens they used and an estimated cost, against the user's generation quotas.",
+    responses(
+        (status = 200, description = "Usage for the current day and month", body = UsageResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 500, description = "Database error - failed to read usage", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Usage"
+)]
+pub async fn get_usage<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+) -> Result<Json<UsageResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let summary = state
+        .usage_use_case
+        .execute(&Requester::User(user.user_id))
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(UsageResponse::new(&summary, Utc::now())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/generations",
+    summary = "List your past generations",
+    description = "Returns one page of the authenticated user's generation history, newest first: every successful AI generation made while signed in, with its inputs, the model and prompt version used, the model's raw output, the parsed recipes and how long it took. Generations are kept for the configured retention period. Pass the returned `nextCursor` as `cursor` to fetch the following page.",
+    params(ListGenerationsQuery),
+    responses(
+        (status = 200, description = "A page of generations", body = GenerationPageResponse),
+        (status = 400, description = "Invalid request - invalid limit or cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 500, description = "Database error - failed to retrieve generations", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Generations"
+)]
+pub async fn list_generations<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    ValidatedQuery(query): ValidatedQuery<ListGenerationsQuery>,
+) -> Result<Json<GenerationPageResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let after = decode_cursor(
+        &state.cursor_codec,
+        query.cursor.as_deref(),
+        RecipeSort::CreatedAt,
+        GENERATIONS_LIST,
+        &user.user_id,
+    )?;
+    let page = state
+        .list_generations_use_case
+        .execute(&user.user_id, query.page_request(after))
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(GenerationPageResponse {
+        next_cursor: page.next.map(|c| {
+            state
+                .cursor_codec
+                .encode(&c, GENERATIONS_LIST, &user.user_id)
+        }),
+        items: page.items.into_iter().map(Into::into).collect(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/generations/{id}/save",
+    summary = "Save a recipe from your generation history",
+    description = "Saves one of a past generation's recipes as a new recipe owned by the authenticated user, exactly as it was generated. Pass `index` to pick among several recipes generated at once. Only the user the generation was made for can save from it.",
+    params(
+        ("id" = Uuid, Path, description = "Generation UUID"),
+        SaveGenerationQuery
+    ),
+    responses(
+        (status = 200, description = "Recipe saved successfully", body = RecipeResponse),
+        (status = 400, description = "Invalid request - the generation has no recipe at `index`", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - the generation was made for another user", body = ErrorResponse),
+        (status = 404, description = "Generation not found, or older than the retention period", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to save recipe", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Generations"
+)]
+pub async fn save_generation<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<SaveGenerationQuery>,
+) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut generation = state
+        .get_generation_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let index = query.index.unwrap_or(0);
+    if index >= generation.recipes.len() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Generation has no recipe at index {}", index),
+                violations: None,
+            }),
+        ));
+    }
+    let recipe = Recipe::from_generated(generation.recipes.swap_remove(index), user.user_id);
+
+    state
+        .save_use_case
+        .execute(recipe.clone())
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(recipe.into()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/recipes/{id}",
+    summary = "Update a recipe",
+    description = "Replaces the content of a saved recipe. Only the recipe owner can update their recipes.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID")
+    ),
+    request_body = UpdateRecipeRequest,
+    responses(
+        (status = 200, description = "Recipe updated successfully", body = RecipeResponse),
+        (status = 400, description = "Invalid request - missing required fields or invalid data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to update recipe", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn update_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<UpdateRecipeRequest>,
+) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let recipe = state
+        .update_use_case
+        .execute(id, &user.user_id, request.into())
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(recipe.into()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/recipes/{id}",
+    summary = "Delete a recipe",
+    description = "Permanently deletes a saved recipe together with all of its shares. Only the recipe owner can delete their recipes.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID")
+    ),
+    responses(
+        (status = 204, description = "Recipe deleted successfully"),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to delete recipe", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn delete_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .delete_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/revisions",
+    summary = "List revisions of a recipe",
+    description = "Returns every saved version of a recipe, newest first. Available to the recipe owner and to users the recipe is shared with.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID")
+    ),
+    responses(
+        (status = 200, description = "List of recipe revisions", body = [RecipeRevisionSummaryResponse]),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Revisions"
+)]
+pub async fn list_recipe_revisions<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<RecipeRevisionSummaryResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let revisions = state
+        .list_revisions_use_case
+        .execute(id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(revisions.into_iter().map(|r| r.into()).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/revisions/{revision}",
+    summary = "Get a single recipe revision",
+    description = "Returns the full content of a recipe as it was at the given revision. Available to the recipe owner and to users the recipe is shared with.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID"),
+        ("revision" = i32, Path, description = "Revision number")
+    ),
+    responses(
+        (status = 200, description = "Recipe revision", body = RecipeRevisionResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe or revision not found", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Revisions"
+)]
+pub async fn get_recipe_revision<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path((id, revision)): Path<(Uuid, i32)>,
+) -> Result<Json<RecipeRevisionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let revision = state
+        .get_revision_use_case
+        .execute(id, revision)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(revision.into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/{id}/revisions/diff",
+    summary = "Compare two recipe revisions",
+    description = "Returns a field-level diff between two revisions: changed title, times and servings, plus a line-by-line comparison of ingredients and instructions.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID"),
+        RecipeDiffQuery
+    ),
+    responses(
+        (status = 200, description = "Diff between the two revisions", body = RecipeDiffResponse),
+        (status = 400, description = "Invalid request - missing or malformed revision numbers", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe or revision not found", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Revisions"
+)]
+pub async fn diff_recipe_revisions<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<RecipeDiffQuery>,
+) -> Result<Json<RecipeDiffResponse>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .get_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    let diff = state
+        .diff_revisions_use_case
+        .execute(id, query.from, query.to)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(diff.into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/recipes/{id}/revisions/{revision}/restore",
+    summary = "Restore a recipe revision",
+    description = "Makes the content of an earlier revision the current recipe. The restore is recorded as a new revision, so no history is lost. Only the recipe owner can restore revisions.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID"),
+        ("revision" = i32, Path, description = "Revision number to restore")
+    ),
+    responses(
+        (status = 200, description = "Revision restored successfully", body = RecipeResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe or revision not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to restore revision", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Revisions"
+)]
+pub async fn restore_recipe_revision<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path((id, revision)): Path<(Uuid, i32)>,
+) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let recipe = state
+        .restore_revision_use_case
+        .execute(id, &user.user_id, revision)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(recipe.into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes",
+    summary = "List user's recipes",
+    description = "Returns one page of the recipes owned by the authenticated user, newest first unless another `sort` is requested. Pass the returned `nextCursor` as `cursor` to fetch the following page.",
+    params(ListRecipesQuery),
+    responses(
+        (status = 200, description = "A page of the user's recipes", body = RecipePageResponse),
+        (status = 400, description = "Invalid request - invalid limit, sort, unit system or cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 500, description = "Database error - failed to retrieve recipes", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn list_my_recipes<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    ValidatedQuery(query): ValidatedQuery<ListRecipesQuery>,
+) -> Result<Json<RecipePageResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let after = decode_cursor(
+        &state.cursor_codec,
+        query.cursor.as_deref(),
+        query.sort(),
+        OWNED_LIST,
+        &user.user_id,
+    )?;
+    let page = state
+        .list_owned_use_case
+        .execute(&user.user_id, query.page_request(after))
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(RecipePageResponse {
+        next_cursor: page
+            .next
+            .map(|c| state.cursor_codec.encode(&c, OWNED_LIST, &user.user_id)),
+        items: page
+            .items
+            .into_iter()
+            .map(|r| in_units(r, query.units).into())
+            .collect(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/shared",
+    summary = "List recipes shared with user",
+    description = "Returns one page of the recipes other users have shared with the authenticated user, most recently shared first unless another `sort` is requested. Pass the returned `nextCursor` as `cursor` to fetch the following page.",
+    params(ListRecipesQuery),
+    responses(
+        (status = 200, description = "A page of recipes shared with user", body = RecipePageResponse),
+        (status = 400, description = "Invalid request - invalid limit, sort, unit system or cursor", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 500, description = "Database error - failed to retrieve shared recipes", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn list_shared_recipes<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    ValidatedQuery(query): ValidatedQuery<ListRecipesQuery>,
+) -> Result<Json<RecipePageResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let after = decode_cursor(
+        &state.cursor_codec,
+        query.cursor.as_deref(),
+        query.sort(),
+        SHARED_LIST,
+        &user.user_id,
+    )?;
+    let page = state
+        .list_shared_use_case
+        .execute(&user.user_id, query.page_request(after))
+        .await
+        .map_err(map_repo_error)?;
+
+    let mut items: Vec<RecipeResponse> = Vec::new();
+    for recipe in page.items {
+        let mut response: RecipeResponse = in_units(recipe, query.units).into();
+        if let Ok(Some(email)) = crate::shared::auth::get_user_email_by_id(&response.owner_id).await
+        {
+            response = response.with_owner_email(Some(email));
+        }
+        items.push(response);
+    }
+
+    Ok(Json(RecipePageResponse {
+        items,
+        next_cursor: page
+            .next
+            .map(|c| state.cursor_codec.encode(&c, SHARED_LIST, &user.user_id)),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/search",
+    summary = "Search recipes",
+    description = "Full-text search over the title, ingredients and instructions of the recipes the user owns or that are shared with them, best matches first. Without `q` the filters alone are applied and the newest recipes come first.",
+    params(SearchRecipesQuery),
+    responses(
+        (status = 200, description = "Matching recipes", body = [RecipeResponse]),
+        (status = 400, description = "Invalid request - invalid filter value", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 500, description = "Database error - failed to search recipes", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Recipes"
+)]
+pub async fn search_recipes<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    ValidatedQuery(query): ValidatedQuery<SearchRecipesQuery>,
+) -> Result<Json<Vec<RecipeResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    let units = query.units;
+    let recipes = state
+        .search_use_case
+        .execute(query.into_search(user.user_id))
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(
+        recipes
+            .into_iter()
+            .map(|r| in_units(r, units).into())
+            .collect(),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/recipes/{id}/shares",
+    summary = "Share a recipe with another user",
+    description = "Shares a recipe with another user by their email address. Only the recipe owner can share their recipes.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID")
+    ),
+    request_body = CreateShareRequest,
+    responses(
+        (status = 201, description = "Recipe shared successfully"),
+        (status = 400, description = "Invalid request - invalid email format", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe or user not found - recipe doesn't exist or user with email not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to create share", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Sharing"
+)]
+pub async fn create_share<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(recipe_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CreateShareRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let user_id = crate::shared::auth::get_user_id_by_email(&request.email)
+        .await
+        .map_err(map_auth_lookup_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "User with this email not found".to_string(),
+                    violations: None,
+                }),
+            )
+        })?;
+
+    state
+        .create_share_use_case
+        .execute(recipe_id, &user.user_id, user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(StatusCode::CREATED)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/recipes/{recipe_id}/shares/{user_id}",
+    summary = "Unshare a recipe",
+    description = "Removes sharing access for a specific user. Only the recipe owner can unshare their recipes.",
+    params(
+        ("recipe_id" = Uuid, Path, description = "Recipe UUID"),
+        ("user_id" = String, Path, description = "User ID to remove sharing access from")
+    ),
+    responses(
+        (status = 204, description = "Share removed successfully"),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
+        (status = 404, description = "Share not found - recipe is not shared with this user", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to remove share", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Sharing"
+)]
+pub async fn delete_share<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path((recipe_id, shared_user_id)): Path<(Uuid, String)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .delete_share_use_case
+        .execute(recipe_id, &user.user_id, &shared_user_id)
         .await
-        .map_err(map_llm_error)?;
+        .map_err(map_repo_error)?;
 
-    Ok(Json(recipe.into()))
+    Ok(StatusCode::NO_CONTENT)
 }
 
 #[utoipa::path(
-    post,
-    path = "/api/recipes",
-    summary = "Save a recipe",
-    description = "Saves a recipe to the user's collection. The recipe will be associated with the authenticated user and visible in 'My Recipes'.",
-    request_body = SaveRecipeRequest,
+    get,
+    path = "/api/recipes/{id}/shares",
+    summary = "List users a recipe is shared with",
+    description = "Returns a list of users (with emails) that the recipe is shared with. Only the recipe owner can view this list.",
+    params(
+        ("id" = Uuid, Path, description = "Recipe UUID")
+    ),
     responses(
-        (status = 200, description = "Recipe saved successfully", body = RecipeResponse),
-        (status = 400, description = "Invalid request - missing required fields or invalid data", body = ErrorResponse),
+        (status = 200, description = "List of users the recipe is shared with", body = [ShareResponse]),
         (status = 401, description = "Unauthorized - authentication token missing or invalid"),
-        (status = 500, description = "Database error - failed to save recipe", body = ErrorResponse),
+        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
+        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 500, description = "Database error", body = ErrorResponse),
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Recipes"
+    tag = "Sharing"
 )]
-pub async fn save_recipe<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn list_recipe_shares<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
     user: AuthenticatedUser,
-    ValidatedJson(request): ValidatedJson<SaveRecipeRequest>,
-) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let recipe = Recipe::from_generated(request.into(), user.user_id);
+    Path(recipe_id): Path<Uuid>,
+) -> Result<Json<Vec<ShareResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    let recipe = state
+        .get_use_case
+        .execute(recipe_id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
 
-    state
-        .save_use_case
-        .execute(recipe.clone())
+    if recipe.owner_id != user.user_id {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Access denied".to_string(),
+                violations: None,
+            }),
+        ));
+    }
+
+    let shares = state
+        .list_recipe_shares_use_case
+        .execute(recipe_id)
         .await
         .map_err(map_repo_error)?;
 
-    Ok(Json(recipe.into()))
+    let mut responses: Vec<ShareResponse> = Vec::new();
+    for share in shares {
+        if let Ok(Some(email)) = crate::shared::auth::get_user_email_by_id(&share.user_id).await {
+            responses.push(ShareResponse {
+                user_id: share.user_id,
+                email,
+                created_at: share.created_at,
+            });
+        }
+    }
+
+    Ok(Json(responses))
 }
 
 #[utoipa::path(
-    get,
-    path = "/api/recipes/{id}",
-    summary = "Get a recipe by ID",
-    description = "Retrieves a recipe by its ID. The user must be the owner of the recipe or have the recipe shared with them.",
+    put,
+    path = "/api/recipes/{id}/tags",
+    summary = "Set a recipe's tags",
+    description = "Replaces the tags on a recipe. Tags are trimmed, lowercased and deduplicated. They are not part of the recipe's content, so no revision is recorded. Only the recipe owner can tag their recipes.",
     params(
         ("id" = Uuid, Path, description = "Recipe UUID")
     ),
+    request_body = SetTagsRequest,
     responses(
-        (status = 200, description = "Recipe retrieved successfully", body = RecipeResponse),
+        (status = 200, description = "Recipe with its new tags", body = RecipeResponse),
+        (status = 400, description = "Invalid request - more than 20 tags or a tag over 40 characters", body = ErrorResponse),
         (status = 401, description = "Unauthorized - authentication token missing or invalid"),
-        (status = 403, description = "Access denied - user does not have permission to view this recipe", body = ErrorResponse),
+        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
         (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to save tags", body = ErrorResponse),
     ),
     security(
         ("bearer_auth" = [])
     ),
     tag = "Recipes"
 )]
-pub async fn get_recipe<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn set_recipe_tags<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<SetTagsRequest>,
 ) -> Result<Json<RecipeResponse>, (StatusCode, Json<ErrorResponse>)> {
     let recipe = state
-        .get_use_case
-        .execute(id, &user.user_id)
+        .set_tags_use_case
+        .execute(id, &user.user_id, request.tags)
         .await
         .map_err(map_repo_error)?;
 
-    let mut response: RecipeResponse = recipe.into();
+    Ok(Json(recipe.into()))
+}
 
-    if response.owner_id != user.user_id {
-        if let Ok(Some(email)) = crate::shared::auth::get_user_email_by_id(&response.owner_id).await
-        {
-            response = response.with_owner_email(Some(email));
-        }
-    }
+#[utoipa::path(
+    post,
+    path = "/api/collections",
+    summary = "Create a collection",
+    description = "Creates an empty named collection owned by the authenticated user.",
+    request_body = CollectionRequest,
+    responses(
+        (status = 201, description = "Collection created", body = CollectionResponse),
+        (status = 400, description = "Invalid request - blank name or name over 100 characters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 500, description = "Database error - failed to create collection", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Collections"
+)]
+pub async fn create_collection<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    ValidatedJson(request): ValidatedJson<CollectionRequest>,
+) -> Result<(StatusCode, Json<CollectionResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let collection = state
+        .create_collection_use_case
+        .execute(user.user_id, request.name)
+        .await
+        .map_err(map_repo_error)?;
 
-    Ok(Json(response))
+    Ok((StatusCode::CREATED, Json(collection.into())))
 }
 
 #[utoipa::path(
     get,
-    path = "/api/recipes",
-    summary = "List user's recipes",
-    description = "Returns all recipes owned by the authenticated user, ordered by creation date (newest first).",
+    path = "/api/collections",
+    summary = "List user's collections",
+    description = "Returns the collections owned by the authenticated user, sorted by name.",
     responses(
-        (status = 200, description = "List of user's recipes", body = [RecipeResponse]),
+        (status = 200, description = "List of user's collections", body = [CollectionResponse]),
         (status = 401, description = "Unauthorized - authentication token missing or invalid"),
-        (status = 500, description = "Database error - failed to retrieve recipes", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to retrieve collections", body = ErrorResponse),
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Recipes"
+    tag = "Collections"
 )]
-pub async fn list_my_recipes<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn list_collections<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
     user: AuthenticatedUser,
-) -> Result<Json<Vec<RecipeResponse>>, (StatusCode, Json<ErrorResponse>)> {
-    let recipes = state
-        .list_owned_use_case
+) -> Result<Json<Vec<CollectionResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    let collections = state
+        .list_collections_use_case
         .execute(&user.user_id)
         .await
         .map_err(map_repo_error)?;
 
-    Ok(Json(recipes.into_iter().map(|r| r.into()).collect()))
+    Ok(Json(collections.into_iter().map(Into::into).collect()))
 }
 
 #[utoipa::path(
     get,
-    path = "/api/recipes/shared",
-    summary = "List recipes shared with user",
-    description = "Returns all recipes that have been shared with the authenticated user by other users, ordered by share date (newest first).",
+    path = "/api/collections/shared",
+    summary = "List collections shared with user",
+    description = "Returns the collections other users have shared with the authenticated user, most recently shared first.",
     responses(
-        (status = 200, description = "List of recipes shared with user", body = [RecipeResponse]),
+        (status = 200, description = "List of collections shared with user", body = [CollectionResponse]),
         (status = 401, description = "Unauthorized - authentication token missing or invalid"),
-        (status = 500, description = "Database error - failed to retrieve shared recipes", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to retrieve shared collections", body = ErrorResponse),
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Recipes"
+    tag = "Collections"
 )]
-pub async fn list_shared_recipes<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn list_shared_collections<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
     user: AuthenticatedUser,
-) -> Result<Json<Vec<RecipeResponse>>, (StatusCode, Json<ErrorResponse>)> {
-    let recipes = state
-        .list_shared_use_case
+) -> Result<Json<Vec<CollectionResponse>>, (StatusCode, Json<ErrorResponse>)> {
+    let collections = state
+        .list_shared_collections_use_case
         .execute(&user.user_id)
         .await
         .map_err(map_repo_error)?;
 
-    let mut responses: Vec<RecipeResponse> = Vec::new();
-    for recipe in recipes {
-        let mut response: RecipeResponse = recipe.into();
+    let mut responses: Vec<CollectionResponse> = Vec::new();
+    for collection in collections {
+        let mut response: CollectionResponse = collection.into();
         if let Ok(Some(email)) = crate::shared::auth::get_user_email_by_id(&response.owner_id).await
         {
             response = response.with_owner_email(Some(email));
@@ -237,21 +2021,247 @@ pub async fn list_shared_recipes<T: LlmService, R: RecipeRepository, S: RecipeSh
     Ok(Json(responses))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}",
+    summary = "Get a collection",
+    description = "Returns a collection and the recipes in it that the user can see. Available to the owner and to users the collection is shared with. Users it is shared with see the owner's recipes in it, plus any recipes that were shared with them directly.",
+    params(
+        ("id" = Uuid, Path, description = "Collection UUID"),
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Collection with its recipes", body = CollectionDetailResponse),
+        (status = 400, description = "Invalid request - unknown unit system", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - collection is not owned by or shared with user", body = ErrorResponse),
+        (status = 404, description = "Collection not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to retrieve collection", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Collections"
+)]
+pub async fn get_collection<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<UnitsQuery>,
+) -> Result<Json<CollectionDetailResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let (collection, recipes) = state
+        .get_collection_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(CollectionDetailResponse {
+        collection: collection.into(),
+        recipes: recipes
+            .into_iter()
+            .map(|r| in_units(r, query.units).into())
+            .collect(),
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/collections/{id}",
+    summary = "Rename a collection",
+    description = "Renames a collection. Only the collection owner can rename it.",
+    params(
+        ("id" = Uuid, Path, description = "Collection UUID")
+    ),
+    request_body = CollectionRequest,
+    responses(
+        (status = 200, description = "Collection renamed", body = CollectionResponse),
+        (status = 400, description = "Invalid request - blank name or name over 100 characters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this collection", body = ErrorResponse),
+        (status = 404, description = "Collection not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to rename collection", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Collections"
+)]
+pub async fn rename_collection<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<CollectionRequest>,
+) -> Result<Json<CollectionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let collection = state
+        .rename_collection_use_case
+        .execute(id, &user.user_id, request.name)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(Json(collection.into()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{id}",
+    summary = "Delete a collection",
+    description = "Deletes a collection and its shares. The recipes in it are not deleted. Only the collection owner can delete it.",
+    params(
+        ("id" = Uuid, Path, description = "Collection UUID")
+    ),
+    responses(
+        (status = 204, description = "Collection deleted"),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this collection", body = ErrorResponse),
+        (status = 404, description = "Collection not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to delete collection", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Collections"
+)]
+pub async fn delete_collection<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .delete_collection_use_case
+        .execute(id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 #[utoipa::path(
     post,
-    path = "/api/recipes/{id}/shares",
-    summary = "Share a recipe with another user",
-    description = "Shares a recipe with another user by their email address. Only the recipe owner can share their recipes.",
+    path = "/api/collections/{id}/recipes",
+    summary = "Add a recipe to a collection",
+    description = "Adds a recipe the user owns or that was shared with them. Adding a recipe that is already in the collection has no effect. Only the collection owner can add recipes.",
     params(
-        ("id" = Uuid, Path, description = "Recipe UUID")
+        ("id" = Uuid, Path, description = "Collection UUID")
+    ),
+    request_body = AddCollectionRecipeRequest,
+    responses(
+        (status = 204, description = "Recipe is in the collection"),
+        (status = 400, description = "Invalid request - malformed recipe id", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user does not own the collection or cannot see the recipe", body = ErrorResponse),
+        (status = 404, description = "Collection or recipe not found", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to add recipe", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Collections"
+)]
+pub async fn add_collection_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<AddCollectionRecipeRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .add_collection_recipe_use_case
+        .execute(id, request.recipe_id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{id}/recipes/{recipe_id}",
+    summary = "Remove a recipe from a collection",
+    description = "Removes a recipe from a collection without deleting the recipe. Only the collection owner can remove recipes.",
+    params(
+        ("id" = Uuid, Path, description = "Collection UUID"),
+        ("recipe_id" = Uuid, Path, description = "Recipe UUID")
+    ),
+    responses(
+        (status = 204, description = "Recipe removed from the collection"),
+        (status = 401, description = "Unauthorized - authentication token missing or invalid"),
+        (status = 403, description = "Access denied - user is not the owner of this collection", body = ErrorResponse),
+        (status = 404, description = "Collection not found or recipe is not in it", body = ErrorResponse),
+        (status = 500, description = "Database error - failed to remove recipe", body = ErrorResponse),
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Collections"
+)]
+pub async fn remove_collection_recipe<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
+    user: AuthenticatedUser,
+    Path((id, recipe_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .remove_collection_recipe_use_case
+        .execute(id, recipe_id, &user.user_id)
+        .await
+        .map_err(map_repo_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/collections/{id}/shares",
+    summary = "Share a collection with another user",
+    description = "Shares a collection with another user by their email address. They can then see the collection and the owner's recipes in it, including recipes added later. Only the collection owner can share it.",
+    params(
+        ("id" = Uuid, Path, description = "Collection UUID")
     ),
     request_body = CreateShareRequest,
     responses(
-        (status = 201, description = "Recipe shared successfully"),
+        (status = 201, description = "Collection shared successfully"),
         (status = 400, description = "Invalid request - invalid email format", body = ErrorResponse),
         (status = 401, description = "Unauthorized - authentication token missing or invalid"),
-        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
-        (status = 404, description = "Recipe or user not found - recipe doesn't exist or user with email not found", body = ErrorResponse),
+        (status = 403, description = "Access denied - user is not the owner of this collection", body = ErrorResponse),
+        (status = 404, description = "Collection or user not found", body = ErrorResponse),
         (status = 500, description = "Database error - failed to create share", body = ErrorResponse),
     ),
     security(
@@ -259,10 +2269,18 @@ pub async fn list_shared_recipes<T: LlmService, R: RecipeRepository, S: RecipeSh
     ),
     tag = "Sharing"
 )]
-pub async fn create_share<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn create_collection_share<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
     user: AuthenticatedUser,
-    Path(recipe_id): Path<Uuid>,
+    Path(id): Path<Uuid>,
     ValidatedJson(request): ValidatedJson<CreateShareRequest>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
     let user_id = crate::shared::auth::get_user_id_by_email(&request.email)
@@ -273,13 +2291,14 @@ pub async fn create_share<T: LlmService, R: RecipeRepository, S: RecipeShareRepo
                 StatusCode::NOT_FOUND,
                 Json(ErrorResponse {
                     error: "User with this email not found".to_string(),
+                    violations: None,
                 }),
             )
         })?;
 
     state
-        .create_share_use_case
-        .execute(recipe_id, &user.user_id, user_id)
+        .create_collection_share_use_case
+        .execute(id, &user.user_id, user_id)
         .await
         .map_err(map_repo_error)?;
 
@@ -288,18 +2307,18 @@ pub async fn create_share<T: LlmService, R: RecipeRepository, S: RecipeShareRepo
 
 #[utoipa::path(
     delete,
-    path = "/api/recipes/{recipe_id}/shares/{user_id}",
-    summary = "Unshare a recipe",
-    description = "Removes sharing access for a specific user. Only the recipe owner can unshare their recipes.",
+    path = "/api/collections/{id}/shares/{user_id}",
+    summary = "Unshare a collection",
+    description = "Removes a user's access to a collection. Recipes shared with them directly stay shared. Only the collection owner can unshare it.",
     params(
-        ("recipe_id" = Uuid, Path, description = "Recipe UUID"),
+        ("id" = Uuid, Path, description = "Collection UUID"),
         ("user_id" = String, Path, description = "User ID to remove sharing access from")
     ),
     responses(
         (status = 204, description = "Share removed successfully"),
         (status = 401, description = "Unauthorized - authentication token missing or invalid"),
-        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
-        (status = 404, description = "Share not found - recipe is not shared with this user", body = ErrorResponse),
+        (status = 403, description = "Access denied - user is not the owner of this collection", body = ErrorResponse),
+        (status = 404, description = "Collection not found or not shared with this user", body = ErrorResponse),
         (status = 500, description = "Database error - failed to remove share", body = ErrorResponse),
     ),
     security(
@@ -307,14 +2326,22 @@ pub async fn create_share<T: LlmService, R: RecipeRepository, S: RecipeShareRepo
     ),
     tag = "Sharing"
 )]
-pub async fn delete_share<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn delete_collection_share<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
     user: AuthenticatedUser,
-    Path((recipe_id, shared_user_id)): Path<(Uuid, String)>,
+    Path((id, shared_user_id)): Path<(Uuid, String)>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
     state
-        .delete_share_use_case
-        .execute(recipe_id, &user.user_id, &shared_user_id)
+        .delete_collection_share_use_case
+        .execute(id, &user.user_id, &shared_user_id)
         .await
         .map_err(map_repo_error)?;
 
@@ -323,17 +2350,17 @@ pub async fn delete_share<T: LlmService, R: RecipeRepository, S: RecipeShareRepo
 
 #[utoipa::path(
     get,
-    path = "/api/recipes/{id}/shares",
-    summary = "List users a recipe is shared with",
-    description = "Returns a list of users (with emails) that the recipe is shared with. Only the recipe owner can view this list.",
+    path = "/api/collections/{id}/shares",
+    summary = "List users a collection is shared with",
+    description = "Returns the users (with emails) that the collection is shared with. Only the collection owner can view this list.",
     params(
-        ("id" = Uuid, Path, description = "Recipe UUID")
+        ("id" = Uuid, Path, description = "Collection UUID")
     ),
     responses(
-        (status = 200, description = "List of users the recipe is shared with", body = [ShareResponse]),
+        (status = 200, description = "List of users the collection is shared with", body = [ShareResponse]),
         (status = 401, description = "Unauthorized - authentication token missing or invalid"),
-        (status = 403, description = "Access denied - user is not the owner of this recipe", body = ErrorResponse),
-        (status = 404, description = "Recipe not found", body = ErrorResponse),
+        (status = 403, description = "Access denied - user is not the owner of this collection", body = ErrorResponse),
+        (status = 404, description = "Collection not found", body = ErrorResponse),
         (status = 500, description = "Database error", body = ErrorResponse),
     ),
     security(
@@ -341,29 +2368,22 @@ pub async fn delete_share<T: LlmService, R: RecipeRepository, S: RecipeShareRepo
     ),
     tag = "Sharing"
 )]
-pub async fn list_recipe_shares<T: LlmService, R: RecipeRepository, S: RecipeShareRepository>(
-    State(state): State<AppState<T, R, S>>,
+pub async fn list_collection_shares<
+    T: LlmService,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+    C: CollectionRepository,
+    G: GenerationCache,
+    U: UsageRepository,
+    H: GenerationRepository,
+>(
+    State(state): State<AppState<T, R, S, C, G, U, H>>,
     user: AuthenticatedUser,
-    Path(recipe_id): Path<Uuid>,
+    Path(id): Path<Uuid>,
 ) -> Result<Json<Vec<ShareResponse>>, (StatusCode, Json<ErrorResponse>)> {
-    let recipe = state
-        .get_use_case
-        .execute(recipe_id, &user.user_id)
-        .await
-        .map_err(map_repo_error)?;
-
-    if recipe.owner_id != user.user_id {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ErrorResponse {
-                error: "Access denied".to_string(),
-            }),
-        ));
-    }
-
     let shares = state
-        .list_recipe_shares_use_case
-        .execute(recipe_id)
+        .list_collection_shares_use_case
+        .execute(id, &user.user_id)
         .await
         .map_err(map_repo_error)?;
 
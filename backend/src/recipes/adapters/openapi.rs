@@ -1,8 +1,18 @@
 use utoipa::OpenApi;
 
 use super::dto::{
-    CreateShareRequest, GenerateRecipeRequest, GeneratedRecipeResponse, RecipeResponse,
-    SaveRecipeRequest, ShareResponse,
+    AddCollectionRecipeRequest, CollectionDetailResponse, CollectionRequest, CollectionResponse,
+    CreateShareRequest, DifficultyParam, EquipmentParam, GenerateRecipeRequest,
+    GenerateRecipeResponse, GeneratedRecipeResponse, GeneratedRecipesResponse,
+    GenerationConstraintsResponse, GenerationPageResponse, GenerationResponse,
+    LineChangeKindResponse, LineChangeResponse, NumberChangeResponse, NutritionFactsResponse,
+    NutritionResponse, ParsedIngredientResponse, RecipeDiffResponse, RecipePageResponse,
+    RecipeResponse, RecipeRevisionResponse, RecipeRevisionSummaryResponse, RecipeSortParam,
+    RecipeViolationResponse, RefineRecipeRequest, RefineSavedRecipeRequest, SaveRecipeRequest,
+    ScalingResponse, SearchScopeParam, SetTagsRequest, ShareResponse, StreamErrorEvent,
+    StreamItemEvent, StreamTitleEvent, SubstituteResponse, SubstituteSourceResponse,
+    SubstitutionsResponse, TextChangeResponse, UnitSystemParam, UpdateRecipeRequest,
+    UsagePeriodResponse, UsageResponse,
 };
 use super::handlers::ErrorResponse;
 
@@ -10,26 +20,97 @@ use super::handlers::ErrorResponse;
 #[openapi(
     paths(
         crate::recipes::adapters::handlers::generate_recipe,
+        crate::recipes::adapters::handlers::generate_recipe_stream,
+        crate::recipes::adapters::handlers::refine_recipe,
+        crate::recipes::adapters::handlers::refine_saved_recipe,
         crate::recipes::adapters::handlers::save_recipe,
         crate::recipes::adapters::handlers::get_recipe,
+        crate::recipes::adapters::handlers::get_scaled_recipe,
+        crate::recipes::adapters::handlers::get_recipe_nutrition,
+        crate::recipes::adapters::handlers::get_recipe_substitutions,
+        crate::recipes::adapters::handlers::get_usage,
+        crate::recipes::adapters::handlers::list_generations,
+        crate::recipes::adapters::handlers::save_generation,
+        crate::recipes::adapters::handlers::update_recipe,
+        crate::recipes::adapters::handlers::delete_recipe,
+        crate::recipes::adapters::handlers::list_recipe_revisions,
+        crate::recipes::adapters::handlers::get_recipe_revision,
+        crate::recipes::adapters::handlers::diff_recipe_revisions,
+        crate::recipes::adapters::handlers::restore_recipe_revision,
         crate::recipes::adapters::handlers::list_my_recipes,
         crate::recipes::adapters::handlers::list_shared_recipes,
+        crate::recipes::adapters::handlers::search_recipes,
         crate::recipes::adapters::handlers::list_recipe_shares,
         crate::recipes::adapters::handlers::create_share,
         crate::recipes::adapters::handlers::delete_share,
+        crate::recipes::adapters::handlers::set_recipe_tags,
+        crate::recipes::adapters::handlers::create_collection,
+        crate::recipes::adapters::handlers::list_collections,
+        crate::recipes::adapters::handlers::list_shared_collections,
+        crate::recipes::adapters::handlers::get_collection,
+        crate::recipes::adapters::handlers::rename_collection,
+        crate::recipes::adapters::handlers::delete_collection,
+        crate::recipes::adapters::handlers::add_collection_recipe,
+        crate::recipes::adapters::handlers::remove_collection_recipe,
+        crate::recipes::adapters::handlers::create_collection_share,
+        crate::recipes::adapters::handlers::delete_collection_share,
+        crate::recipes::adapters::handlers::list_collection_shares,
     ),
     components(schemas(
         GenerateRecipeRequest,
+        EquipmentParam,
+        DifficultyParam,
+        GenerateRecipeResponse,
         GeneratedRecipeResponse,
+        GeneratedRecipesResponse,
+        StreamTitleEvent,
+        StreamItemEvent,
+        StreamErrorEvent,
+        RecipeViolationResponse,
+        RefineRecipeRequest,
+        RefineSavedRecipeRequest,
         SaveRecipeRequest,
+        UpdateRecipeRequest,
         RecipeResponse,
+        RecipePageResponse,
+        ParsedIngredientResponse,
+        ScalingResponse,
+        NutritionFactsResponse,
+        NutritionResponse,
+        SubstituteSourceResponse,
+        SubstituteResponse,
+        SubstitutionsResponse,
+        UsagePeriodResponse,
+        UsageResponse,
+        GenerationConstraintsResponse,
+        GenerationResponse,
+        GenerationPageResponse,
+        UnitSystemParam,
+        SearchScopeParam,
+        RecipeSortParam,
+        SetTagsRequest,
+        CollectionRequest,
+        AddCollectionRecipeRequest,
+        CollectionResponse,
+        CollectionDetailResponse,
         CreateShareRequest,
         ShareResponse,
+        RecipeRevisionSummaryResponse,
+        RecipeRevisionResponse,
+        RecipeDiffResponse,
+        TextChangeResponse,
+        NumberChangeResponse,
+        LineChangeResponse,
+        LineChangeKindResponse,
         ErrorResponse,
     )),
     tags(
         (name = "Recipes", description = "Recipe management endpoints"),
         (name = "Sharing", description = "Recipe sharing endpoints"),
+        (name = "Revisions", description = "Recipe revision history endpoints"),
+        (name = "Collections", description = "Recipe collection endpoints"),
+        (name = "Usage", description = "AI usage and quota endpoints"),
+        (name = "Generations", description = "Generation history endpoints"),
     ),
     modifiers(&SecurityAddon),
 )]
@@ -1,18 +1,31 @@
 use axum::{
     Router,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
 };
 use std::sync::Arc;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::recipes::application::{
-    CreateShareUseCase, DeleteShareUseCase, GenerateRecipeUseCase, GetRecipeUseCase,
-    ListOwnedRecipesUseCase, ListRecipeSharesUseCase, ListSharedRecipesUseCase, SaveRecipeUseCase,
+    AddRecipeToCollectionUseCase, CreateCollectionShareUseCase, CreateCollectionUseCase,
+    CreateShareUseCase, DeleteCollectionShareUseCase, DeleteCollectionUseCase, DeleteRecipeUseCase,
+    DeleteShareUseCase, DiffRecipeRevisionsUseCase, GenerateRecipeCandidatesUseCase,
+    GenerateRecipeUseCase, GetCollectionUseCase, GetGenerationUseCase, GetRecipeRevisionUseCase,
+    GetRecipeUseCase, GetUsageUseCase, ListCollectionSharesUseCase, ListCollectionsUseCase,
+    ListGenerationsUseCase, ListOwnedRecipesUseCase, ListRecipeRevisionsUseCase,
+    ListRecipeSharesUseCase, ListSharedCollectionsUseCase, ListSharedRecipesUseCase,
+    RecordGenerationUseCase, RecordUsageUseCase, RefineRecipeUseCase,
+    RemoveRecipeFromCollectionUseCase, RenameCollectionUseCase, RestoreRecipeRevisionUseCase,
+    SaveRecipeUseCase, SearchRecipesUseCase, SetRecipeTagsUseCase, StreamRecipeUseCase,
+    SuggestSubstitutesUseCase, UpdateRecipeUseCase,
 };
-use crate::recipes::domain::{LlmService, RecipeRepository, RecipeShareRepository};
-use crate::shared::auth::create_clerk_layer;
+use crate::recipes::domain::{
+    CollectionRepository, GenerationCache, GenerationRepository, LlmService, RecipeRepository,
+    RecipeShareRepository, UsageRepository,
+};
+use crate::shared::auth::{create_clerk_layer, identify_user};
 
+use super::cursor::CursorCodec;
 use super::handlers;
 use super::openapi::ApiDoc;
 use super::state::AppState;
@@ -21,42 +34,152 @@ async fn health() -> &'static str {
     "OK"
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_router<
     T: LlmService + 'static,
     R: RecipeRepository + 'static,
     S: RecipeShareRepository + 'static,
+    C: CollectionRepository + 'static,
+    G: GenerationCache + 'static,
+    U: UsageRepository + 'static,
+    H: GenerationRepository + 'static,
 >(
-    generate_use_case: Arc<GenerateRecipeUseCase<T>>,
+    generate_use_case: Arc<GenerateRecipeUseCase<T, G>>,
+    stream_use_case: Arc<StreamRecipeUseCase<T, G>>,
+    candidates_use_case: Arc<GenerateRecipeCandidatesUseCase<T, G>>,
+    refine_use_case: Arc<RefineRecipeUseCase<T>>,
+    substitutes_use_case: Arc<SuggestSubstitutesUseCase<T>>,
+    usage_use_case: Arc<GetUsageUseCase<U>>,
+    record_usage_use_case: Arc<RecordUsageUseCase<U>>,
+    record_generation_use_case: Arc<RecordGenerationUseCase<H>>,
+    list_generations_use_case: Arc<ListGenerationsUseCase<H>>,
+    get_generation_use_case: Arc<GetGenerationUseCase<H>>,
     save_use_case: Arc<SaveRecipeUseCase<R>>,
     get_use_case: Arc<GetRecipeUseCase<R, S>>,
+    update_use_case: Arc<UpdateRecipeUseCase<R>>,
+    set_tags_use_case: Arc<SetRecipeTagsUseCase<R>>,
+    delete_use_case: Arc<DeleteRecipeUseCase<R>>,
+    list_revisions_use_case: Arc<ListRecipeRevisionsUseCase<R>>,
+    get_revision_use_case: Arc<GetRecipeRevisionUseCase<R>>,
+    diff_revisions_use_case: Arc<DiffRecipeRevisionsUseCase<R>>,
+    restore_revision_use_case: Arc<RestoreRecipeRevisionUseCase<R>>,
     list_owned_use_case: Arc<ListOwnedRecipesUseCase<R>>,
     list_shared_use_case: Arc<ListSharedRecipesUseCase<R>>,
+    search_use_case: Arc<SearchRecipesUseCase<R>>,
     list_recipe_shares_use_case: Arc<ListRecipeSharesUseCase<S>>,
     create_share_use_case: Arc<CreateShareUseCase<R, S>>,
     delete_share_use_case: Arc<DeleteShareUseCase<R, S>>,
+    create_collection_use_case: Arc<CreateCollectionUseCase<C>>,
+    rename_collection_use_case: Arc<RenameCollectionUseCase<C>>,
+    delete_collection_use_case: Arc<DeleteCollectionUseCase<C>>,
+    list_collections_use_case: Arc<ListCollectionsUseCase<C>>,
+    list_shared_collections_use_case: Arc<ListSharedCollectionsUseCase<C>>,
+    get_collection_use_case: Arc<GetCollectionUseCase<C>>,
+    add_collection_recipe_use_case: Arc<AddRecipeToCollectionUseCase<C, R, S>>,
+    remove_collection_recipe_use_case: Arc<RemoveRecipeFromCollectionUseCase<C>>,
+    list_collection_shares_use_case: Arc<ListCollectionSharesUseCase<C>>,
+    create_collection_share_use_case: Arc<CreateCollectionShareUseCase<C>>,
+    delete_collection_share_use_case: Arc<DeleteCollectionShareUseCase<C>>,
+    cursor_codec: Arc<CursorCodec>,
 ) -> Router {
     let state = AppState {
         generate_use_case,
+        stream_use_case,
+        candidates_use_case,
+        refine_use_case,
+        substitutes_use_case,
+        usage_use_case,
+        record_usage_use_case,
+        record_generation_use_case,
+        list_generations_use_case,
+        get_generation_use_case,
         save_use_case,
         get_use_case,
+        update_use_case,
+        set_tags_use_case,
+        delete_use_case,
+        list_revisions_use_case,
+        get_revision_use_case,
+        diff_revisions_use_case,
+        restore_revision_use_case,
         list_owned_use_case,
         list_shared_use_case,
+        search_use_case,
         list_recipe_shares_use_case,
         create_share_use_case,
         delete_share_use_case,
+        create_collection_use_case,
+        rename_collection_use_case,
+        delete_collection_use_case,
+        list_collections_use_case,
+        list_shared_collections_use_case,
+        get_collection_use_case,
+        add_collection_recipe_use_case,
+        remove_collection_recipe_use_case,
+        list_collection_shares_use_case,
+        create_collection_share_use_case,
+        delete_collection_share_use_case,
+        cursor_codec,
     };
 
     let public_routes = Router::new()
         .route("/health", get(health))
-        .route("/api/recipes/generate", post(handlers::generate_recipe));
+        .route("/api/recipes/generate", post(handlers::generate_recipe))
+        .route(
+            "/api/recipes/generate/stream",
+            post(handlers::generate_recipe_stream),
+        )
+        .route("/api/recipes/refine", post(handlers::refine_recipe))
+        .layer(axum::middleware::from_fn(identify_user));
 
     let protected_routes = Router::new()
         .route("/api/recipes/shared", get(handlers::list_shared_recipes))
+        .route("/api/generations", get(handlers::list_generations))
+        .route(
+            "/api/generations/{id}/save",
+            post(handlers::save_generation),
+        )
+        .route("/api/recipes/search", get(handlers::search_recipes))
         .route(
             "/api/recipes",
             post(handlers::save_recipe).get(handlers::list_my_recipes),
         )
-        .route("/api/recipes/{id}", get(handlers::get_recipe))
+        .route(
+            "/api/recipes/{id}",
+            get(handlers::get_recipe)
+                .put(handlers::update_recipe)
+                .delete(handlers::delete_recipe),
+        )
+        .route("/api/recipes/{id}/tags", put(handlers::set_recipe_tags))
+        .route(
+            "/api/recipes/{id}/refine",
+            post(handlers::refine_saved_recipe),
+        )
+        .route("/api/recipes/{id}/scaled", get(handlers::get_scaled_recipe))
+        .route(
+            "/api/recipes/{id}/nutrition",
+            get(handlers::get_recipe_nutrition),
+        )
+        .route(
+            "/api/recipes/{id}/substitutions",
+            get(handlers::get_recipe_substitutions),
+        )
+        .route(
+            "/api/recipes/{id}/revisions",
+            get(handlers::list_recipe_revisions),
+        )
+        .route(
+            "/api/recipes/{id}/revisions/diff",
+            get(handlers::diff_recipe_revisions),
+        )
+        .route(
+            "/api/recipes/{id}/revisions/{revision}",
+            get(handlers::get_recipe_revision),
+        )
+        .route(
+            "/api/recipes/{id}/revisions/{revision}/restore",
+            post(handlers::restore_recipe_revision),
+        )
         .route(
             "/api/recipes/{id}/shares",
             post(handlers::create_share).get(handlers::list_recipe_shares),
@@ -65,6 +188,37 @@ pub fn create_router<
             "/api/recipes/{recipe_id}/shares/{user_id}",
             delete(handlers::delete_share),
         )
+        .route(
+            "/api/collections",
+            post(handlers::create_collection).get(handlers::list_collections),
+        )
+        .route(
+            "/api/collections/shared",
+            get(handlers::list_shared_collections),
+        )
+        .route(
+            "/api/collections/{id}",
+            get(handlers::get_collection)
+                .put(handlers::rename_collection)
+                .delete(handlers::delete_collection),
+        )
+        .route(
+            "/api/collections/{id}/recipes",
+            post(handlers::add_collection_recipe),
+        )
+        .route(
+            "/api/collections/{id}/recipes/{recipe_id}",
+            delete(handlers::remove_collection_recipe),
+        )
+        .route(
+            "/api/collections/{id}/shares",
+            post(handlers::create_collection_share).get(handlers::list_collection_shares),
+        )
+        .route(
+            "/api/collections/{id}/shares/{user_id}",
+            delete(handlers::delete_collection_share),
+        )
+        .route("/api/usage", get(handlers::get_usage))
         .layer(create_clerk_layer());
 
     let openapi = ApiDoc::openapi();
@@ -1,39 +1,121 @@
 use std::sync::Arc;
 
+use super::cursor::CursorCodec;
 use crate::recipes::application::{
-    CreateShareUseCase, DeleteShareUseCase, GenerateRecipeUseCase, GetRecipeUseCase,
-    ListOwnedRecipesUseCase, ListRecipeSharesUseCase, ListSharedRecipesUseCase, SaveRecipeUseCase,
+    AddRecipeToCollectionUseCase, CreateCollectionShareUseCase, CreateCollectionUseCase,
+    CreateShareUseCase, DeleteCollectionShareUseCase, DeleteCollectionUseCase, DeleteRecipeUseCase,
+    DeleteShareUseCase, DiffRecipeRevisionsUseCase, GenerateRecipeCandidatesUseCase,
+    GenerateRecipeUseCase, GetCollectionUseCase, GetGenerationUseCase, GetRecipeRevisionUseCase,
+    GetRecipeUseCase, GetUsageUseCase, ListCollectionSharesUseCase, ListCollectionsUseCase,
+    ListGenerationsUseCase, ListOwnedRecipesUseCase, ListRecipeRevisionsUseCase,
+    ListRecipeSharesUseCase, ListSharedCollectionsUseCase, ListSharedRecipesUseCase,
+    RecordGenerationUseCase, RecordUsageUseCase, RefineRecipeUseCase,
+    RemoveRecipeFromCollectionUseCase, RenameCollectionUseCase, RestoreRecipeRevisionUseCase,
+    SaveRecipeUseCase, SearchRecipesUseCase, SetRecipeTagsUseCase, StreamRecipeUseCase,
+    SuggestSubstitutesUseCase, UpdateRecipeUseCase,
+};
+use crate::recipes::domain::{
+    CollectionRepository, GenerationCache, GenerationRepository, LlmService, RecipeRepository,
+    RecipeShareRepository, UsageRepository,
 };
-use crate::recipes::domain::{LlmService, RecipeRepository, RecipeShareRepository};
 
 pub struct AppState<
     T: LlmService + 'static,
     R: RecipeRepository + 'static,
     S: RecipeShareRepository + 'static,
+    C: CollectionRepository + 'static,
+    G: GenerationCache + 'static,
+    U: UsageRepository + 'static,
+    H: GenerationRepository + 'static,
 > {
-    pub generate_use_case: Arc<GenerateRecipeUseCase<T>>,
+    pub generate_use_case: Arc<GenerateRecipeUseCase<T, G>>,
+    pub stream_use_case: Arc<StreamRecipeUseCase<T, G>>,
+    pub candidates_use_case: Arc<GenerateRecipeCandidatesUseCase<T, G>>,
+    pub refine_use_case: Arc<RefineRecipeUseCase<T>>,
+    pub substitutes_use_case: Arc<SuggestSubstitutesUseCase<T>>,
+    pub usage_use_case: Arc<GetUsageUseCase<U>>,
+    pub record_usage_use_case: Arc<RecordUsageUseCase<U>>,
+    pub record_generation_use_case: Arc<RecordGenerationUseCase<H>>,
+    pub list_generations_use_case: Arc<ListGenerationsUseCase<H>>,
+    pub get_generation_use_case: Arc<GetGenerationUseCase<H>>,
     pub save_use_case: Arc<SaveRecipeUseCase<R>>,
     pub get_use_case: Arc<GetRecipeUseCase<R, S>>,
+    pub update_use_case: Arc<UpdateRecipeUseCase<R>>,
+    pub set_tags_use_case: Arc<SetRecipeTagsUseCase<R>>,
+    pub delete_use_case: Arc<DeleteRecipeUseCase<R>>,
+    pub list_revisions_use_case: Arc<ListRecipeRevisionsUseCase<R>>,
+    pub get_revision_use_case: Arc<GetRecipeRevisionUseCase<R>>,
+    pub diff_revisions_use_case: Arc<DiffRecipeRevisionsUseCase<R>>,
+    pub restore_revision_use_case: Arc<RestoreRecipeRevisionUseCase<R>>,
     pub list_owned_use_case: Arc<ListOwnedRecipesUseCase<R>>,
     pub list_shared_use_case: Arc<ListSharedRecipesUseCase<R>>,
+    pub search_use_case: Arc<SearchRecipesUseCase<R>>,
     pub list_recipe_shares_use_case: Arc<ListRecipeSharesUseCase<S>>,
     pub create_share_use_case: Arc<CreateShareUseCase<R, S>>,
     pub delete_share_use_case: Arc<DeleteShareUseCase<R, S>>,
+    pub create_collection_use_case: Arc<CreateCollectionUseCase<C>>,
+    pub rename_collection_use_case: Arc<RenameCollectionUseCase<C>>,
+    pub delete_collection_use_case: Arc<DeleteCollectionUseCase<C>>,
+    pub list_collections_use_case: Arc<ListCollectionsUseCase<C>>,
+    pub list_shared_collections_use_case: Arc<ListSharedCollectionsUseCase<C>>,
+    pub get_collection_use_case: Arc<GetCollectionUseCase<C>>,
+    pub add_collection_recipe_use_case: Arc<AddRecipeToCollectionUseCase<C, R, S>>,
+    pub remove_collection_recipe_use_case: Arc<RemoveRecipeFromCollectionUseCase<C>>,
+    pub list_collection_shares_use_case: Arc<ListCollectionSharesUseCase<C>>,
+    pub create_collection_share_use_case: Arc<CreateCollectionShareUseCase<C>>,
+    pub delete_collection_share_use_case: Arc<DeleteCollectionShareUseCase<C>>,
+    pub cursor_codec: Arc<CursorCodec>,
 }
 
-impl<T: LlmService + 'static, R: RecipeRepository + 'static, S: RecipeShareRepository + 'static>
-    Clone for AppState<T, R, S>
+impl<
+    T: LlmService + 'static,
+    R: RecipeRepository + 'static,
+    S: RecipeShareRepository + 'static,
+    C: CollectionRepository + 'static,
+    G: GenerationCache + 'static,
+    U: UsageRepository + 'static,
+    H: GenerationRepository + 'static,
+> Clone for AppState<T, R, S, C, G, U, H>
 {
     fn clone(&self) -> Self {
         Self {
             generate_use_case: Arc::clone(&self.generate_use_case),
+            stream_use_case: Arc::clone(&self.stream_use_case),
+            candidates_use_case: Arc::clone(&self.candidates_use_case),
+            refine_use_case: Arc::clone(&self.refine_use_case),
+            substitutes_use_case: Arc::clone(&self.substitutes_use_case),
+            usage_use_case: Arc::clone(&self.usage_use_case),
+            record_usage_use_case: Arc::clone(&self.record_usage_use_case),
+            record_generation_use_case: Arc::clone(&self.record_generation_use_case),
+            list_generations_use_case: Arc::clone(&self.list_generations_use_case),
+            get_generation_use_case: Arc::clone(&self.get_generation_use_case),
             save_use_case: Arc::clone(&self.save_use_case),
             get_use_case: Arc::clone(&self.get_use_case),
+            update_use_case: Arc::clone(&self.update_use_case),
+            set_tags_use_case: Arc::clone(&self.set_tags_use_case),
+            delete_use_case: Arc::clone(&self.delete_use_case),
+            list_revisions_use_case: Arc::clone(&self.list_revisions_use_case),
+            get_revision_use_case: Arc::clone(&self.get_revision_use_case),
+            diff_revisions_use_case: Arc::clone(&self.diff_revisions_use_case),
+            restore_revision_use_case: Arc::clone(&self.restore_revision_use_case),
             list_owned_use_case: Arc::clone(&self.list_owned_use_case),
             list_shared_use_case: Arc::clone(&self.list_shared_use_case),
+            search_use_case: Arc::clone(&self.search_use_case),
             list_recipe_shares_use_case: Arc::clone(&self.list_recipe_shares_use_case),
             create_share_use_case: Arc::clone(&self.create_share_use_case),
             delete_share_use_case: Arc::clone(&self.delete_share_use_case),
+            create_collection_use_case: Arc::clone(&self.create_collection_use_case),
+            rename_collection_use_case: Arc::clone(&self.rename_collection_use_case),
+            delete_collection_use_case: Arc::clone(&self.delete_collection_use_case),
+            list_collections_use_case: Arc::clone(&self.list_collections_use_case),
+            list_shared_collections_use_case: Arc::clone(&self.list_shared_collections_use_case),
+            get_collection_use_case: Arc::clone(&self.get_collection_use_case),
+            add_collection_recipe_use_case: Arc::clone(&self.add_collection_recipe_use_case),
+            remove_collection_recipe_use_case: Arc::clone(&self.remove_collection_recipe_use_case),
+            list_collection_shares_use_case: Arc::clone(&self.list_collection_shares_use_case),
+            create_collection_share_use_case: Arc::clone(&self.create_collection_share_use_case),
+            delete_collection_share_use_case: Arc::clone(&self.delete_collection_share_use_case),
+            cursor_codec: Arc::clone(&self.cursor_codec),
         }
     }
 }
@@ -1,17 +1,73 @@
+mod add_recipe_to_collection;
+mod create_collection;
+mod create_collection_share;
 mod create_share;
+mod delete_collection;
+mod delete_collection_share;
+mod delete_recipe;
 mod delete_share;
+mod diff_recipe_revisions;
 mod generate_recipe;
+mod generate_recipe_candidates;
+mod get_collection;
+mod get_generation;
 mod get_recipe;
+mod get_recipe_revision;
+mod get_usage;
+mod list_collection_shares;
+mod list_collections;
+mod list_generations;
 mod list_owned_recipes;
+mod list_recipe_revisions;
 mod list_recipe_shares;
+mod list_shared_collections;
 mod list_shared_recipes;
+mod record_generation;
+mod record_usage;
+mod refine_recipe;
+mod remove_recipe_from_collection;
+mod rename_collection;
+mod restore_recipe_revision;
 mod save_recipe;
+mod search_recipes;
+mod set_recipe_tags;
+mod stream_recipe;
+mod suggest_substitutes;
+mod update_recipe;
 
+pub use add_recipe_to_collection::AddRecipeToCollectionUseCase;
+pub use create_collection::CreateCollectionUseCase;
+pub use create_collection_share::CreateCollectionShareUseCase;
 pub use create_share::CreateShareUseCase;
+pub use delete_collection::DeleteCollectionUseCase;
+pub use delete_collection_share::DeleteCollectionShareUseCase;
+pub use delete_recipe::DeleteRecipeUseCase;
 pub use delete_share::DeleteShareUseCase;
+pub use diff_recipe_revisions::DiffRecipeRevisionsUseCase;
 pub use generate_recipe::GenerateRecipeUseCase;
+pub use generate_recipe_candidates::GenerateRecipeCandidatesUseCase;
+pub use get_collection::GetCollectionUseCase;
+pub use get_generation::GetGenerationUseCase;
 pub use get_recipe::GetRecipeUseCase;
+pub use get_recipe_revision::GetRecipeRevisionUseCase;
+pub use get_usage::GetUsageUseCase;
+pub use list_collection_shares::ListCollectionSharesUseCase;
+pub use list_collections::ListCollectionsUseCase;
+pub use list_generations::ListGenerationsUseCase;
 pub use list_owned_recipes::ListOwnedRecipesUseCase;
+pub use list_recipe_revisions::ListRecipeRevisionsUseCase;
 pub use list_recipe_shares::ListRecipeSharesUseCase;
+pub use list_shared_collections::ListSharedCollectionsUseCase;
 pub use list_shared_recipes::ListSharedRecipesUseCase;
+pub use record_generation::RecordGenerationUseCase;
+pub use record_usage::RecordUsageUseCase;
+pub use refine_recipe::RefineRecipeUseCase;
+pub use remove_recipe_from_collection::RemoveRecipeFromCollectionUseCase;
+pub use rename_collection::RenameCollectionUseCase;
+pub use restore_recipe_revision::RestoreRecipeRevisionUseCase;
 pub use save_recipe::SaveRecipeUseCase;
+pub use search_recipes::SearchRecipesUseCase;
+pub use set_recipe_tags::SetRecipeTagsUseCase;
+pub use stream_recipe::StreamRecipeUseCase;
+pub use suggest_substitutes::SuggestSubstitutesUseCase;
+pub use update_recipe::UpdateRecipeUseCase;
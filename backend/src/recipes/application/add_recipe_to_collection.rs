@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{
+    CollectionRepository, RecipeRepository, RecipeShareRepository, RepositoryError,
+};
+
+pub struct AddRecipeToCollectionUseCase<
+    C: CollectionRepository,
+    R: RecipeRepository,
+    S: RecipeShareRepository,
+> {
+    collection_repository: Arc<C>,
+    recipe_repository: Arc<R>,
+    share_repository: Arc<S>,
+}
+
+impl<C: CollectionRepository, R: RecipeRepository, S: RecipeShareRepository>
+    AddRecipeToCollectionUseCase<C, R, S>
+{
+    pub fn new(
+        collection_repository: Arc<C>,
+        recipe_repository: Arc<R>,
+        share_repository: Arc<S>,
+    ) -> Self {
+        Self {
+            collection_repository,
+            recipe_repository,
+            share_repository,
+        }
+    }
+
+    /// Adds a recipe the collection owner owns or that was shared with them.
+    pub async fn execute(
+        &self,
+        collection_id: Uuid,
+        recipe_id: Uuid,
+        owner_id: &str,
+    ) -> Result<(), RepositoryError> {
+        let collection = self.collection_repository.find_by_id(collection_id).await?;
+
+        if collection.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        let recipe = self.recipe_repository.find_by_id(recipe_id).await?;
+
+        if recipe.owner_id != owner_id
+            && !self
+                .share_repository
+                .is_shared_with_user(recipe_id, owner_id)
+                .await?
+        {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        self.collection_repository
+            .add_recipe(collection_id, recipe_id)
+            .await
+    }
+}
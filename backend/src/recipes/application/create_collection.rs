@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use crate::recipes::domain::{Collection, CollectionRepository, RepositoryError};
+
+pub struct CreateCollectionUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> CreateCollectionUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        owner_id: String,
+        name: String,
+    ) -> Result<Collection, RepositoryError> {
+        let collection = Collection::new(owner_id, name);
+        self.collection_repository.save(&collection).await?;
+        Ok(collection)
+    }
+}
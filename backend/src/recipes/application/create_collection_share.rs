@@ -0,0 +1,34 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{CollectionRepository, CollectionShare, RepositoryError};
+
+pub struct CreateCollectionShareUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> CreateCollectionShareUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        collection_id: Uuid,
+        owner_id: &str,
+        share_with_user_id: String,
+    ) -> Result<CollectionShare, RepositoryError> {
+        let collection = self.collection_repository.find_by_id(collection_id).await?;
+
+        if collection.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        let share = CollectionShare::new(collection_id, share_with_user_id);
+        self.collection_repository.create_share(&share).await?;
+
+        Ok(share)
+    }
+}
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{CollectionRepository, RepositoryError};
+
+pub struct DeleteCollectionUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> DeleteCollectionUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    /// Deletes the collection only; the recipes in it are kept.
+    pub async fn execute(
+        &self,
+        collection_id: Uuid,
+        owner_id: &str,
+    ) -> Result<(), RepositoryError> {
+        let collection = self.collection_repository.find_by_id(collection_id).await?;
+
+        if collection.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        self.collection_repository.delete(collection_id).await
+    }
+}
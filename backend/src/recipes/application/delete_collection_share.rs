@@ -0,0 +1,33 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{CollectionRepository, RepositoryError};
+
+pub struct DeleteCollectionShareUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> DeleteCollectionShareUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        collection_id: Uuid,
+        owner_id: &str,
+        shared_user_id: &str,
+    ) -> Result<(), RepositoryError> {
+        let collection = self.collection_repository.find_by_id(collection_id).await?;
+
+        if collection.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        self.collection_repository
+            .delete_share(collection_id, shared_user_id)
+            .await
+    }
+}
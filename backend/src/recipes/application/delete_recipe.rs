@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{RecipeRepository, RepositoryError};
+
+pub struct DeleteRecipeUseCase<R: RecipeRepository> {
+    recipe_repository: Arc<R>,
+}
+
+impl<R: RecipeRepository> DeleteRecipeUseCase<R> {
+    pub fn new(recipe_repository: Arc<R>) -> Self {
+        Self { recipe_repository }
+    }
+
+    pub async fn execute(&self, recipe_id: Uuid, owner_id: &str) -> Result<(), RepositoryError> {
+        let recipe = self.recipe_repository.find_by_id(recipe_id).await?;
+
+        if recipe.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        self.recipe_repository.delete(recipe_id).await
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{RecipeDiff, RecipeRepository, RepositoryError};
+
+pub struct DiffRecipeRevisionsUseCase<R: RecipeRepository> {
+    recipe_repository: Arc<R>,
+}
+
+impl<R: RecipeRepository> DiffRecipeRevisionsUseCase<R> {
+    pub fn new(recipe_repository: Arc<R>) -> Self {
+        Self { recipe_repository }
+    }
+
+    pub async fn execute(
+        &self,
+        recipe_id: Uuid,
+        from_revision: i32,
+        to_revision: i32,
+    ) -> Result<RecipeDiff, RepositoryError> {
+        let from = self
+            .recipe_repository
+            .find_revision(recipe_id, from_revision)
+            .await?;
+        let to = self
+            .recipe_repository
+            .find_revision(recipe_id, to_revision)
+            .await?;
+
+        Ok(RecipeDiff::between(&from, &to))
+    }
+}
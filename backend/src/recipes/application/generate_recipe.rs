@@ -1,22 +1,109 @@
-use crate::recipes::domain::{GeneratedRecipe, LlmError, LlmService};
+use crate::recipes::domain::{
+    DietaryRestriction, GeneratedRecipe, GenerationCache, GenerationKey, LlmError, LlmService,
+    RecipeConstraints, UsageMeter,
+};
 use std::sync::Arc;
 
-pub struct GenerateRecipeUseCase<T: LlmService> {
+/// How many times the model is asked for a recipe before giving up on one
+/// that is valid and respects the requested dietary restrictions and
+/// constraints.
+const MAX_ATTEMPTS: usize = 3;
+
+pub struct GenerateRecipeUseCase<T: LlmService, G: GenerationCache> {
     llm_service: Arc<T>,
+    cache: Arc<G>,
 }
 
-impl<T: LlmService> GenerateRecipeUseCase<T> {
-    pub fn new(llm_service: Arc<T>) -> Self {
-        Self { llm_service }
+impl<T: LlmService, G: GenerationCache> GenerateRecipeUseCase<T, G> {
+    pub fn new(llm_service: Arc<T>, cache: Arc<G>) -> Self {
+        Self { llm_service, cache }
     }
 
-    pub async fn execute(
+    /// A valid recipe for the request, reused from the cache when the same
+    /// request was answered recently. `fresh` skips the lookup; the new
+    /// recipe still replaces the cached one. `reserve` is called once before
+    /// the model is, and its error ends the request. Calls to the model are
+    /// recorded in `usage`.
+    pub async fn execute<E: From<LlmError>, F: AsyncFnOnce() -> Result<(), E>>(
         &self,
         ingredients: Vec<String>,
         dietary_restrictions: Option<Vec<String>>,
-    ) -> Result<GeneratedRecipe, LlmError> {
-        self.llm_service
-            .generate_recipe(ingredients, dietary_restrictions)
-            .await
+        constraints: RecipeConstraints,
+        fresh: bool,
+        usage: &UsageMeter,
+        reserve: F,
+    ) -> Result<GeneratedRecipe, E> {
+        let prompt = self.llm_service.choose_prompt();
+        let key = GenerationKey::new(
+            &prompt.version,
+            &ingredients,
+            dietary_restrictions.as_deref(),
+            &constraints,
+            1,
+        );
+        if !fresh
+            && let Some(recipe) = self
+                .cache
+                .get(&key)
+                .await
+                .and_then(|recipes| recipes.into_iter().next())
+        {
+            return Ok(recipe);
+        }
+
+        let restrictions = dietary_restrictions
+            .as_deref()
+            .map(DietaryRestriction::parse_all)
+            .unwrap_or_default();
+
+        let mut prompt_restrictions = dietary_restrictions;
+        let mut last_error = LlmError::InvalidRecipe(Vec::new());
+
+        reserve().await?;
+
+        for _ in 0..MAX_ATTEMPTS {
+            let recipe = self
+                .llm_service
+                .generate_recipe(
+                    ingredients.clone(),
+                    prompt_restrictions.clone(),
+                    constraints.clone(),
+                    &prompt,
+                    usage,
+                )
+                .await?;
+
+            let mut problems = recipe.validate(&ingredients);
+            problems.extend(recipe.constraint_violations(&constraints));
+            let violations = if problems.is_empty() {
+                recipe.dietary_violations(&restrictions)
+            } else {
+                Vec::new()
+            };
+            if problems.is_empty() && violations.is_empty() {
+                self.cache.put(key, vec![recipe.clone()]).await;
+                return Ok(recipe);
+            }
+
+            // Spell out what went wrong so the next attempt can avoid it.
+            let mut retry_restrictions = prompt_restrictions.unwrap_or_default();
+            retry_restrictions.extend(problems.iter().map(|p| format!("must be fixed: {}", p)));
+            retry_restrictions.extend(violations.iter().map(|v| {
+                format!(
+                    "must not use \"{}\" because the recipe has to be {}",
+                    v.ingredient, v.restriction
+                )
+            }));
+            prompt_restrictions = Some(retry_restrictions);
+
+            last_error = if problems.is_empty() {
+                let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                LlmError::DietaryViolation(details.join("; "))
+            } else {
+                LlmError::InvalidRecipe(problems)
+            };
+        }
+
+        Err(last_error.into())
     }
 }
@@ -0,0 +1,127 @@
+use crate::recipes::domain::{
+    DietaryRestriction, GeneratedRecipe, GenerationCache, GenerationKey, LlmError, LlmService,
+    RecipeConstraints, UsageMeter,
+};
+use std::sync::Arc;
+
+/// How many times the model is asked for candidates before settling for
+/// fewer distinct, valid recipes than requested.
+const MAX_ROUNDS: usize = 2;
+
+pub struct GenerateRecipeCandidatesUseCase<T: LlmService, G: GenerationCache> {
+    llm_service: Arc<T>,
+    cache: Arc<G>,
+    max_candidates: usize,
+}
+
+impl<T: LlmService, G: GenerationCache> GenerateRecipeCandidatesUseCase<T, G> {
+    pub fn new(llm_service: Arc<T>, cache: Arc<G>, max_candidates: usize) -> Self {
+        Self {
+            llm_service,
+            cache,
+            max_candidates: max_candidates.max(1),
+        }
+    }
+
+    /// Up to `count` distinct recipes, never more than the configured
+    /// maximum. Invalid candidates, ones that miss a constraint or break a
+    /// dietary restriction, and near-duplicates of earlier ones are dropped;
+    /// if too few remain, the model is asked once more for different dishes.
+    /// Recipes cached for the same request are reused unless `fresh` is set.
+    /// `reserve` is called once before the model is, and its error ends the
+    /// request. Calls to the model are recorded in `usage`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute<E: From<LlmError>, F: AsyncFnOnce() -> Result<(), E>>(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        count: usize,
+        fresh: bool,
+        usage: &UsageMeter,
+        reserve: F,
+    ) -> Result<Vec<GeneratedRecipe>, E> {
+        let count = count.clamp(1, self.max_candidates);
+        let prompt = self.llm_service.choose_prompt();
+        let key = GenerationKey::new(
+            &prompt.version,
+            &ingredients,
+            dietary_restrictions.as_deref(),
+            &constraints,
+            count,
+        );
+        if !fresh && let Some(recipes) = self.cache.get(&key).await {
+            return Ok(recipes);
+        }
+
+        let restrictions = dietary_restrictions
+            .as_deref()
+            .map(DietaryRestriction::parse_all)
+            .unwrap_or_default();
+
+        let mut prompt_restrictions = dietary_restrictions.clone();
+        let mut accepted: Vec<GeneratedRecipe> = Vec::with_capacity(count);
+        let mut last_error = LlmError::InvalidRecipe(Vec::new());
+
+        reserve().await?;
+
+        for _ in 0..MAX_ROUNDS {
+            let candidates = self
+                .llm_service
+                .generate_recipes(
+                    ingredients.clone(),
+                    prompt_restrictions.clone(),
+                    constraints.clone(),
+                    count - accepted.len(),
+                    &prompt,
+                    usage,
+                )
+                .await?;
+
+            for recipe in candidates {
+                if accepted.len() == count {
+                    break;
+                }
+
+                let mut problems = recipe.validate(&ingredients);
+                problems.extend(recipe.constraint_violations(&constraints));
+                if !problems.is_empty() {
+                    last_error = LlmError::InvalidRecipe(problems);
+                    continue;
+                }
+                let violations = recipe.dietary_violations(&restrictions);
+                if !violations.is_empty() {
+                    let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                    last_error = LlmError::DietaryViolation(details.join("; "));
+                    continue;
+                }
+                if accepted
+                    .iter()
+                    .any(|other| other.is_near_duplicate_of(&recipe))
+                {
+                    continue;
+                }
+                accepted.push(recipe);
+            }
+
+            if accepted.len() == count {
+                break;
+            }
+
+            // Steer the next round away from the dishes already chosen.
+            let mut retry_restrictions = dietary_restrictions.clone().unwrap_or_default();
+            retry_restrictions.extend(
+                accepted
+                    .iter()
+                    .map(|recipe| format!("must be a different dish from \"{}\"", recipe.title)),
+            );
+            prompt_restrictions = Some(retry_restrictions);
+        }
+
+        if accepted.is_empty() {
+            return Err(last_error.into());
+        }
+        self.cache.put(key, accepted.clone()).await;
+        Ok(accepted)
+    }
+}
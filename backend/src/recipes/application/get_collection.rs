@@ -0,0 +1,41 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{Collection, CollectionRepository, Recipe, RepositoryError};
+
+pub struct GetCollectionUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> GetCollectionUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    /// The collection and the recipes in it that the user can see.
+    pub async fn execute(
+        &self,
+        collection_id: Uuid,
+        user_id: &str,
+    ) -> Result<(Collection, Vec<Recipe>), RepositoryError> {
+        let collection = self.collection_repository.find_by_id(collection_id).await?;
+
+        if collection.owner_id != user_id
+            && !self
+                .collection_repository
+                .is_shared_with_user(collection_id, user_id)
+                .await?
+        {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        let recipes = self
+            .collection_repository
+            .find_recipes(collection_id, user_id)
+            .await?;
+
+        Ok((collection, recipes))
+    }
+}
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{Generation, GenerationRepository, RepositoryError};
+
+pub struct GetGenerationUseCase<H: GenerationRepository> {
+    repository: Arc<H>,
+}
+
+impl<H: GenerationRepository> GetGenerationUseCase<H> {
+    pub fn new(repository: Arc<H>) -> Self {
+        Self { repository }
+    }
+
+    /// The generation, which only the user it was made for may see.
+    pub async fn execute(&self, id: Uuid, user_id: &str) -> Result<Generation, RepositoryError> {
+        let generation = self.repository.find_by_id(id).await?;
+
+        if generation.user_id == user_id {
+            Ok(generation)
+        } else {
+            Err(RepositoryError::AccessDenied)
+        }
+    }
+}
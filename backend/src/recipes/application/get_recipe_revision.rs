@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{RecipeRepository, RecipeRevision, RepositoryError};
+
+pub struct GetRecipeRevisionUseCase<R: RecipeRepository> {
+    recipe_repository: Arc<R>,
+}
+
+impl<R: RecipeRepository> GetRecipeRevisionUseCase<R> {
+    pub fn new(recipe_repository: Arc<R>) -> Self {
+        Self { recipe_repository }
+    }
+
+    pub async fn execute(
+        &self,
+        recipe_id: Uuid,
+        revision: i32,
+    ) -> Result<RecipeRevision, RepositoryError> {
+        self.recipe_repository
+            .find_revision(recipe_id, revision)
+            .await
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::recipes::domain::{
+    QuotaPolicy, RepositoryError, Requester, TokenPrices, UsageRepository, UsageSummary, day_start,
+    month_start,
+};
+
+pub struct GetUsageUseCase<U: UsageRepository> {
+    repository: Arc<U>,
+    policy: QuotaPolicy,
+    prices: TokenPrices,
+}
+
+impl<U: UsageRepository> GetUsageUseCase<U> {
+    pub fn new(repository: Arc<U>, policy: QuotaPolicy, prices: TokenPrices) -> Self {
+        Self {
+            repository,
+            policy,
+            prices,
+        }
+    }
+
+    /// What `requester` has used today and this month, UTC, against the
+    /// quotas that apply to them.
+    pub async fn execute(&self, requester: &Requester) -> Result<UsageSummary, RepositoryError> {
+        let now = Utc::now();
+        let today = self
+            .repository
+            .totals_since(requester, day_start(now))
+            .await?;
+        let this_month = self
+            .repository
+            .totals_since(requester, month_start(now))
+            .await?;
+
+        Ok(UsageSummary {
+            today,
+            this_month,
+            limits: self.policy.limits_for(requester),
+            prices: self.prices,
+        })
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{CollectionRepository, CollectionShare, RepositoryError};
+
+pub struct ListCollectionSharesUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> ListCollectionSharesUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        collection_id: Uuid,
+        owner_id: &str,
+    ) -> Result<Vec<CollectionShare>, RepositoryError> {
+        let collection = self.collection_repository.find_by_id(collection_id).await?;
+
+        if collection.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        self.collection_repository.find_shares(collection_id).await
+    }
+}
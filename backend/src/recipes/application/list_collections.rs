@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use crate::recipes::domain::{Collection, CollectionRepository, RepositoryError};
+
+pub struct ListCollectionsUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> ListCollectionsUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    pub async fn execute(&self, owner_id: &str) -> Result<Vec<Collection>, RepositoryError> {
+        self.collection_repository.find_by_owner(owner_id).await
+    }
+}
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use crate::recipes::domain::{
+    Generation, GenerationRepository, Page, PageRequest, RepositoryError,
+};
+
+pub struct ListGenerationsUseCase<H: GenerationRepository> {
+    repository: Arc<H>,
+}
+
+impl<H: GenerationRepository> ListGenerationsUseCase<H> {
+    pub fn new(repository: Arc<H>) -> Self {
+        Self { repository }
+    }
+
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        page: PageRequest,
+    ) -> Result<Page<Generation>, RepositoryError> {
+        self.repository.find_by_user(user_id, &page).await
+    }
+}
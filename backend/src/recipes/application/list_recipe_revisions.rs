@@ -0,0 +1,18 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{RecipeRepository, RecipeRevision, RepositoryError};
+
+pub struct ListRecipeRevisionsUseCase<R: RecipeRepository> {
+    recipe_repository: Arc<R>,
+}
+
+impl<R: RecipeRepository> ListRecipeRevisionsUseCase<R> {
+    pub fn new(recipe_repository: Arc<R>) -> Self {
+        Self { recipe_repository }
+    }
+
+    pub async fn execute(&self, recipe_id: Uuid) -> Result<Vec<RecipeRevision>, RepositoryError> {
+        self.recipe_repository.find_revisions(recipe_id).await
+    }
+}
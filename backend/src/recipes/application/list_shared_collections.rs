@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use crate::recipes::domain::{Collection, CollectionRepository, RepositoryError};
+
+pub struct ListSharedCollectionsUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> ListSharedCollectionsUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    pub async fn execute(&self, user_id: &str) -> Result<Vec<Collection>, RepositoryError> {
+        self.collection_repository
+            .find_shared_with_user(user_id)
+            .await
+    }
+}
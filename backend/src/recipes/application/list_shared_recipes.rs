@@ -1,4 +1,4 @@
-use crate::recipes::domain::{Recipe, RecipeRepository, RepositoryError};
+use crate::recipes::domain::{Page, PageRequest, Recipe, RecipeRepository, RepositoryError};
 use std::sync::Arc;
 
 pub struct ListSharedRecipesUseCase<T: RecipeRepository> {
@@ -10,7 +10,11 @@ impl<T: RecipeRepository> ListSharedRecipesUseCase<T> {
         Self { repository }
     }
 
-    pub async fn execute(&self, user_id: &str) -> Result<Vec<Recipe>, RepositoryError> {
-        self.repository.find_shared_with_user(user_id).await
+    pub async fn execute(
+        &self,
+        user_id: &str,
+        page: PageRequest,
+    ) -> Result<Page<Recipe>, RepositoryError> {
+        self.repository.find_shared_with_user(user_id, &page).await
     }
 }
@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use crate::recipes::domain::{Generation, GenerationRepository, RepositoryError};
+
+pub struct RecordGenerationUseCase<H: GenerationRepository> {
+    repository: Arc<H>,
+}
+
+impl<H: GenerationRepository> RecordGenerationUseCase<H> {
+    pub fn new(repository: Arc<H>) -> Self {
+        Self { repository }
+    }
+
+    pub async fn execute(&self, generation: &Generation) -> Result<(), RepositoryError> {
+        self.repository.save(generation).await
+    }
+}
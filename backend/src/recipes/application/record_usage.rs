@@ -0,0 +1,49 @@
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{
+    QuotaPolicy, RepositoryError, Requester, UsageMeter, UsageRepository, day_start, month_start,
+};
+
+pub struct RecordUsageUseCase<U: UsageRepository> {
+    repository: Arc<U>,
+    policy: QuotaPolicy,
+}
+
+impl<U: UsageRepository> RecordUsageUseCase<U> {
+    pub fn new(repository: Arc<U>, policy: QuotaPolicy) -> Self {
+        Self { repository, policy }
+    }
+
+    /// Counts a generation for `requester` before the model is called, so
+    /// concurrent requests cannot all get through on the last one left.
+    /// Returns `None` when either of their quotas is used up.
+    pub async fn reserve(&self, requester: &Requester) -> Result<Option<Uuid>, RepositoryError> {
+        let now = Utc::now();
+        self.repository
+            .reserve(
+                requester,
+                self.policy.limits_for(requester),
+                day_start(now),
+                month_start(now),
+            )
+            .await
+    }
+
+    /// Settles `reservation` with the tokens metered by `usage` if the model
+    /// was called, or gives it back if it was not after all, say because its
+    /// circuit breaker was open. Returns whether a generation was recorded.
+    pub async fn execute(
+        &self,
+        reservation: Uuid,
+        usage: &UsageMeter,
+    ) -> Result<bool, RepositoryError> {
+        if !usage.called_model() {
+            self.repository.release(reservation).await?;
+            return Ok(false);
+        }
+        self.repository.settle(reservation, usage.usage()).await?;
+        Ok(true)
+    }
+}
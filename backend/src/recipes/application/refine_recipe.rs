@@ -0,0 +1,57 @@
+use crate::recipes::domain::{GeneratedRecipe, LlmError, LlmService, RecipeViolation, UsageMeter};
+use std::sync::Arc;
+
+/// How many times the model is asked for a revision before giving up on one
+/// that is valid.
+const MAX_ATTEMPTS: usize = 3;
+
+pub struct RefineRecipeUseCase<T: LlmService> {
+    llm_service: Arc<T>,
+}
+
+impl<T: LlmService> RefineRecipeUseCase<T> {
+    pub fn new(llm_service: Arc<T>) -> Self {
+        Self { llm_service }
+    }
+
+    /// Revises `recipe` as `instruction` asks. The instruction may call for
+    /// ingredients the recipe did not have, so only the recipe's shape is
+    /// checked, not where its ingredients came from.
+    pub async fn execute(
+        &self,
+        recipe: GeneratedRecipe,
+        instruction: String,
+        usage: &UsageMeter,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        let prompt = self.llm_service.choose_prompt();
+        let mut prompt_instruction = instruction.clone();
+        let mut last_problems = Vec::new();
+
+        for _ in 0..MAX_ATTEMPTS {
+            let refined = self
+                .llm_service
+                .refine_recipe(recipe.clone(), prompt_instruction, &prompt, usage)
+                .await?;
+
+            let problems: Vec<RecipeViolation> = refined
+                .validate(&recipe.ingredients)
+                .into_iter()
+                .filter(|p| !matches!(p, RecipeViolation::UnlistedIngredient(_)))
+                .collect();
+            if problems.is_empty() {
+                return Ok(refined);
+            }
+
+            // Spell out what went wrong so the next attempt can avoid it.
+            let details: Vec<String> = problems.iter().map(ToString::to_string).collect();
+            prompt_instruction = format!(
+                "{}\nThe revised recipe must also fix these problems: {}",
+                instruction,
+                details.join("; ")
+            );
+            last_problems = problems;
+        }
+
+        Err(LlmError::InvalidRecipe(last_problems))
+    }
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{Collection, CollectionRepository, RepositoryError};
+
+pub struct RenameCollectionUseCase<C: CollectionRepository> {
+    collection_repository: Arc<C>,
+}
+
+impl<C: CollectionRepository> RenameCollectionUseCase<C> {
+    pub fn new(collection_repository: Arc<C>) -> Self {
+        Self {
+            collection_repository,
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        collection_id: Uuid,
+        owner_id: &str,
+        name: String,
+    ) -> Result<Collection, RepositoryError> {
+        let mut collection = self.collection_repository.find_by_id(collection_id).await?;
+
+        if collection.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        collection.name = name.trim().to_string();
+        self.collection_repository.rename(&collection).await?;
+
+        Ok(collection)
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{Recipe, RecipeRepository, RepositoryError};
+
+pub struct RestoreRecipeRevisionUseCase<R: RecipeRepository> {
+    recipe_repository: Arc<R>,
+}
+
+impl<R: RecipeRepository> RestoreRecipeRevisionUseCase<R> {
+    pub fn new(recipe_repository: Arc<R>) -> Self {
+        Self { recipe_repository }
+    }
+
+    /// Restoring never rewrites history: the old content is saved as a new
+    /// revision on top of the current one.
+    pub async fn execute(
+        &self,
+        recipe_id: Uuid,
+        owner_id: &str,
+        revision: i32,
+    ) -> Result<Recipe, RepositoryError> {
+        let mut recipe = self.recipe_repository.find_by_id(recipe_id).await?;
+
+        if recipe.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        let revision = self
+            .recipe_repository
+            .find_revision(recipe_id, revision)
+            .await?;
+
+        recipe.apply_changes(revision.content());
+        self.recipe_repository.update(&recipe, owner_id).await?;
+
+        Ok(recipe)
+    }
+}
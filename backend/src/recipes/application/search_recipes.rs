@@ -0,0 +1,16 @@
+use crate::recipes::domain::{Recipe, RecipeRepository, RecipeSearch, RepositoryError};
+use std::sync::Arc;
+
+pub struct SearchRecipesUseCase<T: RecipeRepository> {
+    repository: Arc<T>,
+}
+
+impl<T: RecipeRepository> SearchRecipesUseCase<T> {
+    pub fn new(repository: Arc<T>) -> Self {
+        Self { repository }
+    }
+
+    pub async fn execute(&self, search: RecipeSearch) -> Result<Vec<Recipe>, RepositoryError> {
+        self.repository.search(&search).await
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{Recipe, RecipeRepository, RepositoryError};
+
+pub struct SetRecipeTagsUseCase<R: RecipeRepository> {
+    recipe_repository: Arc<R>,
+}
+
+impl<R: RecipeRepository> SetRecipeTagsUseCase<R> {
+    pub fn new(recipe_repository: Arc<R>) -> Self {
+        Self { recipe_repository }
+    }
+
+    pub async fn execute(
+        &self,
+        recipe_id: Uuid,
+        owner_id: &str,
+        tags: Vec<String>,
+    ) -> Result<Recipe, RepositoryError> {
+        let mut recipe = self.recipe_repository.find_by_id(recipe_id).await?;
+
+        if recipe.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        recipe.set_tags(tags);
+        self.recipe_repository.update_tags(&recipe).await?;
+
+        Ok(recipe)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::recipes::domain::{
+    DietaryRestriction, GeneratedRecipe, GenerationCache, GenerationKey, LlmError, LlmService,
+    RecipeConstraints, RecipeStreamEvent, RecipeStreamParser, UsageMeter,
+};
+use std::sync::Arc;
+
+pub struct StreamRecipeUseCase<T: LlmService, G: GenerationCache> {
+    llm_service: Arc<T>,
+    cache: Arc<G>,
+}
+
+impl<T: LlmService, G: GenerationCache> StreamRecipeUseCase<T, G> {
+    pub fn new(llm_service: Arc<T>, cache: Arc<G>) -> Self {
+        Self { llm_service, cache }
+    }
+
+    /// Generates a recipe, reporting its parts through `on_event` while the
+    /// model writes them. Parts already reported cannot be taken back, so a
+    /// recipe that is invalid, misses a constraint or breaks a dietary
+    /// restriction is rejected rather than regenerated. A cached recipe is
+    /// reported all at once unless `fresh` is set. `reserve` is called before
+    /// the model is, and its error ends the request. Calls to the model are
+    /// recorded in `usage`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute<
+        E: From<LlmError>,
+        R: AsyncFnOnce() -> Result<(), E>,
+        F: FnMut(RecipeStreamEvent) + Send,
+    >(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        fresh: bool,
+        usage: &UsageMeter,
+        reserve: R,
+        mut on_event: F,
+    ) -> Result<GeneratedRecipe, E> {
+        let restrictions = dietary_restrictions
+            .as_deref()
+            .map(DietaryRestriction::parse_all)
+            .unwrap_or_default();
+
+        let mut parser = RecipeStreamParser::new();
+        let prompt = self.llm_service.choose_prompt();
+        let key = GenerationKey::new(
+            &prompt.version,
+            &ingredients,
+            dietary_restrictions.as_deref(),
+            &constraints,
+            1,
+        );
+        if !fresh
+            && let Some(recipe) = self
+                .cache
+                .get(&key)
+                .await
+                .and_then(|recipes| recipes.into_iter().next())
+        {
+            if let Ok(text) = serde_json::to_string(&recipe) {
+                parser.push(&text).into_iter().for_each(&mut on_event);
+            }
+            return Ok(recipe);
+        }
+
+        reserve().await?;
+        let recipe = self
+            .llm_service
+            .stream_recipe(
+                ingredients.clone(),
+                dietary_restrictions,
+                constraints.clone(),
+                &prompt,
+                usage,
+                |text| parser.push(text).into_iter().for_each(&mut on_event),
+            )
+            .await?;
+
+        let mut problems = recipe.validate(&ingredients);
+        problems.extend(recipe.constraint_violations(&constraints));
+        if !problems.is_empty() {
+            return Err(LlmError::InvalidRecipe(problems).into());
+        }
+
+        let violations = recipe.dietary_violations(&restrictions);
+        if !violations.is_empty() {
+            let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+            return Err(LlmError::DietaryViolation(details.join("; ")).into());
+        }
+
+        self.cache.put(key, vec![recipe.clone()]).await;
+        Ok(recipe)
+    }
+}
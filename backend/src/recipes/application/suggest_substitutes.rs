@@ -0,0 +1,62 @@
+use crate::recipes::domain::{
+    LlmError, LlmService, Recipe, Substitute, SubstituteSource, Substitutions, UsageMeter,
+    curated_substitutes,
+};
+use std::sync::Arc;
+
+pub struct SuggestSubstitutesUseCase<T: LlmService> {
+    llm_service: Arc<T>,
+}
+
+impl<T: LlmService> SuggestSubstitutesUseCase<T> {
+    pub fn new(llm_service: Arc<T>) -> Self {
+        Self { llm_service }
+    }
+
+    /// Substitutes for `ingredient` in `recipe`, or `None` when the recipe
+    /// does not use it. The curated table is tried first; the AI is only
+    /// asked about ingredients the table does not cover, after `reserve`
+    /// has been called. Its error ends the request.
+    pub async fn execute<E: From<LlmError>, F: AsyncFnOnce() -> Result<(), E>>(
+        &self,
+        recipe: &Recipe,
+        ingredient: &str,
+        usage: &UsageMeter,
+        reserve: F,
+    ) -> Result<Option<Substitutions>, E> {
+        let Some(line) = recipe.find_ingredient(ingredient) else {
+            return Ok(None);
+        };
+        let restrictions = recipe.tagged_restrictions();
+
+        let mut source = SubstituteSource::Curated;
+        let mut suggestions = curated_substitutes(line);
+        if suggestions.is_empty() {
+            let prompt_restrictions = (!restrictions.is_empty())
+                .then(|| restrictions.iter().map(ToString::to_string).collect());
+            reserve().await?;
+            suggestions = self
+                .llm_service
+                .suggest_substitutes(
+                    recipe.content(),
+                    line.clone(),
+                    prompt_restrictions,
+                    &self.llm_service.choose_prompt(),
+                    usage,
+                )
+                .await?;
+            source = SubstituteSource::Ai;
+        }
+
+        let substitutes = suggestions
+            .into_iter()
+            .map(|suggestion| Substitute::new(suggestion, source, &restrictions))
+            .collect();
+
+        Ok(Some(Substitutions {
+            ingredient: line.clone(),
+            restrictions,
+            substitutes,
+        }))
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::recipes::domain::{GeneratedRecipe, Recipe, RecipeRepository, RepositoryError};
+
+pub struct UpdateRecipeUseCase<R: RecipeRepository> {
+    recipe_repository: Arc<R>,
+}
+
+impl<R: RecipeRepository> UpdateRecipeUseCase<R> {
+    pub fn new(recipe_repository: Arc<R>) -> Self {
+        Self { recipe_repository }
+    }
+
+    pub async fn execute(
+        &self,
+        recipe_id: Uuid,
+        owner_id: &str,
+        changes: GeneratedRecipe,
+    ) -> Result<Recipe, RepositoryError> {
+        let mut recipe = self.recipe_repository.find_by_id(recipe_id).await?;
+
+        if recipe.owner_id != owner_id {
+            return Err(RepositoryError::AccessDenied);
+        }
+
+        recipe.apply_changes(changes);
+        self.recipe_repository.update(&recipe, owner_id).await?;
+
+        Ok(recipe)
+    }
+}
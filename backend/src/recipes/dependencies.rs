@@ -2,41 +2,184 @@ use std::sync::Arc;
 
 use sqlx::PgPool;
 
+use crate::recipes::adapters::CursorCodec;
 use crate::recipes::application::{
-    CreateShareUseCase, DeleteShareUseCase, GenerateRecipeUseCase, GetRecipeUseCase,
-    ListOwnedRecipesUseCase, ListRecipeSharesUseCase, ListSharedRecipesUseCase, SaveRecipeUseCase,
+    AddRecipeToCollectionUseCase, CreateCollectionShareUseCase, CreateCollectionUseCase,
+    CreateShareUseCase, DeleteCollectionShareUseCase, DeleteCollectionUseCase, DeleteRecipeUseCase,
+    DeleteShareUseCase, DiffRecipeRevisionsUseCase, GenerateRecipeCandidatesUseCase,
+    GenerateRecipeUseCase, GetCollectionUseCase, GetGenerationUseCase, GetRecipeRevisionUseCase,
+    GetRecipeUseCase, GetUsageUseCase, ListCollectionSharesUseCase, ListCollectionsUseCase,
+    ListGenerationsUseCase, ListOwnedRecipesUseCase, ListRecipeRevisionsUseCase,
+    ListRecipeSharesUseCase, ListSharedCollectionsUseCase, ListSharedRecipesUseCase,
+    RecordGenerationUseCase, RecordUsageUseCase, RefineRecipeUseCase,
+    RemoveRecipeFromCollectionUseCase, RenameCollectionUseCase, RestoreRecipeRevisionUseCase,
+    SaveRecipeUseCase, SearchRecipesUseCase, SetRecipeTagsUseCase, StreamRecipeUseCase,
+    SuggestSubstitutesUseCase, UpdateRecipeUseCase,
+};
+use crate::recipes::domain::{QuotaLimits, QuotaPolicy, TokenPrices};
+use crate::recipes::infrastructure::{
+    ConfiguredLlm, GenerationCacheStore, PgCollectionRepository, PgGenerationRepository,
+    PgRecipeRepository, PgRecipeShareRepository, PgUsageRepository,
 };
-use crate::recipes::infrastructure::{OpenAiClient, PgRecipeRepository, PgRecipeShareRepository};
 use crate::shared::config::AppConfig;
 
+/// A configured quota, where 0 means unlimited.
+fn quota(limit: u32) -> Option<i64> {
+    (limit > 0).then_some(i64::from(limit))
+}
+
 pub struct RecipeDependencies {
-    pub generate_use_case: Arc<GenerateRecipeUseCase<OpenAiClient>>,
+    pub generate_use_case: Arc<GenerateRecipeUseCase<ConfiguredLlm, GenerationCacheStore>>,
+    pub stream_use_case: Arc<StreamRecipeUseCase<ConfiguredLlm, GenerationCacheStore>>,
+    pub candidates_use_case:
+        Arc<GenerateRecipeCandidatesUseCase<ConfiguredLlm, GenerationCacheStore>>,
+    pub refine_use_case: Arc<RefineRecipeUseCase<ConfiguredLlm>>,
+    pub substitutes_use_case: Arc<SuggestSubstitutesUseCase<ConfiguredLlm>>,
+    pub usage_use_case: Arc<GetUsageUseCase<PgUsageRepository>>,
+    pub record_usage_use_case: Arc<RecordUsageUseCase<PgUsageRepository>>,
+    pub record_generation_use_case: Arc<RecordGenerationUseCase<PgGenerationRepository>>,
+    pub list_generations_use_case: Arc<ListGenerationsUseCase<PgGenerationRepository>>,
+    pub get_generation_use_case: Arc<GetGenerationUseCase<PgGenerationRepository>>,
     pub save_use_case: Arc<SaveRecipeUseCase<PgRecipeRepository>>,
     pub get_use_case: Arc<GetRecipeUseCase<PgRecipeRepository, PgRecipeShareRepository>>,
+    pub update_use_case: Arc<UpdateRecipeUseCase<PgRecipeRepository>>,
+    pub set_tags_use_case: Arc<SetRecipeTagsUseCase<PgRecipeRepository>>,
+    pub delete_use_case: Arc<DeleteRecipeUseCase<PgRecipeRepository>>,
+    pub list_revisions_use_case: Arc<ListRecipeRevisionsUseCase<PgRecipeRepository>>,
+    pub get_revision_use_case: Arc<GetRecipeRevisionUseCase<PgRecipeRepository>>,
+    pub diff_revisions_use_case: Arc<DiffRecipeRevisionsUseCase<PgRecipeRepository>>,
+    pub restore_revision_use_case: Arc<RestoreRecipeRevisionUseCase<PgRecipeRepository>>,
     pub list_owned_use_case: Arc<ListOwnedRecipesUseCase<PgRecipeRepository>>,
     pub list_shared_use_case: Arc<ListSharedRecipesUseCase<PgRecipeRepository>>,
+    pub search_use_case: Arc<SearchRecipesUseCase<PgRecipeRepository>>,
     pub list_recipe_shares_use_case: Arc<ListRecipeSharesUseCase<PgRecipeShareRepository>>,
     pub create_share_use_case: Arc<CreateShareUseCase<PgRecipeRepository, PgRecipeShareRepository>>,
     pub delete_share_use_case: Arc<DeleteShareUseCase<PgRecipeRepository, PgRecipeShareRepository>>,
+    pub create_collection_use_case: Arc<CreateCollectionUseCase<PgCollectionRepository>>,
+    pub rename_collection_use_case: Arc<RenameCollectionUseCase<PgCollectionRepository>>,
+    pub delete_collection_use_case: Arc<DeleteCollectionUseCase<PgCollectionRepository>>,
+    pub list_collections_use_case: Arc<ListCollectionsUseCase<PgCollectionRepository>>,
+    pub list_shared_collections_use_case: Arc<ListSharedCollectionsUseCase<PgCollectionRepository>>,
+    pub get_collection_use_case: Arc<GetCollectionUseCase<PgCollectionRepository>>,
+    pub add_collection_recipe_use_case: Arc<
+        AddRecipeToCollectionUseCase<
+            PgCollectionRepository,
+            PgRecipeRepository,
+            PgRecipeShareRepository,
+        >,
+    >,
+    pub remove_collection_recipe_use_case:
+        Arc<RemoveRecipeFromCollectionUseCase<PgCollectionRepository>>,
+    pub list_collection_shares_use_case: Arc<ListCollectionSharesUseCase<PgCollectionRepository>>,
+    pub create_collection_share_use_case: Arc<CreateCollectionShareUseCase<PgCollectionRepository>>,
+    pub delete_collection_share_use_case: Arc<DeleteCollectionShareUseCase<PgCollectionRepository>>,
+    pub cursor_codec: Arc<CursorCodec>,
 }
 
 impl RecipeDependencies {
     pub fn new(config: &AppConfig, db_pool: PgPool) -> Self {
-        let llm_client = Arc::new(OpenAiClient::new(config.openai_api_key.clone()));
+        tracing::info!(
+            provider = ?config.llm.provider,
+            model = %config.llm.model,
+            "Using LLM provider"
+        );
+        let llm_client = Arc::new(ConfiguredLlm::from_config(&config.llm));
+        tracing::info!(
+            backend = ?config.generation_cache.backend,
+            ttl_secs = config.generation_cache.ttl.as_secs(),
+            "Using generation cache"
+        );
+        let generation_cache = Arc::new(GenerationCacheStore::from_config(
+            &config.generation_cache,
+            db_pool.clone(),
+        ));
         let recipe_repository = Arc::new(PgRecipeRepository::new(db_pool.clone()));
-        let share_repository = Arc::new(PgRecipeShareRepository::new(db_pool));
+        let share_repository = Arc::new(PgRecipeShareRepository::new(db_pool.clone()));
+        let collection_repository = Arc::new(PgCollectionRepository::new(db_pool.clone()));
+        let usage_repository = Arc::new(PgUsageRepository::new(db_pool.clone()));
+        let generation_repository = Arc::new(PgGenerationRepository::new(
+            db_pool,
+            config.generation_retention,
+        ));
+        let quota_policy = QuotaPolicy {
+            user: QuotaLimits {
+                daily: quota(config.usage.user_daily_quota),
+                monthly: quota(config.usage.user_monthly_quota),
+            },
+            anonymous: QuotaLimits {
+                daily: quota(config.usage.anonymous_daily_quota),
+                monthly: quota(config.usage.anonymous_monthly_quota),
+            },
+        };
+        let token_prices = TokenPrices {
+            prompt_per_million: config.usage.prompt_price,
+            completion_per_million: config.usage.completion_price,
+        };
+        let cursor_codec = match &config.cursor_secret {
+            Some(secret) => CursorCodec::new(secret.as_bytes()),
+            None => {
+                tracing::warn!("CURSOR_SECRET is not set; page cursors will not survive a restart");
+                CursorCodec::random()
+            }
+        };
 
         Self {
-            generate_use_case: Arc::new(GenerateRecipeUseCase::new(llm_client)),
+            generate_use_case: Arc::new(GenerateRecipeUseCase::new(
+                llm_client.clone(),
+                generation_cache.clone(),
+            )),
+            stream_use_case: Arc::new(StreamRecipeUseCase::new(
+                llm_client.clone(),
+                generation_cache.clone(),
+            )),
+            candidates_use_case: Arc::new(GenerateRecipeCandidatesUseCase::new(
+                llm_client.clone(),
+                generation_cache,
+                config.max_recipe_candidates,
+            )),
+            refine_use_case: Arc::new(RefineRecipeUseCase::new(llm_client.clone())),
+            substitutes_use_case: Arc::new(SuggestSubstitutesUseCase::new(llm_client)),
+            usage_use_case: Arc::new(GetUsageUseCase::new(
+                usage_repository.clone(),
+                quota_policy,
+                token_prices,
+            )),
+            record_usage_use_case: Arc::new(RecordUsageUseCase::new(
+                usage_repository,
+                quota_policy,
+            )),
+            record_generation_use_case: Arc::new(RecordGenerationUseCase::new(
+                generation_repository.clone(),
+            )),
+            list_generations_use_case: Arc::new(ListGenerationsUseCase::new(
+                generation_repository.clone(),
+            )),
+            get_generation_use_case: Arc::new(GetGenerationUseCase::new(generation_repository)),
             save_use_case: Arc::new(SaveRecipeUseCase::new(recipe_repository.clone())),
             get_use_case: Arc::new(GetRecipeUseCase::new(
                 recipe_repository.clone(),
                 share_repository.clone(),
             )),
+            update_use_case: Arc::new(UpdateRecipeUseCase::new(recipe_repository.clone())),
+            set_tags_use_case: Arc::new(SetRecipeTagsUseCase::new(recipe_repository.clone())),
+            delete_use_case: Arc::new(DeleteRecipeUseCase::new(recipe_repository.clone())),
+            list_revisions_use_case: Arc::new(ListRecipeRevisionsUseCase::new(
+                recipe_repository.clone(),
+            )),
+            get_revision_use_case: Arc::new(GetRecipeRevisionUseCase::new(
+                recipe_repository.clone(),
+            )),
+            diff_revisions_use_case: Arc::new(DiffRecipeRevisionsUseCase::new(
+                recipe_repository.clone(),
+            )),
+            restore_revision_use_case: Arc::new(RestoreRecipeRevisionUseCase::new(
+                recipe_repository.clone(),
+            )),
             list_owned_use_case: Arc::new(ListOwnedRecipesUseCase::new(recipe_repository.clone())),
             list_shared_use_case: Arc::new(ListSharedRecipesUseCase::new(
                 recipe_repository.clone(),
             )),
+            search_use_case: Arc::new(SearchRecipesUseCase::new(recipe_repository.clone())),
             list_recipe_shares_use_case: Arc::new(ListRecipeSharesUseCase::new(
                 share_repository.clone(),
             )),
@@ -45,9 +188,45 @@ impl RecipeDependencies {
                 share_repository.clone(),
             )),
             delete_share_use_case: Arc::new(DeleteShareUseCase::new(
+                recipe_repository.clone(),
+                share_repository.clone(),
+            )),
+            create_collection_use_case: Arc::new(CreateCollectionUseCase::new(
+                collection_repository.clone(),
+            )),
+            rename_collection_use_case: Arc::new(RenameCollectionUseCase::new(
+                collection_repository.clone(),
+            )),
+            delete_collection_use_case: Arc::new(DeleteCollectionUseCase::new(
+                collection_repository.clone(),
+            )),
+            list_collections_use_case: Arc::new(ListCollectionsUseCase::new(
+                collection_repository.clone(),
+            )),
+            list_shared_collections_use_case: Arc::new(ListSharedCollectionsUseCase::new(
+                collection_repository.clone(),
+            )),
+            get_collection_use_case: Arc::new(GetCollectionUseCase::new(
+                collection_repository.clone(),
+            )),
+            add_collection_recipe_use_case: Arc::new(AddRecipeToCollectionUseCase::new(
+                collection_repository.clone(),
                 recipe_repository,
                 share_repository,
             )),
+            remove_collection_recipe_use_case: Arc::new(RemoveRecipeFromCollectionUseCase::new(
+                collection_repository.clone(),
+            )),
+            list_collection_shares_use_case: Arc::new(ListCollectionSharesUseCase::new(
+                collection_repository.clone(),
+            )),
+            create_collection_share_use_case: Arc::new(CreateCollectionShareUseCase::new(
+                collection_repository.clone(),
+            )),
+            delete_collection_share_use_case: Arc::new(DeleteCollectionShareUseCase::new(
+                collection_repository,
+            )),
+            cursor_codec: Arc::new(cursor_codec),
         }
     }
 }
@@ -1,7 +1,50 @@
+mod constraints;
+mod dietary;
+mod diff;
 mod entities;
+mod generation_cache;
+mod ingredients;
+mod nutrition;
+mod pagination;
+mod prompt_input;
+mod recipe_stream;
 mod repositories;
+mod scaling;
+mod search;
 mod services;
+mod similarity;
+mod substitutions;
+mod units;
+mod usage;
+mod validation;
 
-pub use entities::{GeneratedRecipe, Recipe, RecipeShare};
-pub use repositories::{RecipeRepository, RecipeShareRepository, RepositoryError};
-pub use services::{LlmError, LlmService};
+pub use constraints::{Difficulty, Equipment, RecipeConstraints};
+pub use dietary::{Allergen, DietaryRestriction};
+pub use diff::{FieldChange, LineChange, LineChangeKind, RecipeDiff};
+pub use entities::{
+    Collection, CollectionShare, GeneratedRecipe, Generation, Recipe, RecipeRevision, RecipeShare,
+};
+pub use generation_cache::{GenerationCache, GenerationKey};
+pub use ingredients::ParsedIngredient;
+pub use nutrition::{NutritionFacts, NutritionReport};
+pub use pagination::{Page, PageRequest, RecipeCursor, RecipeSort, SortKey};
+pub use prompt_input::{
+    MAX_PROMPT_INGREDIENTS, MAX_PROMPT_RESTRICTIONS, check_prompt_text, clean_prompt_text,
+};
+pub use recipe_stream::{RecipeStreamEvent, RecipeStreamParser};
+pub use repositories::{
+    CollectionRepository, GenerationRepository, RecipeRepository, RecipeShareRepository,
+    RepositoryError, UsageRepository,
+};
+pub use scaling::{Scaling, ScalingError};
+pub use search::{RecipeSearch, SearchScope};
+pub use services::{LlmError, LlmService, PromptChoice};
+pub use substitutions::{
+    Substitute, SubstituteSource, Substitutions, SuggestedSubstitute, curated_substitutes,
+};
+pub use units::UnitSystem;
+pub use usage::{
+    ModelOutput, QuotaLimits, QuotaPolicy, Requester, TokenPrices, TokenUsage, UsageMeter,
+    UsageSummary, UsageTotals, day_start, month_start,
+};
+pub use validation::RecipeViolation;
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+use super::entities::GeneratedRecipe;
+use super::ingredients::{ParsedIngredient, mentions, words};
+use super::validation::RecipeViolation;
+
+/// A limit on the kitchen equipment a recipe may call for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Equipment {
+    NoOven,
+    AirFryerOnly,
+    OnePot,
+}
+
+impl Equipment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Equipment::NoOven => "no_oven",
+            Equipment::AirFryerOnly => "air_fryer_only",
+            Equipment::OnePot => "one_pot",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+/// What the user asked for beyond ingredients and dietary restrictions.
+/// Everything is optional; the default asks for nothing in particular.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecipeConstraints {
+    pub cuisine: Option<String>,
+    /// Longest the recipe may take, prep and cooking together.
+    pub max_total_minutes: Option<i32>,
+    pub equipment: Vec<Equipment>,
+    pub difficulty: Option<Difficulty>,
+    /// Number of servings the recipe should be written for.
+    pub servings: Option<i32>,
+    /// Ingredients the recipe must not use, staples included.
+    pub excluded_ingredients: Vec<String>,
+}
+
+impl GeneratedRecipe {
+    /// The ways the recipe misses `constraints` that can be checked: its
+    /// total time, its serving count and excluded ingredients. Unknown times
+    /// and servings are not held against it, as in [`GeneratedRecipe::validate`].
+    pub fn constraint_violations(&self, constraints: &RecipeConstraints) -> Vec<RecipeViolation> {
+        let mut violations = Vec::new();
+
+        if let Some(max) = constraints.max_total_minutes {
+            let minutes = self.prep_time_minutes.unwrap_or(0) + self.cook_time_minutes.unwrap_or(0);
+            if minutes > max {
+                violations.push(RecipeViolation::TooLong { minutes, max });
+            }
+        }
+        if let (Some(expected), Some(servings)) = (constraints.servings, self.servings)
+            && servings != expected
+        {
+            violations.push(RecipeViolation::WrongServings { servings, expected });
+        }
+
+        let excluded: Vec<Vec<String>> = constraints
+            .excluded_ingredients
+            .iter()
+            .map(|name| words(name))
+            .filter(|name| !name.is_empty())
+            .collect();
+        violations.extend(
+            self.ingredients
+                .iter()
+                .filter(|line| {
+                    let item = words(&ParsedIngredient::parse(line).item);
+                    excluded.iter().any(|name| mentions(&item, name))
+                })
+                .map(|line| RecipeViolation::ExcludedIngredient(line.clone())),
+        );
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe() -> GeneratedRecipe {
+        GeneratedRecipe {
+            title: "Chicken and Rice".to_string(),
+            ingredients: vec![
+                "2 chicken thighs".to_string(),
+                "1 cup rice".to_string(),
+                "2 tbsp butter".to_string(),
+            ],
+            instructions: vec!["Cook".to_string()],
+            prep_time_minutes: Some(10),
+            cook_time_minutes: Some(25),
+            servings: Some(4),
+        }
+    }
+
+    #[test]
+    fn recipes_within_the_constraints_pass() {
+        let constraints = RecipeConstraints {
+            cuisine: Some("Thai".to_string()),
+            max_total_minutes: Some(35),
+            equipment: vec![Equipment::OnePot],
+            difficulty: Some(Difficulty::Easy),
+            servings: Some(4),
+            excluded_ingredients: vec!["cilantro".to_string()],
+        };
+
+        assert_eq!(recipe().constraint_violations(&constraints), vec![]);
+    }
+
+    #[test]
+    fn flags_time_servings_and_excluded_ingredients() {
+        let constraints = RecipeConstraints {
+            max_total_minutes: Some(30),
+            servings: Some(2),
+            excluded_ingredients: vec!["Butter".to_string()],
+            ..RecipeConstraints::default()
+        };
+
+        let violations = recipe().constraint_violations(&constraints);
+
+        assert_eq!(
+            violations,
+            vec![
+                RecipeViolation::TooLong {
+                    minutes: 35,
+                    max: 30
+                },
+                RecipeViolation::WrongServings {
+                    servings: 4,
+                    expected: 2
+                },
+                RecipeViolation::ExcludedIngredient("2 tbsp butter".to_string()),
+            ]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "the recipe takes 35 minutes, more than the 30 allowed"
+        );
+    }
+
+    #[test]
+    fn unknown_times_and_servings_are_not_flagged() {
+        let mut recipe = recipe();
+        recipe.prep_time_minutes = None;
+        recipe.cook_time_minutes = None;
+        recipe.servings = None;
+        let constraints = RecipeConstraints {
+            max_total_minutes: Some(5),
+            servings: Some(2),
+            ..RecipeConstraints::default()
+        };
+
+        assert_eq!(recipe.constraint_violations(&constraints), vec![]);
+    }
+}
@@ -0,0 +1,563 @@
+use std::fmt;
+
+use super::entities::{GeneratedRecipe, Recipe};
+use super::ingredients::{mentions, words};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Allergen {
+    Gluten,
+    Dairy,
+    Egg,
+    Peanut,
+    TreeNut,
+    Soy,
+    Fish,
+    Shellfish,
+    Sesame,
+}
+
+impl Allergen {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Allergen::Gluten => "gluten",
+            Allergen::Dairy => "dairy",
+            Allergen::Egg => "egg",
+            Allergen::Peanut => "peanut",
+            Allergen::TreeNut => "tree-nut",
+            Allergen::Soy => "soy",
+            Allergen::Fish => "fish",
+            Allergen::Shellfish => "shellfish",
+            Allergen::Sesame => "sesame",
+        }
+    }
+}
+
+/// A restriction we know how to verify. Free-text restrictions that do not
+/// map to one of these (e.g. "keto", "low sodium") are passed to the model
+/// but not checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DietaryRestriction {
+    Avoid(Allergen),
+    Vegetarian,
+    Vegan,
+    Pescatarian,
+}
+
+impl DietaryRestriction {
+    /// Recognises the common ways of writing a restriction: "gluten-free",
+    /// "no dairy", "nut allergy", "Vegan". A single phrase can yield several
+    /// restrictions ("nut-free" covers peanuts and tree nuts).
+    pub fn parse(text: &str) -> Vec<Self> {
+        let text = words(text);
+        let has = |phrase: &[&str]| mentions(&text, phrase);
+
+        let mut found = Vec::new();
+        if has(&["vegan"]) {
+            found.push(DietaryRestriction::Vegan);
+        }
+        if has(&["vegetarian"]) {
+            found.push(DietaryRestriction::Vegetarian);
+        }
+        if has(&["pescatarian"]) || has(&["pescetarian"]) {
+            found.push(DietaryRestriction::Pescatarian);
+        }
+        if has(&["gluten"]) || has(&["wheat"]) || has(&["celiac"]) || has(&["coeliac"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::Gluten));
+        }
+        if has(&["dairy"]) || has(&["lactose"]) || has(&["milk"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::Dairy));
+        }
+        if has(&["egg"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::Egg));
+        }
+        if has(&["peanut"]) || (has(&["nut"]) && !has(&["tree", "nut"])) {
+            found.push(DietaryRestriction::Avoid(Allergen::Peanut));
+        }
+        if has(&["tree", "nut"]) || has(&["nut"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::TreeNut));
+        }
+        if has(&["soy"]) || has(&["soya"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::Soy));
+        }
+        if has(&["shellfish"]) || has(&["crustacean"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::Shellfish));
+        }
+        if has(&["fish"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::Fish));
+        }
+        if has(&["sesame"]) {
+            found.push(DietaryRestriction::Avoid(Allergen::Sesame));
+        }
+        found
+    }
+
+    pub fn parse_all(restrictions: &[String]) -> Vec<Self> {
+        let mut all: Vec<Self> = Vec::new();
+        for restriction in restrictions.iter().flat_map(|r| Self::parse(r)) {
+            if !all.contains(&restriction) {
+                all.push(restriction);
+            }
+        }
+        all
+    }
+
+    fn forbids(&self, group: FoodGroup) -> bool {
+        match self {
+            DietaryRestriction::Avoid(allergen) => group == FoodGroup::Allergen(*allergen),
+            DietaryRestriction::Pescatarian => group == FoodGroup::Meat,
+            DietaryRestriction::Vegetarian => matches!(
+                group,
+                FoodGroup::Meat
+                    | FoodGroup::Allergen(Allergen::Fish)
+                    | FoodGroup::Allergen(Allergen::Shellfish)
+            ),
+            DietaryRestriction::Vegan => matches!(
+                group,
+                FoodGroup::Meat
+                    | FoodGroup::OtherAnimalProduct
+                    | FoodGroup::Allergen(
+                        Allergen::Fish | Allergen::Shellfish | Allergen::Dairy | Allergen::Egg
+                    )
+            ),
+        }
+    }
+}
+
+impl fmt::Display for DietaryRestriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DietaryRestriction::Avoid(allergen) => write!(f, "{}-free", allergen.as_str()),
+            DietaryRestriction::Vegetarian => f.write_str("vegetarian"),
+            DietaryRestriction::Vegan => f.write_str("vegan"),
+            DietaryRestriction::Pescatarian => f.write_str("pescatarian"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DietaryViolation {
+    pub restriction: DietaryRestriction,
+    pub ingredient: String,
+}
+
+impl fmt::Display for DietaryViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\" is not {}", self.ingredient, self.restriction)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FoodGroup {
+    Allergen(Allergen),
+    Meat,
+    OtherAnimalProduct,
+}
+
+struct Rule {
+    group: FoodGroup,
+    keywords: &'static [&'static str],
+    /// Phrases that make a keyword match harmless, e.g. "almond milk" for dairy.
+    exceptions: &'static [&'static str],
+}
+
+/// Phrases that make a whole line free of animal products. Narrower ones,
+/// such as "dairy free", are exceptions of the rule they clear.
+const PLANT_BASED: [&str; 2] = ["vegan", "plant based"];
+
+const RULES: &[Rule] = &[
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Gluten),
+        keywords: &[
+            "wheat",
+            "flour",
+            "bread",
+            "breadcrumb",
+            "panko",
+            "pasta",
+            "spaghetti",
+            "penne",
+            "macaroni",
+            "lasagna",
+            "noodle",
+            "couscous",
+            "barley",
+            "rye",
+            "bulgur",
+            "semolina",
+            "farro",
+            "seitan",
+            "tortilla",
+            "pita",
+            "cracker",
+            "crouton",
+            "orzo",
+            "beer",
+            "soy sauce",
+            "bun",
+            "baguette",
+            "brioche",
+        ],
+        exceptions: &[
+            "gluten free",
+            "rice flour",
+            "almond flour",
+            "coconut flour",
+            "corn flour",
+            "chickpea flour",
+            "corn tortilla",
+            "rice noodle",
+            "rice pasta",
+            "tamari",
+        ],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Dairy),
+        keywords: &[
+            "milk",
+            "butter",
+            "cheese",
+            "cream",
+            "yogurt",
+            "yoghurt",
+            "ghee",
+            "parmesan",
+            "mozzarella",
+            "cheddar",
+            "feta",
+            "ricotta",
+            "mascarpone",
+            "buttermilk",
+            "whey",
+            "custard",
+            "creme fraiche",
+            "paneer",
+            "brie",
+            "gouda",
+            "half and half",
+        ],
+        exceptions: &[
+            "almond milk",
+            "oat milk",
+            "soy milk",
+            "coconut milk",
+            "coconut cream",
+            "rice milk",
+            "cashew milk",
+            "peanut butter",
+            "almond butter",
+            "cashew butter",
+            "cocoa butter",
+            "nut butter",
+            "cream of tartar",
+            "dairy free",
+        ],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Egg),
+        keywords: &["egg", "mayonnaise", "mayo", "meringue", "aioli"],
+        exceptions: &["egg free", "flax egg", "egg replacer"],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Peanut),
+        keywords: &["peanut"],
+        exceptions: &[],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::TreeNut),
+        keywords: &[
+            "nut",
+            "almond",
+            "walnut",
+            "pecan",
+            "cashew",
+            "pistachio",
+            "hazelnut",
+            "macadamia",
+            "chestnut",
+            "praline",
+            "marzipan",
+            "nutella",
+        ],
+        exceptions: &["nut free"],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Soy),
+        keywords: &["soy", "soya", "tofu", "edamame", "tempeh", "miso", "tamari"],
+        exceptions: &["soy free"],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Fish),
+        keywords: &[
+            "fish",
+            "salmon",
+            "tuna",
+            "cod",
+            "anchovy",
+            "sardine",
+            "trout",
+            "tilapia",
+            "halibut",
+            "mackerel",
+            "haddock",
+            "worcestershire",
+        ],
+        exceptions: &[],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Shellfish),
+        keywords: &[
+            "shellfish",
+            "shrimp",
+            "prawn",
+            "crab",
+            "lobster",
+            "scallop",
+            "clam",
+            "mussel",
+            "oyster",
+            "crawfish",
+            "crayfish",
+            "squid",
+            "calamari",
+            "octopus",
+        ],
+        exceptions: &["oyster mushroom", "oyster sauce"],
+    },
+    Rule {
+        group: FoodGroup::Allergen(Allergen::Sesame),
+        keywords: &["sesame", "tahini"],
+        exceptions: &[],
+    },
+    Rule {
+        group: FoodGroup::Meat,
+        keywords: &[
+            "chicken",
+            "beef",
+            "pork",
+            "bacon",
+            "ham",
+            "sausage",
+            "lamb",
+            "turkey",
+            "duck",
+            "veal",
+            "venison",
+            "prosciutto",
+            "pancetta",
+            "chorizo",
+            "salami",
+            "pepperoni",
+            "steak",
+            "mince",
+            "gelatin",
+            "gelatine",
+            "lard",
+            "meat",
+        ],
+        exceptions: &["meatless"],
+    },
+    Rule {
+        group: FoodGroup::OtherAnimalProduct,
+        keywords: &["honey"],
+        exceptions: &[],
+    },
+];
+
+fn food_groups(line: &str) -> Vec<FoodGroup> {
+    let line_words = words(line);
+    let says = |phrase: &str| mentions(&line_words, &words(phrase));
+    let plant_based = PLANT_BASED.iter().any(|p| says(p));
+
+    RULES
+        .iter()
+        .filter(|rule| {
+            let animal = !matches!(
+                rule.group,
+                FoodGroup::Allergen(
+                    Allergen::Gluten
+                        | Allergen::Peanut
+                        | Allergen::TreeNut
+                        | Allergen::Soy
+                        | Allergen::Sesame
+                )
+            );
+            rule.keywords.iter().any(|k| says(k))
+                && !rule.exceptions.iter().any(|e| says(e))
+                && !(animal && plant_based)
+        })
+        .map(|rule| rule.group)
+        .collect()
+}
+
+/// Major allergens found in a list of ingredient lines, in a stable order.
+pub fn detect_allergens(ingredients: &[String]) -> Vec<Allergen> {
+    let mut allergens: Vec<Allergen> = ingredients
+        .iter()
+        .flat_map(|line| food_groups(line))
+        .filter_map(|group| match group {
+            FoodGroup::Allergen(allergen) => Some(allergen),
+            _ => None,
+        })
+        .collect();
+    allergens.sort();
+    allergens.dedup();
+    allergens
+}
+
+/// Every ingredient line that breaks one of the restrictions.
+pub fn find_violations(
+    ingredients: &[String],
+    restrictions: &[DietaryRestriction],
+) -> Vec<DietaryViolation> {
+    let mut violations = Vec::new();
+    for line in ingredients {
+        let groups = food_groups(line);
+        for restriction in restrictions {
+            if groups.iter().any(|group| restriction.forbids(*group)) {
+                violations.push(DietaryViolation {
+                    restriction: *restriction,
+                    ingredient: line.clone(),
+                });
+            }
+        }
+    }
+    violations
+}
+
+impl Recipe {
+    pub fn allergens(&self) -> Vec<Allergen> {
+        detect_allergens(&self.ingredients)
+    }
+}
+
+impl GeneratedRecipe {
+    pub fn allergens(&self) -> Vec<Allergen> {
+        detect_allergens(&self.ingredients)
+    }
+
+    pub fn dietary_violations(&self, restrictions: &[DietaryRestriction]) -> Vec<DietaryViolation> {
+        find_violations(&self.ingredients, restrictions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_major_allergens() {
+        let ingredients = lines(&[
+            "2 cups all-purpose flour",
+            "1/2 cup butter, melted",
+            "2 eggs",
+            "200 g shrimp, peeled",
+            "1 tbsp soy sauce",
+            "1 tsp sesame oil",
+        ]);
+
+        assert_eq!(
+            detect_allergens(&ingredients),
+            vec![
+                Allergen::Gluten,
+                Allergen::Dairy,
+                Allergen::Egg,
+                Allergen::Soy,
+                Allergen::Shellfish,
+                Allergen::Sesame,
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_look_alikes_and_plant_based_swaps() {
+        let ingredients = lines(&[
+            "1 eggplant, diced",
+            "1 can coconut milk",
+            "1 tsp nutmeg",
+            "2 cups butternut squash",
+            "1 cup almond flour",
+            "1 tbsp vegan butter",
+        ]);
+
+        assert_eq!(detect_allergens(&ingredients), vec![Allergen::TreeNut]);
+    }
+
+    #[test]
+    fn dairy_free_only_clears_dairy() {
+        let ingredients = lines(&["2 tbsp dairy-free mayo", "1 lb dairy-free chicken sausage"]);
+
+        assert_eq!(detect_allergens(&ingredients), vec![Allergen::Egg]);
+
+        let restrictions = DietaryRestriction::parse_all(&lines(&["vegetarian", "vegan"]));
+        let described: Vec<String> = find_violations(&ingredients, &restrictions)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                "\"2 tbsp dairy-free mayo\" is not vegan",
+                "\"1 lb dairy-free chicken sausage\" is not vegetarian",
+                "\"1 lb dairy-free chicken sausage\" is not vegan",
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_common_restriction_phrasings() {
+        assert_eq!(
+            DietaryRestriction::parse("Gluten-Free"),
+            vec![DietaryRestriction::Avoid(Allergen::Gluten)]
+        );
+        assert_eq!(
+            DietaryRestriction::parse("nut allergy"),
+            vec![
+                DietaryRestriction::Avoid(Allergen::Peanut),
+                DietaryRestriction::Avoid(Allergen::TreeNut),
+            ]
+        );
+        assert_eq!(
+            DietaryRestriction::parse("tree nut allergy"),
+            vec![DietaryRestriction::Avoid(Allergen::TreeNut)]
+        );
+        assert_eq!(
+            DietaryRestriction::parse("no shellfish"),
+            vec![DietaryRestriction::Avoid(Allergen::Shellfish)]
+        );
+        assert!(DietaryRestriction::parse("keto").is_empty());
+    }
+
+    #[test]
+    fn reports_violations_per_restriction() {
+        let ingredients = lines(&["2 chicken breasts", "1 cup milk", "1 tbsp honey", "1 onion"]);
+        let restrictions = DietaryRestriction::parse_all(&lines(&["vegetarian", "vegan"]));
+
+        let violations = find_violations(&ingredients, &restrictions);
+        let described: Vec<String> = violations.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            described,
+            vec![
+                "\"2 chicken breasts\" is not vegetarian",
+                "\"2 chicken breasts\" is not vegan",
+                "\"1 cup milk\" is not vegan",
+                "\"1 tbsp honey\" is not vegan",
+            ]
+        );
+    }
+
+    #[test]
+    fn pescatarian_allows_fish() {
+        let restrictions = DietaryRestriction::parse("pescatarian");
+        let ingredients = lines(&["1 salmon fillet", "2 slices bacon"]);
+
+        let violations = find_violations(&ingredients, &restrictions);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].ingredient, "2 slices bacon");
+    }
+}
@@ -0,0 +1,198 @@
+use super::entities::RecipeRevision;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange<T> {
+    pub from: T,
+    pub to: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChangeKind {
+    Added,
+    Removed,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineChange {
+    pub kind: LineChangeKind,
+    pub line: String,
+}
+
+/// Field-level difference between two revisions of the same recipe.
+/// Scalar fields are `None` when unchanged; list fields always contain the
+/// full line-by-line comparison so clients can render context around edits.
+#[derive(Debug, Clone)]
+pub struct RecipeDiff {
+    pub from_revision: i32,
+    pub to_revision: i32,
+    pub title: Option<FieldChange<String>>,
+    pub ingredients: Vec<LineChange>,
+    pub instructions: Vec<LineChange>,
+    pub prep_time_minutes: Option<FieldChange<Option<i32>>>,
+    pub cook_time_minutes: Option<FieldChange<Option<i32>>>,
+    pub servings: Option<FieldChange<Option<i32>>>,
+}
+
+impl RecipeDiff {
+    pub fn between(from: &RecipeRevision, to: &RecipeRevision) -> Self {
+        Self {
+            from_revision: from.revision,
+            to_revision: to.revision,
+            title: field_change(&from.title, &to.title),
+            ingredients: diff_lines(&from.ingredients, &to.ingredients),
+            instructions: diff_lines(&from.instructions, &to.instructions),
+            prep_time_minutes: field_change(&from.prep_time_minutes, &to.prep_time_minutes),
+            cook_time_minutes: field_change(&from.cook_time_minutes, &to.cook_time_minutes),
+            servings: field_change(&from.servings, &to.servings),
+        }
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.title.is_some()
+            || self.prep_time_minutes.is_some()
+            || self.cook_time_minutes.is_some()
+            || self.servings.is_some()
+            || self
+                .ingredients
+                .iter()
+                .chain(self.instructions.iter())
+                .any(|c| c.kind != LineChangeKind::Unchanged)
+    }
+}
+
+fn field_change<T: Clone + PartialEq>(from: &T, to: &T) -> Option<FieldChange<T>> {
+    if from == to {
+        None
+    } else {
+        Some(FieldChange {
+            from: from.clone(),
+            to: to.clone(),
+        })
+    }
+}
+
+/// Line diff based on the longest common subsequence. Recipe lists are short
+/// (tens of lines), so the quadratic table is not a concern.
+fn diff_lines(from: &[String], to: &[String]) -> Vec<LineChange> {
+    let (n, m) = (from.len(), to.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if from[i] == to[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if from[i] == to[j] {
+            changes.push(line(LineChangeKind::Unchanged, &from[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            changes.push(line(LineChangeKind::Removed, &from[i]));
+            i += 1;
+        } else {
+            changes.push(line(LineChangeKind::Added, &to[j]));
+            j += 1;
+        }
+    }
+    changes.extend(from[i..].iter().map(|l| line(LineChangeKind::Removed, l)));
+    changes.extend(to[j..].iter().map(|l| line(LineChangeKind::Added, l)));
+
+    changes
+}
+
+fn line(kind: LineChangeKind, text: &str) -> LineChange {
+    LineChange {
+        kind,
+        line: text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn revision(revision: i32, title: &str, ingredients: &[&str]) -> RecipeRevision {
+        RecipeRevision {
+            recipe_id: Uuid::nil(),
+            revision,
+            editor_id: "user_123".to_string(),
+            title: title.to_string(),
+            ingredients: ingredients.iter().map(|s| s.to_string()).collect(),
+            instructions: vec!["Cook".to_string()],
+            prep_time_minutes: Some(10),
+            cook_time_minutes: Some(20),
+            servings: Some(4),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn identical_revisions_have_no_changes() {
+        let a = revision(1, "Soup", &["water", "salt"]);
+        let b = revision(2, "Soup", &["water", "salt"]);
+
+        let diff = RecipeDiff::between(&a, &b);
+
+        assert!(!diff.has_changes());
+        assert_eq!(diff.ingredients.len(), 2);
+    }
+
+    #[test]
+    fn reports_title_and_scalar_changes() {
+        let a = revision(1, "Sop", &["water"]);
+        let mut b = revision(2, "Soup", &["water"]);
+        b.servings = Some(6);
+
+        let diff = RecipeDiff::between(&a, &b);
+
+        assert_eq!(
+            diff.title,
+            Some(FieldChange {
+                from: "Sop".to_string(),
+                to: "Soup".to_string()
+            })
+        );
+        assert_eq!(
+            diff.servings,
+            Some(FieldChange {
+                from: Some(4),
+                to: Some(6)
+            })
+        );
+        assert!(diff.prep_time_minutes.is_none());
+    }
+
+    #[test]
+    fn reports_added_removed_and_unchanged_lines_in_order() {
+        let a = revision(1, "Soup", &["water", "salt", "leek"]);
+        let b = revision(2, "Soup", &["water", "pepper", "leek", "cream"]);
+
+        let diff = RecipeDiff::between(&a, &b);
+        let kinds: Vec<_> = diff
+            .ingredients
+            .iter()
+            .map(|c| (c.kind, c.line.as_str()))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (LineChangeKind::Unchanged, "water"),
+                (LineChangeKind::Removed, "salt"),
+                (LineChangeKind::Added, "pepper"),
+                (LineChangeKind::Unchanged, "leek"),
+                (LineChangeKind::Added, "cream"),
+            ]
+        );
+    }
+}
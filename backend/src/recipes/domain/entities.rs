@@ -2,7 +2,13 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Deserialize)]
+use std::time::Duration;
+
+use super::constraints::RecipeConstraints;
+use super::ingredients::ParsedIngredient;
+use super::usage::ModelOutput;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneratedRecipe {
     pub title: String,
     pub ingredients: Vec<String>,
@@ -18,10 +24,14 @@ pub struct Recipe {
     pub owner_id: String,
     pub title: String,
     pub ingredients: Vec<String>,
+    #[sqlx(json)]
+    pub parsed_ingredients: Vec<ParsedIngredient>,
     pub instructions: Vec<String>,
     pub prep_time_minutes: Option<i32>,
     pub cook_time_minutes: Option<i32>,
     pub servings: Option<i32>,
+    /// Owner-assigned labels such as "weeknight", normalised by `set_tags`.
+    pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -31,14 +41,94 @@ impl Recipe {
             id: Uuid::new_v4(),
             owner_id,
             title: generated.title,
+            parsed_ingredients: parse_ingredients(&generated.ingredients),
             ingredients: generated.ingredients,
             instructions: generated.instructions,
             prep_time_minutes: generated.prep_time_minutes,
             cook_time_minutes: generated.cook_time_minutes,
             servings: generated.servings,
+            tags: Vec::new(),
             created_at: Utc::now(),
         }
     }
+
+    /// Replaces the tags with `tags` trimmed, lowercased and deduplicated,
+    /// keeping their order. Blank tags are dropped.
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags.clear();
+        for tag in tags {
+            let tag = tag
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase();
+            if !tag.is_empty() && !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+
+    pub fn apply_changes(&mut self, changes: GeneratedRecipe) {
+        self.title = changes.title;
+        self.parsed_ingredients = parse_ingredients(&changes.ingredients);
+        self.ingredients = changes.ingredients;
+        self.instructions = changes.instructions;
+        self.prep_time_minutes = changes.prep_time_minutes;
+        self.cook_time_minutes = changes.cook_time_minutes;
+        self.servings = changes.servings;
+    }
+
+    pub fn content(&self) -> GeneratedRecipe {
+        GeneratedRecipe {
+            title: self.title.clone(),
+            ingredients: self.ingredients.clone(),
+            instructions: self.instructions.clone(),
+            prep_time_minutes: self.prep_time_minutes,
+            cook_time_minutes: self.cook_time_minutes,
+            servings: self.servings,
+        }
+    }
+
+    /// Structured form of each ingredient line, index-aligned with `ingredients`.
+    /// Recipes stored before ingredients were parsed are parsed on the fly.
+    pub fn structured_ingredients(&self) -> Vec<ParsedIngredient> {
+        if self.parsed_ingredients.len() == self.ingredients.len() {
+            self.parsed_ingredients.clone()
+        } else {
+            parse_ingredients(&self.ingredients)
+        }
+    }
+}
+
+fn parse_ingredients(lines: &[String]) -> Vec<ParsedIngredient> {
+    lines.iter().map(|l| ParsedIngredient::parse(l)).collect()
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RecipeRevision {
+    pub recipe_id: Uuid,
+    pub revision: i32,
+    pub editor_id: String,
+    pub title: String,
+    pub ingredients: Vec<String>,
+    pub instructions: Vec<String>,
+    pub prep_time_minutes: Option<i32>,
+    pub cook_time_minutes: Option<i32>,
+    pub servings: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RecipeRevision {
+    pub fn content(&self) -> GeneratedRecipe {
+        GeneratedRecipe {
+            title: self.title.clone(),
+            ingredients: self.ingredients.clone(),
+            instructions: self.instructions.clone(),
+            prep_time_minutes: self.prep_time_minutes,
+            cook_time_minutes: self.cook_time_minutes,
+            servings: self.servings,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
@@ -58,6 +148,46 @@ impl RecipeShare {
     }
 }
 
+/// A named group of recipes, such as a cookbook. It can hold the owner's
+/// recipes and recipes shared with them.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Collection {
+    pub id: Uuid,
+    pub owner_id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Collection {
+    pub fn new(owner_id: String, name: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            owner_id,
+            name: name.trim().to_string(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Sharing a collection lets the user see it and the owner's recipes in it,
+/// as if each of those recipes had been shared with them.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CollectionShare {
+    pub collection_id: Uuid,
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CollectionShare {
+    pub fn new(collection_id: Uuid, user_id: String) -> Self {
+        Self {
+            collection_id,
+            user_id,
+            created_at: Utc::now(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,5 +254,117 @@ mod tests {
         assert_eq!(recipe.title, "Test");
         assert_eq!(recipe.owner_id, "user_123");
         assert!(!recipe.id.is_nil());
+        assert_eq!(recipe.parsed_ingredients.len(), 1);
+    }
+
+    #[test]
+    fn apply_changes_keeps_identity_and_ownership() {
+        let original = GeneratedRecipe {
+            title: "Tset".to_string(),
+            ingredients: vec!["a".to_string()],
+            instructions: vec!["b".to_string()],
+            prep_time_minutes: Some(5),
+            cook_time_minutes: Some(10),
+            servings: Some(2),
+        };
+        let mut recipe = Recipe::from_generated(original, "user_123".to_string());
+        let id = recipe.id;
+        let created_at = recipe.created_at;
+
+        recipe.apply_changes(GeneratedRecipe {
+            title: "Test".to_string(),
+            ingredients: vec!["a".to_string(), "c".to_string()],
+            instructions: vec!["b".to_string()],
+            prep_time_minutes: None,
+            cook_time_minutes: Some(15),
+            servings: Some(4),
+        });
+
+        assert_eq!(recipe.id, id);
+        assert_eq!(recipe.owner_id, "user_123");
+        assert_eq!(recipe.created_at, created_at);
+        assert_eq!(recipe.title, "Test");
+        assert_eq!(recipe.ingredients.len(), 2);
+        assert_eq!(recipe.parsed_ingredients[1].item, "c");
+        assert!(recipe.prep_time_minutes.is_none());
+        assert_eq!(recipe.servings, Some(4));
+    }
+
+    #[test]
+    fn set_tags_normalises_and_deduplicates() {
+        let generated = GeneratedRecipe {
+            title: "Test".to_string(),
+            ingredients: vec!["a".to_string()],
+            instructions: vec!["b".to_string()],
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            servings: None,
+        };
+        let mut recipe = Recipe::from_generated(generated, "user_123".to_string());
+
+        recipe.set_tags(vec![
+            " Weeknight ".to_string(),
+            "Holiday   2026".to_string(),
+            "weeknight".to_string(),
+            "  ".to_string(),
+        ]);
+
+        assert_eq!(recipe.tags, vec!["weeknight", "holiday 2026"]);
+    }
+}
+
+/// A signed-in user's generation request and what it produced, kept so an
+/// unsaved result can be found and saved later.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Generation {
+    pub id: Uuid,
+    pub user_id: String,
+    pub ingredients: Vec<String>,
+    pub dietary_restrictions: Option<Vec<String>>,
+    #[sqlx(json)]
+    pub constraints: RecipeConstraints,
+    /// Provider and model that wrote the recipes; `None` when they came from
+    /// the generation cache.
+    pub model: Option<String>,
+    pub prompt_version: Option<String>,
+    /// The model's reply before it was parsed.
+    pub raw_output: Option<String>,
+    #[sqlx(json)]
+    pub recipes: Vec<GeneratedRecipe>,
+    pub latency_ms: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Generation {
+    pub fn new(
+        user_id: String,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        recipes: Vec<GeneratedRecipe>,
+        output: Option<ModelOutput>,
+        latency: Duration,
+    ) -> Self {
+        let (model, prompt_version, raw_output) = match output {
+            Some(output) => (
+                Some(output.model),
+                Some(output.prompt_version),
+                Some(output.raw),
+            ),
+            None => (None, None, None),
+        };
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            ingredients,
+            dietary_restrictions,
+            constraints,
+            model,
+            prompt_version,
+            raw_output,
+            recipes,
+            latency_ms: i64::try_from(latency.as_millis()).unwrap_or(i64::MAX),
+            created_at: Utc::now(),
+        }
     }
 }
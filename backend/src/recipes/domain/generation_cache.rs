@@ -0,0 +1,149 @@
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::future::Future;
+
+use super::constraints::RecipeConstraints;
+use super::entities::GeneratedRecipe;
+
+/// Identifies a generation request by what it asks for, so requests that
+/// differ only in letter case, spacing or the order of their lists share
+/// one cache entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenerationKey(String);
+
+/// Lowercased, whitespace-collapsed, sorted and deduplicated, without blanks.
+fn normalized(items: &[String]) -> Vec<String> {
+    let mut items: Vec<String> = items
+        .iter()
+        .filter_map(|item| normalized_text(item))
+        .collect();
+    items.sort();
+    items.dedup();
+    items
+}
+
+fn normalized_text(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then(|| text.to_lowercase())
+}
+
+impl GenerationKey {
+    /// `version` names the model and prompt the recipes come from; entries
+    /// made under another version are never reused.
+    pub fn new(
+        version: &str,
+        ingredients: &[String],
+        dietary_restrictions: Option<&[String]>,
+        constraints: &RecipeConstraints,
+        count: usize,
+    ) -> Self {
+        let mut equipment: Vec<&str> = constraints.equipment.iter().map(|e| e.as_str()).collect();
+        equipment.sort_unstable();
+        equipment.dedup();
+
+        let request = json!({
+            "version": version,
+            "ingredients": normalized(ingredients),
+            "dietary_restrictions": normalized(dietary_restrictions.unwrap_or_default()),
+            "cuisine": constraints.cuisine.as_deref().and_then(normalized_text),
+            "max_total_minutes": constraints.max_total_minutes,
+            "equipment": equipment,
+            "difficulty": constraints.difficulty.map(|d| d.as_str()),
+            "servings": constraints.servings,
+            "excluded_ingredients": normalized(&constraints.excluded_ingredients),
+            "count": count,
+        });
+
+        let digest = Sha256::digest(request.to_string().as_bytes());
+        Self(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    /// Hex SHA-256 of the normalized request.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Recipes already generated for a request, kept for a while so repeating
+/// the request does not pay for another generation. Failures are logged by
+/// the cache and treated as misses; they never fail a generation.
+pub trait GenerationCache: Send + Sync {
+    fn get(&self, key: &GenerationKey)
+    -> impl Future<Output = Option<Vec<GeneratedRecipe>>> + Send;
+
+    fn put(
+        &self,
+        key: GenerationKey,
+        recipes: Vec<GeneratedRecipe>,
+    ) -> impl Future<Output = ()> + Send;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipes::domain::Equipment;
+
+    fn lines(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn key(ingredients: &[&str], restrictions: &[&str]) -> GenerationKey {
+        GenerationKey::new(
+            "openai/gpt-4o-mini/prompt-1",
+            &lines(ingredients),
+            Some(&lines(restrictions)),
+            &RecipeConstraints::default(),
+            1,
+        )
+    }
+
+    #[test]
+    fn equivalent_requests_share_a_key() {
+        assert_eq!(
+            key(&["Chicken  Breast", "rice", "rice"], &["Vegan", " "]),
+            key(&["rice", "chicken breast"], &["vegan"])
+        );
+        assert_eq!(key(&["rice"], &[]).as_str().len(), 64);
+    }
+
+    #[test]
+    fn different_requests_get_different_keys() {
+        let base = key(&["rice"], &[]);
+
+        assert_ne!(base, key(&["rice", "egg"], &[]));
+        assert_ne!(base, key(&["rice"], &["vegan"]));
+        assert_ne!(
+            base,
+            GenerationKey::new(
+                "openai/gpt-4o/prompt-1",
+                &lines(&["rice"]),
+                None,
+                &RecipeConstraints::default(),
+                1
+            )
+        );
+        assert_ne!(
+            base,
+            GenerationKey::new(
+                "openai/gpt-4o-mini/prompt-1",
+                &lines(&["rice"]),
+                None,
+                &RecipeConstraints {
+                    equipment: vec![Equipment::OnePot],
+                    ..RecipeConstraints::default()
+                },
+                1
+            )
+        );
+        assert_ne!(
+            base,
+            GenerationKey::new(
+                "openai/gpt-4o-mini/prompt-1",
+                &lines(&["rice"]),
+                None,
+                &RecipeConstraints::default(),
+                3
+            )
+        );
+    }
+}
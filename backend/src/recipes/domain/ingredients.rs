@@ -0,0 +1,565 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Quantity {
+    Exact { value: f64 },
+    Range { min: f64, max: f64 },
+}
+
+impl Quantity {
+    pub fn min(&self) -> f64 {
+        match self {
+            Quantity::Exact { value } => *value,
+            Quantity::Range { min, .. } => *min,
+        }
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        match self {
+            Quantity::Exact { .. } => None,
+            Quantity::Range { max, .. } => Some(*max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Teaspoon,
+    Tablespoon,
+    FluidOunce,
+    Cup,
+    Pint,
+    Quart,
+    Gallon,
+    Milliliter,
+    Liter,
+    Ounce,
+    Pound,
+    Gram,
+    Kilogram,
+    Pinch,
+    Dash,
+    Clove,
+    Can,
+    Slice,
+    Piece,
+    Stick,
+    Bunch,
+    Sprig,
+    Handful,
+    Package,
+}
+
+impl Unit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Unit::Teaspoon => "tsp",
+            Unit::Tablespoon => "tbsp",
+            Unit::FluidOunce => "fl oz",
+            Unit::Cup => "cup",
+            Unit::Pint => "pint",
+            Unit::Quart => "quart",
+            Unit::Gallon => "gallon",
+            Unit::Milliliter => "ml",
+            Unit::Liter => "l",
+            Unit::Ounce => "oz",
+            Unit::Pound => "lb",
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::Pinch => "pinch",
+            Unit::Dash => "dash",
+            Unit::Clove => "clove",
+            Unit::Can => "can",
+            Unit::Slice => "slice",
+            Unit::Piece => "piece",
+            Unit::Stick => "stick",
+            Unit::Bunch => "bunch",
+            Unit::Sprig => "sprig",
+            Unit::Handful => "handful",
+            Unit::Package => "package",
+        }
+    }
+
+    /// Display form of the unit for a given amount, e.g. "cup" vs "cups".
+    /// Abbreviations are the same in singular and plural.
+    pub fn label(&self, plural: bool) -> &'static str {
+        if !plural {
+            return self.as_str();
+        }
+        match self {
+            Unit::Cup => "cups",
+            Unit::Pint => "pints",
+            Unit::Quart => "quarts",
+            Unit::Gallon => "gallons",
+            Unit::Pinch => "pinches",
+            Unit::Dash => "dashes",
+            Unit::Clove => "cloves",
+            Unit::Can => "cans",
+            Unit::Slice => "slices",
+            Unit::Piece => "pieces",
+            Unit::Stick => "sticks",
+            Unit::Bunch => "bunches",
+            Unit::Sprig => "sprigs",
+            Unit::Handful => "handfuls",
+            Unit::Package => "packages",
+            _ => self.as_str(),
+        }
+    }
+
+    /// Matches a single token against the known spellings of a unit.
+    /// Single-letter `T`/`t` follow the common recipe convention of
+    /// tablespoon/teaspoon, so matching is case-sensitive for those.
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "T" | "Tbsp" | "Tbs" => return Some(Unit::Tablespoon),
+            "t" => return Some(Unit::Teaspoon),
+            _ => {}
+        }
+
+        let token = token.trim_end_matches('.').to_lowercase();
+        let unit = match token.as_str() {
+            "tsp" | "tsps" | "teaspoon" | "teaspoons" => Unit::Teaspoon,
+            "tbsp" | "tbsps" | "tbs" | "tablespoon" | "tablespoons" => Unit::Tablespoon,
+            "floz" => Unit::FluidOunce,
+            "c" | "cup" | "cups" => Unit::Cup,
+            "pt" | "pint" | "pints" => Unit::Pint,
+            "qt" | "quart" | "quarts" => Unit::Quart,
+            "gal" | "gallon" | "gallons" => Unit::Gallon,
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Unit::Milliliter,
+            "l" | "liter" | "liters" | "litre" | "litres" => Unit::Liter,
+            "oz" | "ounce" | "ounces" => Unit::Ounce,
+            "lb" | "lbs" | "pound" | "pounds" => Unit::Pound,
+            "g" | "gram" | "grams" | "gr" => Unit::Gram,
+            "kg" | "kilogram" | "kilograms" | "kilo" | "kilos" => Unit::Kilogram,
+            "pinch" | "pinches" => Unit::Pinch,
+            "dash" | "dashes" => Unit::Dash,
+            "clove" | "cloves" => Unit::Clove,
+            "can" | "cans" | "tin" | "tins" => Unit::Can,
+            "slice" | "slices" => Unit::Slice,
+            "piece" | "pieces" | "pc" | "pcs" => Unit::Piece,
+            "stick" | "sticks" => Unit::Stick,
+            "bunch" | "bunches" => Unit::Bunch,
+            "sprig" | "sprigs" => Unit::Sprig,
+            "handful" | "handfuls" => Unit::Handful,
+            "package" | "packages" | "pkg" | "packet" | "packets" => Unit::Package,
+            _ => return None,
+        };
+        Some(unit)
+    }
+}
+
+/// Structured view of a free-text ingredient line such as
+/// "1 1/2 cups rice, rinsed". Parsing never fails: anything that cannot be
+/// recognised as a quantity or unit stays part of `item`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedIngredient {
+    pub quantity: Option<Quantity>,
+    pub unit: Option<Unit>,
+    pub item: String,
+    pub preparation: Option<String>,
+}
+
+impl ParsedIngredient {
+    pub fn parse(line: &str) -> Self {
+        let normalized = normalize(line);
+        let (text, notes) = extract_parentheticals(&normalized);
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        let mut pos = 0;
+
+        let mut quantity = parse_quantity(&tokens, &mut pos);
+        let mut unit = None;
+
+        if let Some(token) = tokens.get(pos) {
+            if let Some((value, glued_unit)) = split_glued_unit(token)
+                && quantity.is_none()
+            {
+                quantity = Some(Quantity::Exact { value });
+                unit = Some(glued_unit);
+                pos += 1;
+            } else if quantity.is_none() && matches!(*token, "a" | "an" | "A" | "An") {
+                if let Some(next) = tokens.get(pos + 1).and_then(|t| Unit::from_token(t)) {
+                    quantity = Some(Quantity::Exact { value: 1.0 });
+                    unit = Some(next);
+                    pos += 2;
+                }
+            } else if let Some((parsed, consumed)) = parse_unit(&tokens[pos..]) {
+                // A bare unit with nothing after it ("2 cups") is more likely the item itself.
+                if pos + consumed < tokens.len() {
+                    unit = Some(parsed);
+                    pos += consumed;
+                }
+            }
+        }
+
+        if unit.is_some()
+            && tokens
+                .get(pos)
+                .is_some_and(|t| t.eq_ignore_ascii_case("of"))
+        {
+            pos += 1;
+        }
+
+        let rest = tokens[pos..].join(" ");
+        let (item, comma_note) = match rest.split_once(',') {
+            Some((item, note)) => (item.trim().to_string(), Some(note.trim().to_string())),
+            None => (rest.trim().to_string(), None),
+        };
+
+        let preparation: Vec<String> = comma_note
+            .into_iter()
+            .chain(notes)
+            .filter(|n| !n.is_empty())
+            .collect();
+
+        let item = if item.is_empty() {
+            line.trim().to_string()
+        } else {
+            item
+        };
+
+        Self {
+            quantity,
+            unit,
+            item,
+            preparation: if preparation.is_empty() {
+                None
+            } else {
+                Some(preparation.join(", "))
+            },
+        }
+    }
+}
+
+/// Lowercase words of a piece of text, for matching ingredient names.
+pub(super) fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether `name` appears in `text` as consecutive whole words, allowing
+/// regular plurals ("tomatoes", "berries"). Both sides come from [`words`].
+pub(super) fn mentions<S: AsRef<str>>(text: &[String], name: &[S]) -> bool {
+    !name.is_empty()
+        && text.windows(name.len()).any(|window| {
+            window
+                .iter()
+                .zip(name)
+                .all(|(word, name)| same_word(word, name.as_ref()))
+        })
+}
+
+fn same_word(word: &str, name: &str) -> bool {
+    word == name
+        || word.strip_suffix('s') == Some(name)
+        || word.strip_suffix("es") == Some(name)
+        || (name.ends_with('y') && word.strip_suffix("ies") == Some(&name[..name.len() - 1]))
+}
+
+const UNICODE_FRACTIONS: [(char, &str); 15] = [
+    ('½', "1/2"),
+    ('⅓', "1/3"),
+    ('⅔', "2/3"),
+    ('¼', "1/4"),
+    ('¾', "3/4"),
+    ('⅕', "1/5"),
+    ('⅖', "2/5"),
+    ('⅗', "3/5"),
+    ('⅘', "4/5"),
+    ('⅙', "1/6"),
+    ('⅚', "5/6"),
+    ('⅛', "1/8"),
+    ('⅜', "3/8"),
+    ('⅝', "5/8"),
+    ('⅞', "7/8"),
+];
+
+fn normalize(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for c in line.trim().chars() {
+        if let Some((_, ascii)) = UNICODE_FRACTIONS.iter().find(|(f, _)| *f == c) {
+            out.push(' ');
+            out.push_str(ascii);
+            out.push(' ');
+        } else if c == '–' || c == '—' {
+            out.push('-');
+        } else if c == '⁄' {
+            out.push('/');
+        } else {
+            out.push(c);
+        }
+    }
+
+    out.trim_start_matches(['-', '*', '•', ' ']).to_string()
+}
+
+fn extract_parentheticals(text: &str) -> (String, Vec<String>) {
+    let mut outside = String::with_capacity(text.len());
+    let mut notes = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+
+    for c in text.chars() {
+        match c {
+            '(' => {
+                if depth > 0 {
+                    current.push(c);
+                }
+                depth += 1;
+            }
+            ')' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    notes.push(current.trim().to_string());
+                    current.clear();
+                    outside.push(' ');
+                } else {
+                    current.push(c);
+                }
+            }
+            _ if depth > 0 => current.push(c),
+            _ => outside.push(c),
+        }
+    }
+
+    if depth > 0 {
+        outside.push_str(&current);
+    }
+
+    (outside, notes)
+}
+
+fn parse_number(token: &str) -> Option<f64> {
+    if let Some((num, den)) = token.split_once('/') {
+        let num: f64 = num.parse().ok()?;
+        let den: f64 = den.parse().ok()?;
+        return (den != 0.0).then_some(num / den);
+    }
+    if !token.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    token.parse().ok().filter(|v: &f64| v.is_finite())
+}
+
+/// Parses a number possibly followed by a fraction ("1 1/2").
+fn parse_mixed(tokens: &[&str], pos: &mut usize) -> Option<f64> {
+    let whole = parse_number(tokens.get(*pos)?)?;
+    *pos += 1;
+
+    let is_whole = !tokens[*pos - 1].contains(['/', '.']);
+    if is_whole
+        && let Some(next) = tokens.get(*pos)
+        && next.contains('/')
+        && let Some(fraction) = parse_number(next)
+    {
+        *pos += 1;
+        return Some(whole + fraction);
+    }
+
+    Some(whole)
+}
+
+fn parse_quantity(tokens: &[&str], pos: &mut usize) -> Option<Quantity> {
+    // "2-3" as a single token
+    if let Some(token) = tokens.get(*pos)
+        && let Some((a, b)) = token.split_once('-')
+        && let (Some(min), Some(max)) = (parse_number(a), parse_number(b))
+    {
+        *pos += 1;
+        return Some(range(min, max));
+    }
+
+    let start = *pos;
+    let Some(min) = parse_mixed(tokens, pos) else {
+        *pos = start;
+        return None;
+    };
+
+    // "2 - 3", "2 to 3", "2 or 3"
+    if let Some(sep) = tokens.get(*pos)
+        && matches!(*sep, "-" | "to" | "or")
+    {
+        let mut lookahead = *pos + 1;
+        if let Some(max) = parse_mixed(tokens, &mut lookahead) {
+            *pos = lookahead;
+            return Some(range(min, max));
+        }
+    }
+
+    // "2- 3" / "2 -3"
+    if let Some(token) = tokens.get(*pos)
+        && let Some(stripped) = token.strip_prefix('-')
+        && let Some(max) = parse_number(stripped)
+    {
+        *pos += 1;
+        return Some(range(min, max));
+    }
+
+    Some(Quantity::Exact { value: min })
+}
+
+fn range(min: f64, max: f64) -> Quantity {
+    if (max - min).abs() < f64::EPSILON {
+        Quantity::Exact { value: min }
+    } else {
+        Quantity::Range {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+}
+
+fn parse_unit(tokens: &[&str]) -> Option<(Unit, usize)> {
+    let first = tokens.first()?;
+    if let Some(second) = tokens.get(1) {
+        let first_lower = first.trim_end_matches('.').to_lowercase();
+        let second_lower = second.trim_end_matches('.').to_lowercase();
+        if matches!(first_lower.as_str(), "fl" | "fluid")
+            && matches!(second_lower.as_str(), "oz" | "ounce" | "ounces")
+        {
+            return Some((Unit::FluidOunce, 2));
+        }
+    }
+
+    Unit::from_token(first).map(|unit| (unit, 1))
+}
+
+/// Handles quantities written without a space before the unit, e.g. "500g".
+fn split_glued_unit(token: &str) -> Option<(f64, Unit)> {
+    let split = token.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    if split == 0 {
+        return None;
+    }
+    let (number, unit) = token.split_at(split);
+    Some((parse_number(number)?, Unit::from_token(unit)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact(value: f64) -> Option<Quantity> {
+        Some(Quantity::Exact { value })
+    }
+
+    #[test]
+    fn parses_count_and_item() {
+        let parsed = ParsedIngredient::parse("2 chicken breasts");
+
+        assert_eq!(parsed.quantity, exact(2.0));
+        assert_eq!(parsed.unit, None);
+        assert_eq!(parsed.item, "chicken breasts");
+        assert_eq!(parsed.preparation, None);
+    }
+
+    #[test]
+    fn parses_quantity_unit_and_item() {
+        let parsed = ParsedIngredient::parse("1 cup rice");
+
+        assert_eq!(parsed.quantity, exact(1.0));
+        assert_eq!(parsed.unit, Some(Unit::Cup));
+        assert_eq!(parsed.item, "rice");
+    }
+
+    #[test]
+    fn parses_mixed_and_unicode_fractions() {
+        assert_eq!(
+            ParsedIngredient::parse("1 1/2 cups flour").quantity,
+            exact(1.5)
+        );
+        assert_eq!(ParsedIngredient::parse("1½ tsp salt").quantity, exact(1.5));
+        assert_eq!(ParsedIngredient::parse("¾ cup milk").quantity, exact(0.75));
+    }
+
+    #[test]
+    fn parses_ranges() {
+        let expected = Some(Quantity::Range { min: 2.0, max: 3.0 });
+
+        assert_eq!(
+            ParsedIngredient::parse("2-3 cloves garlic").quantity,
+            expected
+        );
+        assert_eq!(
+            ParsedIngredient::parse("2 – 3 cloves garlic").quantity,
+            expected
+        );
+        assert_eq!(
+            ParsedIngredient::parse("2 to 3 cloves garlic").quantity,
+            expected
+        );
+
+        let parsed = ParsedIngredient::parse("2-3 cloves garlic");
+        assert_eq!(parsed.unit, Some(Unit::Clove));
+        assert_eq!(parsed.item, "garlic");
+    }
+
+    #[test]
+    fn splits_preparation_and_parenthetical_notes() {
+        let parsed = ParsedIngredient::parse("1 (14 oz) can diced tomatoes, drained");
+
+        assert_eq!(parsed.quantity, exact(1.0));
+        assert_eq!(parsed.unit, Some(Unit::Can));
+        assert_eq!(parsed.item, "diced tomatoes");
+        assert_eq!(parsed.preparation.as_deref(), Some("drained, 14 oz"));
+    }
+
+    #[test]
+    fn handles_article_units_and_of() {
+        let parsed = ParsedIngredient::parse("a pinch of salt");
+
+        assert_eq!(parsed.quantity, exact(1.0));
+        assert_eq!(parsed.unit, Some(Unit::Pinch));
+        assert_eq!(parsed.item, "salt");
+    }
+
+    #[test]
+    fn handles_glued_units_and_abbreviations() {
+        let parsed = ParsedIngredient::parse("500g minced beef");
+        assert_eq!(parsed.quantity, exact(500.0));
+        assert_eq!(parsed.unit, Some(Unit::Gram));
+        assert_eq!(parsed.item, "minced beef");
+
+        assert_eq!(
+            ParsedIngredient::parse("2 T olive oil").unit,
+            Some(Unit::Tablespoon)
+        );
+        assert_eq!(
+            ParsedIngredient::parse("2 t vanilla").unit,
+            Some(Unit::Teaspoon)
+        );
+        assert_eq!(
+            ParsedIngredient::parse("4 fl oz cream").unit,
+            Some(Unit::FluidOunce)
+        );
+    }
+
+    #[test]
+    fn leaves_unquantified_lines_as_item() {
+        let parsed = ParsedIngredient::parse("salt and pepper to taste");
+
+        assert_eq!(parsed.quantity, None);
+        assert_eq!(parsed.unit, None);
+        assert_eq!(parsed.item, "salt and pepper to taste");
+    }
+
+    #[test]
+    fn does_not_treat_trailing_unit_word_as_unit() {
+        let parsed = ParsedIngredient::parse("3 cloves");
+
+        assert_eq!(parsed.quantity, exact(3.0));
+        assert_eq!(parsed.unit, None);
+        assert_eq!(parsed.item, "cloves");
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let parsed = ParsedIngredient::parse("1-2 tbsp honey");
+        let json = serde_json::to_string(&parsed).unwrap();
+        let back: ParsedIngredient = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, back);
+    }
+}
@@ -0,0 +1,266 @@
+use std::ops::{Add, Mul};
+use std::sync::OnceLock;
+
+use super::entities::Recipe;
+use super::ingredients::{ParsedIngredient, Quantity, Unit, mentions, words};
+use super::units::{G_PER_BUTTER_STICK, ML_PER_CUP, grams_per, grams_per_cup, millilitres_per};
+
+const NUTRIENT_TABLE: &str = include_str!("nutrients.csv");
+
+/// Approximate weights for units that do not map to a standard measure.
+const GRAMS_PER_PINCH: f64 = 0.36;
+const GRAMS_PER_DASH: f64 = 0.6;
+const GRAMS_PER_SPRIG: f64 = 1.0;
+const GRAMS_PER_HANDFUL: f64 = 30.0;
+const GRAMS_PER_BUNCH: f64 = 50.0;
+const GRAMS_PER_CAN: f64 = 400.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NutritionFacts {
+    pub calories: f64,
+    pub protein_g: f64,
+    pub fat_g: f64,
+    pub carbohydrates_g: f64,
+    pub fibre_g: f64,
+    pub sodium_mg: f64,
+}
+
+impl Add for NutritionFacts {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            calories: self.calories + other.calories,
+            protein_g: self.protein_g + other.protein_g,
+            fat_g: self.fat_g + other.fat_g,
+            carbohydrates_g: self.carbohydrates_g + other.carbohydrates_g,
+            fibre_g: self.fibre_g + other.fibre_g,
+            sodium_mg: self.sodium_mg + other.sodium_mg,
+        }
+    }
+}
+
+impl Mul<f64> for NutritionFacts {
+    type Output = Self;
+
+    fn mul(self, factor: f64) -> Self {
+        Self {
+            calories: self.calories * factor,
+            protein_g: self.protein_g * factor,
+            fat_g: self.fat_g * factor,
+            carbohydrates_g: self.carbohydrates_g * factor,
+            fibre_g: self.fibre_g * factor,
+            sodium_mg: self.sodium_mg * factor,
+        }
+    }
+}
+
+/// Nutrition estimate for a whole recipe. `per_serving` is only present when
+/// the recipe states how many servings it makes.
+#[derive(Debug, Clone)]
+pub struct NutritionReport {
+    pub total: NutritionFacts,
+    pub per_serving: Option<NutritionFacts>,
+    pub servings: Option<i32>,
+    pub unmatched_ingredients: Vec<String>,
+}
+
+struct Food {
+    words: Vec<String>,
+    per_100g: NutritionFacts,
+    grams_each: Option<f64>,
+}
+
+fn foods() -> &'static [Food] {
+    static FOODS: OnceLock<Vec<Food>> = OnceLock::new();
+    FOODS.get_or_init(|| {
+        NUTRIENT_TABLE
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .skip(1)
+            .filter_map(parse_food)
+            .collect()
+    })
+}
+
+fn parse_food(line: &str) -> Option<Food> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let [name, kcal, protein, fat, carbs, fibre, sodium, grams_each] = fields[..] else {
+        return None;
+    };
+    let number = |s: &str| s.parse::<f64>().ok();
+
+    Some(Food {
+        words: words(name),
+        per_100g: NutritionFacts {
+            calories: number(kcal)?,
+            protein_g: number(protein)?,
+            fat_g: number(fat)?,
+            carbohydrates_g: number(carbs)?,
+            fibre_g: number(fibre)?,
+            sodium_mg: number(sodium)?,
+        },
+        grams_each: number(grams_each).filter(|g| *g > 0.0),
+    })
+}
+
+/// Finds the food whose name appears in the item as whole words. The longest
+/// name wins, so "peanut butter" is not counted as butter.
+fn find_food(item: &str) -> Option<&'static Food> {
+    let item_words = words(item);
+    foods()
+        .iter()
+        .filter(|food| mentions(&item_words, &food.words))
+        .max_by_key(|food| food.words.iter().map(String::len).sum::<usize>())
+}
+
+fn grams_of(ingredient: &ParsedIngredient, food: &Food) -> Option<f64> {
+    let amount = match ingredient.quantity? {
+        Quantity::Exact { value } => value,
+        Quantity::Range { min, max } => (min + max) / 2.0,
+    };
+
+    let grams_per_unit = match ingredient.unit {
+        Some(unit) => {
+            if let Some(grams) = grams_per(unit) {
+                grams
+            } else if let Some(millilitres) = millilitres_per(unit) {
+                // Liquids without a known density are taken to weigh as much as water.
+                let grams_per_ml = grams_per_cup(&ingredient.item)
+                    .map(|g| g / ML_PER_CUP)
+                    .unwrap_or(1.0);
+                millilitres * grams_per_ml
+            } else {
+                match unit {
+                    Unit::Pinch => GRAMS_PER_PINCH,
+                    Unit::Dash => GRAMS_PER_DASH,
+                    Unit::Sprig => GRAMS_PER_SPRIG,
+                    Unit::Handful => GRAMS_PER_HANDFUL,
+                    Unit::Bunch => GRAMS_PER_BUNCH,
+                    Unit::Can | Unit::Package => GRAMS_PER_CAN,
+                    Unit::Stick if food.words == ["butter"] => G_PER_BUTTER_STICK,
+                    _ => food.grams_each?,
+                }
+            }
+        }
+        None => food.grams_each?,
+    };
+
+    Some(amount * grams_per_unit)
+}
+
+impl ParsedIngredient {
+    /// Nutrients for this line, or `None` when the food is not in the table
+    /// or its amount cannot be turned into a weight.
+    pub fn nutrition(&self) -> Option<NutritionFacts> {
+        let food = find_food(&self.item)?;
+        let grams = grams_of(self, food)?;
+        Some(food.per_100g * (grams / 100.0))
+    }
+}
+
+impl Recipe {
+    pub fn nutrition(&self) -> NutritionReport {
+        let mut total = NutritionFacts::default();
+        let mut unmatched_ingredients = Vec::new();
+
+        for (line, ingredient) in self.ingredients.iter().zip(self.structured_ingredients()) {
+            match ingredient.nutrition() {
+                Some(facts) => total = total + facts,
+                None => unmatched_ingredients.push(line.clone()),
+            }
+        }
+
+        let servings = self.servings.filter(|s| *s > 0);
+
+        NutritionReport {
+            total,
+            per_serving: servings.map(|s| total * (1.0 / f64::from(s))),
+            servings,
+            unmatched_ingredients,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn recipe(servings: Option<i32>, ingredients: &[&str]) -> Recipe {
+        let ingredients: Vec<String> = ingredients.iter().map(|s| s.to_string()).collect();
+        Recipe {
+            id: Uuid::new_v4(),
+            owner_id: "user_123".to_string(),
+            title: "Test".to_string(),
+            parsed_ingredients: ingredients
+                .iter()
+                .map(|l| ParsedIngredient::parse(l))
+                .collect(),
+            ingredients,
+            instructions: vec!["Cook".to_string()],
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            servings,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn calories(line: &str) -> Option<f64> {
+        ParsedIngredient::parse(line)
+            .nutrition()
+            .map(|f| f.calories.round())
+    }
+
+    #[test]
+    fn embedded_table_parses() {
+        assert!(foods().len() > 100);
+    }
+
+    #[test]
+    fn computes_from_weights_volumes_and_counts() {
+        assert_eq!(calories("200 g rice"), Some(730.0));
+        assert_eq!(calories("1 tbsp olive oil"), Some(131.0));
+        assert_eq!(calories("2 eggs"), Some(143.0));
+        assert_eq!(calories("1 cup flour"), Some(455.0));
+    }
+
+    #[test]
+    fn prefers_longest_whole_word_match() {
+        let peanut_butter = ParsedIngredient::parse("100 g peanut butter")
+            .nutrition()
+            .unwrap();
+        assert_eq!(peanut_butter.calories, 588.0);
+
+        let tomatoes = ParsedIngredient::parse("2 tomatoes, diced")
+            .nutrition()
+            .unwrap();
+        assert_eq!(tomatoes.calories.round(), 44.0);
+    }
+
+    #[test]
+    fn reports_per_serving_and_unmatched_lines() {
+        let report = recipe(
+            Some(2),
+            &["200 g chicken breast", "salt to taste", "1 cup unobtainium"],
+        )
+        .nutrition();
+
+        assert_eq!(report.total.calories.round(), 240.0);
+        assert_eq!(report.per_serving.unwrap().calories.round(), 120.0);
+        assert_eq!(
+            report.unmatched_ingredients,
+            vec!["salt to taste", "1 cup unobtainium"]
+        );
+    }
+
+    #[test]
+    fn omits_per_serving_without_servings() {
+        let report = recipe(None, &["1 egg"]).nutrition();
+
+        assert!(report.per_serving.is_none());
+        assert_eq!(report.total.protein_g.round(), 6.0);
+    }
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecipeSort {
+    /// Newest first. For shared recipes this is the date they were shared.
+    #[default]
+    CreatedAt,
+    /// Alphabetical, ignoring case.
+    Title,
+    /// Quickest first by prep + cook time; recipes without any time come last.
+    TotalTime,
+}
+
+/// The value a page was sorted on for its last recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKey {
+    CreatedAt(DateTime<Utc>),
+    Title(String),
+    TotalTime(i32),
+}
+
+impl SortKey {
+    pub fn sort(&self) -> RecipeSort {
+        match self {
+            SortKey::CreatedAt(_) => RecipeSort::CreatedAt,
+            SortKey::Title(_) => RecipeSort::Title,
+            SortKey::TotalTime(_) => RecipeSort::TotalTime,
+        }
+    }
+}
+
+/// Position just after the last recipe of a page. The recipe id breaks ties
+/// between recipes with the same sort key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeCursor {
+    pub key: SortKey,
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+pub struct PageRequest {
+    pub sort: RecipeSort,
+    pub limit: i64,
+    /// Continue after this position. Its key must be for `sort`.
+    pub after: Option<RecipeCursor>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Where the next page starts, or `None` on the last page.
+    pub next: Option<RecipeCursor>,
+}
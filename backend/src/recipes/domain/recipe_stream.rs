@@ -0,0 +1,174 @@
+/// A piece of a recipe recognised while the model is still writing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecipeStreamEvent {
+    Title(String),
+    Ingredient { index: usize, text: String },
+    Instruction { index: usize, text: String },
+}
+
+enum Frame {
+    Object { expect_key: bool },
+    Array,
+}
+
+/// Incrementally scans the JSON object a model streams back and reports the
+/// title, ingredients and instructions as soon as each string is complete.
+/// Anything before the opening brace, such as a markdown fence, is skipped;
+/// the complete reply is still parsed and validated separately at the end.
+#[derive(Default)]
+pub struct RecipeStreamParser {
+    stack: Vec<Frame>,
+    /// The top-level key whose value is being read.
+    field: Option<String>,
+    /// Raw contents of the string being read, escapes included.
+    string: Option<String>,
+    escaped: bool,
+    done: bool,
+    ingredients: usize,
+    instructions: usize,
+}
+
+impl RecipeStreamParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the next chunk of the reply and returns what it completed.
+    pub fn push(&mut self, chunk: &str) -> Vec<RecipeStreamEvent> {
+        let mut events = Vec::new();
+        for c in chunk.chars() {
+            if self.done {
+                break;
+            }
+            if let Some(raw) = &mut self.string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if c == '\\' {
+                    self.escaped = true;
+                } else if c == '"' {
+                    let raw = self.string.take().unwrap_or_default();
+                    events.extend(self.finish_string(&raw));
+                    continue;
+                }
+                raw.push(c);
+                continue;
+            }
+
+            match c {
+                '{' => self.stack.push(Frame::Object { expect_key: true }),
+                '[' if !self.stack.is_empty() => self.stack.push(Frame::Array),
+                '}' | ']' if !self.stack.is_empty() => {
+                    self.stack.pop();
+                    self.done = self.stack.is_empty();
+                }
+                '"' if !self.stack.is_empty() => self.string = Some(String::new()),
+                ':' => self.set_expect_key(false),
+                ',' => self.set_expect_key(true),
+                _ => {}
+            }
+        }
+        events
+    }
+
+    fn set_expect_key(&mut self, value: bool) {
+        if let Some(Frame::Object { expect_key }) = self.stack.last_mut() {
+            *expect_key = value;
+        }
+    }
+
+    fn finish_string(&mut self, raw: &str) -> Option<RecipeStreamEvent> {
+        let text: String = serde_json::from_str(&format!("\"{raw}\"")).ok()?;
+
+        match self.stack.as_slice() {
+            [Frame::Object { expect_key: true }] => {
+                self.field = Some(text);
+                None
+            }
+            [Frame::Object { expect_key: false }] if self.field.as_deref() == Some("title") => {
+                Some(RecipeStreamEvent::Title(text))
+            }
+            [Frame::Object { .. }, Frame::Array] => match self.field.as_deref() {
+                Some("ingredients") => {
+                    self.ingredients += 1;
+                    Some(RecipeStreamEvent::Ingredient {
+                        index: self.ingredients - 1,
+                        text,
+                    })
+                }
+                Some("instructions") => {
+                    self.instructions += 1;
+                    Some(RecipeStreamEvent::Instruction {
+                        index: self.instructions - 1,
+                        text,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLY: &str = r#"```json
+{
+  "title": "Tomato \"Soup\"",
+  "ingredients": ["2 tomatoes", "1 cup stock, warm"],
+  "instructions": ["Chop éverything", "Simmer {gently}"],
+  "prep_time_minutes": 5,
+  "cook_time_minutes": 20,
+  "servings": 2
+}
+```"#;
+
+    fn expected() -> Vec<RecipeStreamEvent> {
+        vec![
+            RecipeStreamEvent::Title("Tomato \"Soup\"".to_string()),
+            RecipeStreamEvent::Ingredient {
+                index: 0,
+                text: "2 tomatoes".to_string(),
+            },
+            RecipeStreamEvent::Ingredient {
+                index: 1,
+                text: "1 cup stock, warm".to_string(),
+            },
+            RecipeStreamEvent::Instruction {
+                index: 0,
+                text: "Chop éverything".to_string(),
+            },
+            RecipeStreamEvent::Instruction {
+                index: 1,
+                text: "Simmer {gently}".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn reports_fields_from_a_complete_reply() {
+        assert_eq!(RecipeStreamParser::new().push(REPLY), expected());
+    }
+
+    #[test]
+    fn reports_the_same_fields_when_split_at_every_character() {
+        let mut parser = RecipeStreamParser::new();
+        let events: Vec<_> = REPLY
+            .chars()
+            .flat_map(|c| parser.push(&c.to_string()))
+            .collect();
+
+        assert_eq!(events, expected());
+    }
+
+    #[test]
+    fn ignores_strings_in_other_fields_and_nested_objects() {
+        let mut parser = RecipeStreamParser::new();
+        let events = parser.push(
+            r#"{"notes": ["not an ingredient"], "meta": {"title": "nested"}, "title": "Real"}"#,
+        );
+
+        assert_eq!(events, vec![RecipeStreamEvent::Title("Real".to_string())]);
+    }
+}
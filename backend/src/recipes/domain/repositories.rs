@@ -1,11 +1,23 @@
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use super::entities::{Recipe, RecipeShare};
+use super::entities::{
+    Collection, CollectionShare, Generation, Recipe, RecipeRevision, RecipeShare,
+};
+use super::pagination::{Page, PageRequest};
+use super::search::RecipeSearch;
+use super::usage::{QuotaLimits, Requester, TokenUsage, UsageTotals};
 
 #[derive(Debug, thiserror::Error)]
 pub enum RepositoryError {
     #[error("Recipe not found")]
     NotFound,
+    #[error("Revision not found")]
+    RevisionNotFound,
+    #[error("Collection not found")]
+    CollectionNotFound,
+    #[error("Generation not found")]
+    GenerationNotFound,
     #[error("Access denied")]
     AccessDenied,
     #[error("Database error: {0}")]
@@ -18,6 +30,26 @@ pub trait RecipeRepository: Send + Sync {
         recipe: &Recipe,
     ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
 
+    /// Persists the recipe's new content and records it as the next revision,
+    /// attributed to `editor_id`.
+    fn update(
+        &self,
+        recipe: &Recipe,
+        editor_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn delete(
+        &self,
+        id: Uuid,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Stores the recipe's tags. Tags are not part of the recipe's content, so
+    /// no revision is recorded.
+    fn update_tags(
+        &self,
+        recipe: &Recipe,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
     fn find_by_id(
         &self,
         id: Uuid,
@@ -26,12 +58,33 @@ pub trait RecipeRepository: Send + Sync {
     fn find_by_owner(
         &self,
         owner_id: &str,
-    ) -> impl std::future::Future<Output = Result<Vec<Recipe>, RepositoryError>> + Send;
+        page: &PageRequest,
+    ) -> impl std::future::Future<Output = Result<Page<Recipe>, RepositoryError>> + Send;
 
+    /// Recipes shared with the user directly or through a shared collection,
+    /// most recently shared first when sorted by date.
     fn find_shared_with_user(
         &self,
         user_id: &str,
+        page: &PageRequest,
+    ) -> impl std::future::Future<Output = Result<Page<Recipe>, RepositoryError>> + Send;
+
+    /// Recipes visible to `search.user_id` that match the search, best match first.
+    fn search(
+        &self,
+        search: &RecipeSearch,
     ) -> impl std::future::Future<Output = Result<Vec<Recipe>, RepositoryError>> + Send;
+
+    fn find_revisions(
+        &self,
+        recipe_id: Uuid,
+    ) -> impl std::future::Future<Output = Result<Vec<RecipeRevision>, RepositoryError>> + Send;
+
+    fn find_revision(
+        &self,
+        recipe_id: Uuid,
+        revision: i32,
+    ) -> impl std::future::Future<Output = Result<RecipeRevision, RepositoryError>> + Send;
 }
 
 pub trait RecipeShareRepository: Send + Sync {
@@ -46,6 +99,8 @@ pub trait RecipeShareRepository: Send + Sync {
         user_id: &str,
     ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
 
+    /// Whether the recipe was shared with the user directly or through a
+    /// shared collection of its owner's.
     fn is_shared_with_user(
         &self,
         recipe_id: Uuid,
@@ -57,3 +112,135 @@ pub trait RecipeShareRepository: Send + Sync {
         recipe_id: Uuid,
     ) -> impl std::future::Future<Output = Result<Vec<RecipeShare>, RepositoryError>> + Send;
 }
+
+pub trait CollectionRepository: Send + Sync {
+    fn save(
+        &self,
+        collection: &Collection,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn rename(
+        &self,
+        collection: &Collection,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn delete(
+        &self,
+        id: Uuid,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn find_by_id(
+        &self,
+        id: Uuid,
+    ) -> impl std::future::Future<Output = Result<Collection, RepositoryError>> + Send;
+
+    fn find_by_owner(
+        &self,
+        owner_id: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<Collection>, RepositoryError>> + Send;
+
+    fn find_shared_with_user(
+        &self,
+        user_id: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<Collection>, RepositoryError>> + Send;
+
+    /// Adding a recipe that is already in the collection does nothing.
+    fn add_recipe(
+        &self,
+        collection_id: Uuid,
+        recipe_id: Uuid,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn remove_recipe(
+        &self,
+        collection_id: Uuid,
+        recipe_id: Uuid,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// The recipes in the collection that `viewer_id` is allowed to see,
+    /// most recently added first.
+    fn find_recipes(
+        &self,
+        collection_id: Uuid,
+        viewer_id: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<Recipe>, RepositoryError>> + Send;
+
+    fn create_share(
+        &self,
+        share: &CollectionShare,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn delete_share(
+        &self,
+        collection_id: Uuid,
+        user_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn is_shared_with_user(
+        &self,
+        collection_id: Uuid,
+        user_id: &str,
+    ) -> impl std::future::Future<Output = Result<bool, RepositoryError>> + Send;
+
+    fn find_shares(
+        &self,
+        collection_id: Uuid,
+    ) -> impl std::future::Future<Output = Result<Vec<CollectionShare>, RepositoryError>> + Send;
+}
+
+/// Records what each generation cost, for quotas and usage reports.
+pub trait UsageRepository: Send + Sync {
+    /// Counts one generation for `requester`, with no tokens yet, unless it
+    /// would take them past `limits` for the day starting at `day` or the
+    /// month starting at `month`, in which case `None` is returned.
+    /// Reservations for the same requester are made one at a time, so
+    /// concurrent requests cannot overshoot a quota.
+    fn reserve(
+        &self,
+        requester: &Requester,
+        limits: QuotaLimits,
+        day: DateTime<Utc>,
+        month: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<Option<Uuid>, RepositoryError>> + Send;
+
+    /// Records the tokens the reserved generation used.
+    fn settle(
+        &self,
+        reservation: Uuid,
+        tokens: TokenUsage,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Gives back a reservation for a generation that never called the model.
+    fn release(
+        &self,
+        reservation: Uuid,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Generations made for `requester` since `since`, with their tokens.
+    fn totals_since(
+        &self,
+        requester: &Requester,
+        since: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<UsageTotals, RepositoryError>> + Send;
+}
+
+/// Keeps signed-in users' generations for the configured retention period.
+/// Older generations are treated as gone.
+pub trait GenerationRepository: Send + Sync {
+    fn save(
+        &self,
+        generation: &Generation,
+    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+
+    fn find_by_id(
+        &self,
+        id: Uuid,
+    ) -> impl std::future::Future<Output = Result<Generation, RepositoryError>> + Send;
+
+    /// The user's generations, newest first whatever `page.sort` says.
+    fn find_by_user(
+        &self,
+        user_id: &str,
+        page: &PageRequest,
+    ) -> impl std::future::Future<Output = Result<Page<Generation>, RepositoryError>> + Send;
+}
@@ -0,0 +1,507 @@
+use super::entities::{GeneratedRecipe, Recipe};
+use super::ingredients::{ParsedIngredient, Quantity, Unit};
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ScalingError {
+    #[error("Recipe has no serving count to scale from")]
+    UnknownServings,
+    #[error("Servings must be a positive number")]
+    InvalidServings,
+}
+
+/// Outcome of rescaling a recipe: which factor was applied and which ingredient
+/// lines had to be left as they were because no quantity could be read from them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scaling {
+    pub original_servings: i32,
+    pub servings: i32,
+    pub factor: f64,
+    pub unscaled_ingredients: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Scaled<T> {
+    pub recipe: T,
+    pub scaling: Scaling,
+}
+
+impl Recipe {
+    pub fn scale_to(&self, servings: i32) -> Result<Scaled<Recipe>, ScalingError> {
+        let parsed = self.structured_ingredients();
+        let lines = scale_lines(&self.ingredients, &parsed, self.servings, servings)?;
+
+        let mut recipe = self.clone();
+        recipe.ingredients = lines.text;
+        recipe.parsed_ingredients = lines.parsed;
+        recipe.servings = Some(servings);
+
+        Ok(Scaled {
+            recipe,
+            scaling: lines.scaling,
+        })
+    }
+}
+
+impl GeneratedRecipe {
+    pub fn scale_to(&self, servings: i32) -> Result<Scaled<GeneratedRecipe>, ScalingError> {
+        let parsed: Vec<ParsedIngredient> = self
+            .ingredients
+            .iter()
+            .map(|l| ParsedIngredient::parse(l))
+            .collect();
+        let lines = scale_lines(&self.ingredients, &parsed, self.servings, servings)?;
+
+        let mut recipe = self.clone();
+        recipe.ingredients = lines.text;
+        recipe.servings = Some(servings);
+
+        Ok(Scaled {
+            recipe,
+            scaling: lines.scaling,
+        })
+    }
+}
+
+struct ScaledLines {
+    text: Vec<String>,
+    parsed: Vec<ParsedIngredient>,
+    scaling: Scaling,
+}
+
+fn scale_lines(
+    lines: &[String],
+    parsed: &[ParsedIngredient],
+    from: Option<i32>,
+    to: i32,
+) -> Result<ScaledLines, ScalingError> {
+    if to <= 0 {
+        return Err(ScalingError::InvalidServings);
+    }
+    let from = from
+        .filter(|s| *s > 0)
+        .ok_or(ScalingError::UnknownServings)?;
+    let factor = f64::from(to) / f64::from(from);
+
+    let mut text = Vec::with_capacity(lines.len());
+    let mut scaled_parsed = Vec::with_capacity(lines.len());
+    let mut unscaled = Vec::new();
+
+    for (line, ingredient) in lines.iter().zip(parsed) {
+        match ingredient.quantity {
+            Some(quantity) if from != to => {
+                let scaled = ParsedIngredient {
+                    quantity: Some(scale_quantity(quantity, factor, ingredient.unit)),
+                    ..ingredient.clone()
+                };
+                text.push(format_ingredient(&scaled));
+                scaled_parsed.push(scaled);
+            }
+            Some(_) => {
+                text.push(line.clone());
+                scaled_parsed.push(ingredient.clone());
+            }
+            None => {
+                unscaled.push(line.clone());
+                text.push(line.clone());
+                scaled_parsed.push(ingredient.clone());
+            }
+        }
+    }
+
+    Ok(ScaledLines {
+        text,
+        parsed: scaled_parsed,
+        scaling: Scaling {
+            original_servings: from,
+            servings: to,
+            factor,
+            unscaled_ingredients: unscaled,
+        },
+    })
+}
+
+fn scale_quantity(quantity: Quantity, factor: f64, unit: Option<Unit>) -> Quantity {
+    match quantity {
+        Quantity::Exact { value } => Quantity::Exact {
+            value: round_for_kitchen(value * factor, unit),
+        },
+        Quantity::Range { min, max } => Quantity::Range {
+            min: round_for_kitchen(min * factor, unit),
+            max: round_for_kitchen(max * factor, unit),
+        },
+    }
+}
+
+const FRACTIONS: [(f64, &str); 9] = [
+    (1.0 / 8.0, "1/8"),
+    (1.0 / 4.0, "1/4"),
+    (1.0 / 3.0, "1/3"),
+    (3.0 / 8.0, "3/8"),
+    (1.0 / 2.0, "1/2"),
+    (5.0 / 8.0, "5/8"),
+    (2.0 / 3.0, "2/3"),
+    (3.0 / 4.0, "3/4"),
+    (7.0 / 8.0, "7/8"),
+];
+
+/// Rounds a scaled amount to something that can actually be measured:
+/// whole grams/millilitres (or steps of 5 for larger amounts), and common
+/// measuring-spoon/cup fractions for everything else. Positive amounts never
+/// round down to zero.
+pub(super) fn round_for_kitchen(value: f64, unit: Option<Unit>) -> f64 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+
+    let rounded = match unit {
+        Some(Unit::Gram | Unit::Milliliter) => {
+            if value >= 100.0 {
+                (value / 5.0).round() * 5.0
+            } else if value >= 10.0 {
+                value.round()
+            } else {
+                (value * 2.0).round() / 2.0
+            }
+        }
+        Some(Unit::Kilogram | Unit::Liter) => (value * 20.0).round() / 20.0,
+        None => {
+            if value >= 5.0 {
+                value.round()
+            } else {
+                (value * 4.0).round() / 4.0
+            }
+        }
+        Some(_) => {
+            if value >= 20.0 {
+                value.round()
+            } else if value >= 5.0 {
+                (value * 2.0).round() / 2.0
+            } else {
+                nearest_fraction(value)
+            }
+        }
+    };
+
+    if rounded > 0.0 {
+        rounded
+    } else {
+        match unit {
+            Some(Unit::Gram | Unit::Milliliter) => 0.5,
+            Some(Unit::Kilogram | Unit::Liter) => 0.05,
+            None => 0.25,
+            Some(_) => FRACTIONS[0].0,
+        }
+    }
+}
+
+fn nearest_fraction(value: f64) -> f64 {
+    let whole = value.trunc();
+    let remainder = value - whole;
+    let candidates = std::iter::once(0.0)
+        .chain(FRACTIONS.iter().map(|(f, _)| *f))
+        .chain(std::iter::once(1.0));
+
+    let best = candidates
+        .min_by(|a, b| {
+            (a - remainder)
+                .abs()
+                .partial_cmp(&(b - remainder).abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(0.0);
+
+    whole + best
+}
+
+pub(super) fn format_amount(value: f64, unit: Option<Unit>) -> String {
+    if matches!(
+        unit,
+        Some(Unit::Gram | Unit::Milliliter | Unit::Kilogram | Unit::Liter)
+    ) {
+        let text = format!("{:.2}", value);
+        return text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+
+    let whole = value.trunc();
+    let remainder = value - whole;
+    let fraction = FRACTIONS
+        .iter()
+        .find(|(f, _)| (f - remainder).abs() < 0.01)
+        .map(|(_, s)| *s);
+
+    match (whole as i64, fraction) {
+        (0, Some(f)) => f.to_string(),
+        (w, Some(f)) => format!("{} {}", w, f),
+        (w, None) if remainder.abs() < 0.01 => w.to_string(),
+        (w, None) if (1.0 - remainder).abs() < 0.01 => (w + 1).to_string(),
+        _ => {
+            let text = format!("{:.2}", value);
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+    }
+}
+
+pub(super) fn format_ingredient(ingredient: &ParsedIngredient) -> String {
+    let mut parts = Vec::new();
+    // Whether the amount is more than one; `None` without an amount.
+    let mut plural = None;
+
+    if let Some(quantity) = ingredient.quantity {
+        let text = match quantity {
+            Quantity::Exact { value } => {
+                plural = Some(value > 1.0);
+                format_amount(value, ingredient.unit)
+            }
+            Quantity::Range { min, max } => {
+                plural = Some(true);
+                format!(
+                    "{}-{}",
+                    format_amount(min, ingredient.unit),
+                    format_amount(max, ingredient.unit)
+                )
+            }
+        };
+        parts.push(text);
+    }
+
+    match ingredient.unit {
+        Some(unit) => {
+            parts.push(unit.label(plural == Some(true)).to_string());
+            parts.push(ingredient.item.clone());
+        }
+        None => parts.push(match plural {
+            Some(true) => pluralize_item(&ingredient.item),
+            Some(false) => singularize_item(&ingredient.item),
+            None => ingredient.item.clone(),
+        }),
+    }
+
+    let mut line = parts.join(" ");
+    if let Some(preparation) = &ingredient.preparation {
+        line.push_str(", ");
+        line.push_str(preparation);
+    }
+    line
+}
+
+/// Counted item in the plural, e.g. "red onion" to "red onions". Only the
+/// last word changes, and words that already end in "s" are left as they
+/// are.
+fn pluralize_item(item: &str) -> String {
+    let (start, last) = match item.rsplit_once(' ') {
+        Some((start, last)) => (item[..start.len() + 1].to_string(), last),
+        None => (String::new(), item),
+    };
+    if last.is_empty() || !last.chars().all(char::is_alphabetic) {
+        return item.to_string();
+    }
+
+    let lower = last.to_lowercase();
+    let plural = if lower.ends_with('s') {
+        last.to_string()
+    } else if ["ch", "sh", "x", "z"]
+        .iter()
+        .any(|end| lower.ends_with(end))
+        || ["tomato", "potato"].contains(&lower.as_str())
+    {
+        format!("{}es", last)
+    } else if lower
+        .strip_suffix('y')
+        .is_some_and(|stem| !stem.ends_with(|c| "aeiou".contains(c)))
+    {
+        format!("{}ies", &last[..last.len() - 1])
+    } else {
+        format!("{}s", last)
+    };
+    format!("{}{}", start, plural)
+}
+
+/// Counted item in the singular, e.g. "red onions" to "red onion", undoing
+/// [`pluralize_item`]. Only the last word changes, and words that do not
+/// look plural are left as they are.
+fn singularize_item(item: &str) -> String {
+    let (start, last) = match item.rsplit_once(' ') {
+        Some((start, last)) => (item[..start.len() + 1].to_string(), last),
+        None => (String::new(), item),
+    };
+    if last.is_empty() || !last.chars().all(char::is_alphabetic) {
+        return item.to_string();
+    }
+
+    let lower = last.to_lowercase();
+    let singular = if ["ss", "us", "is"].iter().any(|end| lower.ends_with(end)) {
+        last.to_string()
+    } else if ["ches", "shes", "xes", "zes"]
+        .iter()
+        .any(|end| lower.ends_with(end))
+        || ["tomatoes", "potatoes"].contains(&lower.as_str())
+    {
+        last[..last.len() - 2].to_string()
+    } else if lower.len() > 3
+        && lower.ends_with("ies")
+        && !["cookies", "brownies", "pies"].contains(&lower.as_str())
+    {
+        format!("{}y", &last[..last.len() - 3])
+    } else if lower.ends_with('s') {
+        last[..last.len() - 1].to_string()
+    } else {
+        last.to_string()
+    };
+    format!("{}{}", start, singular)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generated(servings: Option<i32>, ingredients: &[&str]) -> GeneratedRecipe {
+        GeneratedRecipe {
+            title: "Test".to_string(),
+            ingredients: ingredients.iter().map(|s| s.to_string()).collect(),
+            instructions: vec!["Cook".to_string()],
+            prep_time_minutes: Some(10),
+            cook_time_minutes: Some(20),
+            servings,
+        }
+    }
+
+    #[test]
+    fn scales_quantities_by_serving_ratio() {
+        let recipe = generated(Some(4), &["2 chicken breasts", "1 cup rice", "500 g beef"]);
+
+        let scaled = recipe.scale_to(10).unwrap();
+
+        assert_eq!(scaled.scaling.factor, 2.5);
+        assert_eq!(scaled.recipe.servings, Some(10));
+        assert_eq!(
+            scaled.recipe.ingredients,
+            vec!["5 chicken breasts", "2 1/2 cups rice", "1250 g beef"]
+        );
+        assert!(scaled.scaling.unscaled_ingredients.is_empty());
+    }
+
+    #[test]
+    fn rounds_to_kitchen_fractions() {
+        let recipe = generated(Some(3), &["1 tsp salt", "1/2 cup milk"]);
+
+        let scaled = recipe.scale_to(2).unwrap();
+
+        assert_eq!(
+            scaled.recipe.ingredients,
+            vec!["2/3 tsp salt", "1/3 cup milk"]
+        );
+    }
+
+    #[test]
+    fn scales_ranges_and_keeps_preparation() {
+        let recipe = generated(Some(2), &["2-3 cloves garlic, minced"]);
+
+        let scaled = recipe.scale_to(4).unwrap();
+
+        assert_eq!(scaled.recipe.ingredients, vec!["4-6 cloves garlic, minced"]);
+    }
+
+    #[test]
+    fn flags_lines_without_quantity() {
+        let recipe = generated(Some(2), &["salt and pepper to taste", "1 onion"]);
+
+        let scaled = recipe.scale_to(4).unwrap();
+
+        assert_eq!(
+            scaled.scaling.unscaled_ingredients,
+            vec!["salt and pepper to taste"]
+        );
+        assert_eq!(scaled.recipe.ingredients[0], "salt and pepper to taste");
+        assert_eq!(scaled.recipe.ingredients[1], "2 onions");
+    }
+
+    #[test]
+    fn pluralizes_counted_items() {
+        let recipe = generated(
+            Some(1),
+            &[
+                "1 red onion",
+                "1 tomato",
+                "1 peach, sliced",
+                "1 cherry",
+                "2 eggs",
+                "1 cup rice",
+            ],
+        );
+
+        let scaled = recipe.scale_to(2).unwrap();
+
+        assert_eq!(
+            scaled.recipe.ingredients,
+            vec![
+                "2 red onions",
+                "2 tomatoes",
+                "2 peaches, sliced",
+                "2 cherries",
+                "4 eggs",
+                "2 cups rice"
+            ]
+        );
+    }
+
+    #[test]
+    fn singularizes_counted_items_when_scaling_down() {
+        let recipe = generated(
+            Some(4),
+            &[
+                "2 onions",
+                "2 eggs",
+                "2 tomatoes",
+                "2 peaches, sliced",
+                "2 cherries",
+                "2 cookies",
+                "4 cloves garlic",
+                "1 onion",
+            ],
+        );
+
+        let scaled = recipe.scale_to(2).unwrap();
+
+        assert_eq!(
+            scaled.recipe.ingredients,
+            vec![
+                "1 onion",
+                "1 egg",
+                "1 tomato",
+                "1 peach, sliced",
+                "1 cherry",
+                "1 cookie",
+                "2 cloves garlic",
+                "1/2 onion"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_original_text_when_servings_unchanged() {
+        let recipe = generated(Some(4), &["1 ½ cups flour"]);
+
+        let scaled = recipe.scale_to(4).unwrap();
+
+        assert_eq!(scaled.recipe.ingredients, vec!["1 ½ cups flour"]);
+    }
+
+    #[test]
+    fn requires_known_and_positive_servings() {
+        assert_eq!(
+            generated(None, &["1 egg"]).scale_to(2).unwrap_err(),
+            ScalingError::UnknownServings
+        );
+        assert_eq!(
+            generated(Some(2), &["1 egg"]).scale_to(0).unwrap_err(),
+            ScalingError::InvalidServings
+        );
+    }
+
+    #[test]
+    fn never_rounds_positive_amounts_to_zero() {
+        assert_eq!(round_for_kitchen(0.01, Some(Unit::Teaspoon)), 0.125);
+        assert_eq!(round_for_kitchen(0.1, Some(Unit::Gram)), 0.5);
+        assert_eq!(round_for_kitchen(0.1, None), 0.25);
+    }
+}
@@ -0,0 +1,27 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchScope {
+    /// Recipes the user owns and recipes shared with them.
+    #[default]
+    All,
+    Owned,
+    Shared,
+}
+
+/// Full-text search over the recipes a user can see. An empty `text` matches
+/// every recipe, so the filters can also be used on their own.
+#[derive(Debug, Clone)]
+pub struct RecipeSearch {
+    pub user_id: String,
+    pub text: String,
+    pub scope: SearchScope,
+    /// Upper bound on prep + cook time. Recipes without any time are excluded
+    /// when this is set.
+    pub max_total_minutes: Option<i32>,
+    /// Every term must appear, as whole words, in at least one ingredient line.
+    pub include_ingredients: Vec<String>,
+    /// No ingredient line may contain any of these terms as whole words.
+    pub exclude_ingredients: Vec<String>,
+    /// The recipe must carry every one of these tags.
+    pub tags: Vec<String>,
+    pub limit: i64,
+}
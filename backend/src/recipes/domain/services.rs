@@ -1,16 +1,144 @@
+use super::constraints::RecipeConstraints;
 use super::entities::GeneratedRecipe;
+use super::substitutions::SuggestedSubstitute;
+use super::usage::UsageMeter;
+use super::validation::RecipeViolation;
 use std::future::Future;
 
+/// The prompt picked for one request. Every model call made for the request
+/// is given it so that they all use the same prompt, and the request's
+/// recipes are cached under its [`version`](Self::version), to be reused only
+/// by requests given the same prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptChoice {
+    /// The model and prompt version, e.g. `openai/gpt-4o-mini/prompt-v2`.
+    pub version: String,
+    /// The version of the prompt templates alone, e.g. `v2`.
+    pub prompt: String,
+}
+
+/// Every call takes the request's `PromptChoice`, and a `UsageMeter` to which
+/// services that bill for tokens report each request they make to the model.
 pub trait LlmService: Send + Sync {
+    /// The model and prompt version behind this service's recipes, e.g.
+    /// `openai/gpt-4o-mini/prompt-1`. Cached recipes are only reused while it
+    /// stays the same.
+    fn version(&self) -> String;
+
+    /// Picks the prompt for one request. Services that try a second prompt
+    /// on part of the traffic choose between the two here; others always
+    /// give their only prompt, under [`version`](Self::version).
+    fn choose_prompt(&self) -> PromptChoice {
+        let version = self.version();
+        PromptChoice {
+            prompt: version.clone(),
+            version,
+        }
+    }
+
     fn generate_recipe(
         &self,
         ingredients: Vec<String>,
         dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
     ) -> impl Future<Output = Result<GeneratedRecipe, LlmError>> + Send;
+
+    /// `recipe` revised as `instruction` asks, e.g. "make it spicier" or
+    /// "no oven".
+    fn refine_recipe(
+        &self,
+        recipe: GeneratedRecipe,
+        instruction: String,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> impl Future<Output = Result<GeneratedRecipe, LlmError>> + Send;
+
+    /// Replacements for `ingredient`, one of `recipe`'s ingredient lines,
+    /// sized to its amount and respecting `dietary_restrictions`.
+    fn suggest_substitutes(
+        &self,
+        recipe: GeneratedRecipe,
+        ingredient: String,
+        dietary_restrictions: Option<Vec<String>>,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> impl Future<Output = Result<Vec<SuggestedSubstitute>, LlmError>> + Send;
+
+    /// Like `generate_recipe`, but hands each piece of the reply's text to
+    /// `on_text` as the model produces it. Services without a streaming mode
+    /// pass the whole recipe in one piece.
+    fn stream_recipe<F: FnMut(&str) + Send>(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+        mut on_text: F,
+    ) -> impl Future<Output = Result<GeneratedRecipe, LlmError>> + Send {
+        async move {
+            let recipe = self
+                .generate_recipe(
+                    ingredients,
+                    dietary_restrictions,
+                    constraints,
+                    prompt,
+                    usage,
+                )
+                .await?;
+            if let Ok(text) = serde_json::to_string(&recipe) {
+                on_text(&text);
+            }
+            Ok(recipe)
+        }
+    }
+
+    /// Up to `count` different recipes from the same ingredients. Services
+    /// that cannot ask for several at once generate them one at a time, so
+    /// the caller should expect and drop near-duplicates.
+    fn generate_recipes(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        count: usize,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> impl Future<Output = Result<Vec<GeneratedRecipe>, LlmError>> + Send {
+        async move {
+            let mut recipes = Vec::with_capacity(count);
+            for _ in 0..count {
+                recipes.push(
+                    self.generate_recipe(
+                        ingredients.clone(),
+                        dietary_restrictions.clone(),
+                        constraints.clone(),
+                        prompt,
+                        usage,
+                    )
+                    .await?,
+                );
+            }
+            Ok(recipes)
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum LlmError {
     ApiError(String),
     ParseError(String),
+    /// The provider did not answer in time.
+    Timeout(String),
+    /// The provider kept refusing requests over its rate limit.
+    RateLimited(String),
+    /// The provider is down or failing, or calls to it are being cut short
+    /// after repeated failures.
+    Unavailable(String),
+    /// The model kept producing recipes that break a requested dietary restriction.
+    DietaryViolation(String),
+    /// The model kept producing recipes with these problems.
+    InvalidRecipe(Vec<RecipeViolation>),
 }
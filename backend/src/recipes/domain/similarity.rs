@@ -0,0 +1,126 @@
+use std::collections::BTreeSet;
+
+use super::entities::GeneratedRecipe;
+use super::ingredients::words;
+
+/// Share of title words two recipes must have in common to be the same dish.
+const SIMILAR_TITLES: f64 = 0.75;
+/// Share of ingredient lines two recipes must have in common to be the same
+/// dish. Lines are compared whole, amounts included, because recipes made
+/// from the same few provided ingredients always share their names.
+const SIMILAR_INGREDIENTS: f64 = 0.8;
+
+/// Title words that do not tell one dish from another.
+const FILLER_WORDS: &[&str] = &[
+    "a", "an", "and", "the", "with", "of", "in", "on", "style", "easy", "simple", "quick",
+];
+
+fn title_words(title: &str) -> BTreeSet<String> {
+    words(title)
+        .into_iter()
+        .filter(|word| !FILLER_WORDS.contains(&word.as_str()))
+        .map(|word| match word.strip_suffix('s') {
+            Some(singular) if singular.len() > 2 => singular.to_string(),
+            _ => word,
+        })
+        .collect()
+}
+
+fn ingredient_lines(lines: &[String]) -> BTreeSet<String> {
+    lines
+        .iter()
+        .map(|line| words(line).join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Size of the intersection over the size of the union; 0 for two empty sets.
+fn overlap(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+impl GeneratedRecipe {
+    /// Whether `other` is the same dish in different words: the titles name
+    /// nearly the same things, or nearly every ingredient line is shared.
+    pub fn is_near_duplicate_of(&self, other: &GeneratedRecipe) -> bool {
+        overlap(&title_words(&self.title), &title_words(&other.title)) >= SIMILAR_TITLES
+            || overlap(
+                &ingredient_lines(&self.ingredients),
+                &ingredient_lines(&other.ingredients),
+            ) >= SIMILAR_INGREDIENTS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(title: &str, ingredients: &[&str]) -> GeneratedRecipe {
+        GeneratedRecipe {
+            title: title.to_string(),
+            ingredients: ingredients.iter().map(|s| s.to_string()).collect(),
+            instructions: vec!["Cook".to_string()],
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            servings: None,
+        }
+    }
+
+    #[test]
+    fn reworded_titles_are_duplicates() {
+        let a = recipe("Chicken Fried Rice", &["2 chicken thighs"]);
+        let b = recipe("Fried Rice with Chicken", &["1 cup rice"]);
+        let c = recipe("Easy Garlic Chicken Stir-Fry", &["1 clove garlic"]);
+        let d = recipe("Chicken Stir Fry", &["1 onion"]);
+
+        assert!(a.is_near_duplicate_of(&b));
+        assert!(c.is_near_duplicate_of(&d));
+    }
+
+    #[test]
+    fn matching_ingredient_lists_are_duplicates() {
+        let a = recipe(
+            "Tomato Pasta",
+            &[
+                "200 g pasta",
+                "3 tomatoes, diced",
+                "1 tbsp olive oil",
+                "Salt",
+            ],
+        );
+        let b = recipe(
+            "Pomodoro",
+            &[
+                "200 g Pasta",
+                "3 Tomatoes diced",
+                "1 tbsp olive oil",
+                "salt",
+            ],
+        );
+
+        assert!(a.is_near_duplicate_of(&b));
+    }
+
+    #[test]
+    fn different_dishes_from_the_same_ingredients_are_kept() {
+        let soup = recipe(
+            "Chicken and Rice Soup",
+            &["2 chicken thighs", "1/2 cup rice", "6 cups water", "Salt"],
+        );
+        let fried_rice = recipe(
+            "Chicken Fried Rice",
+            &[
+                "1 chicken breast, diced",
+                "2 cups cooked rice",
+                "2 tbsp oil",
+                "Salt",
+            ],
+        );
+
+        assert!(!soup.is_near_duplicate_of(&fried_rice));
+    }
+}
@@ -0,0 +1,499 @@
+use serde::{Deserialize, Serialize};
+
+use super::dietary::{DietaryRestriction, DietaryViolation, find_violations};
+use super::entities::Recipe;
+use super::ingredients::{ParsedIngredient, Quantity, Unit, mentions, words};
+use super::scaling::{format_ingredient, round_for_kitchen};
+
+/// How much of an ingredient a substitute calls for.
+#[derive(Clone, Copy)]
+enum Amount {
+    /// This share of the replaced amount, in the same unit.
+    Ratio(f64),
+    /// This much per item replaced, for things counted rather than measured
+    /// (eggs, garlic cloves).
+    Each(f64, Unit),
+}
+
+struct Part {
+    amount: Amount,
+    item: &'static str,
+}
+
+struct Alternative {
+    parts: &'static [Part],
+    note: &'static str,
+}
+
+struct Entry {
+    names: &'static [&'static str],
+    /// Phrases naming a different ingredient, e.g. "peanut butter" for butter.
+    exceptions: &'static [&'static str],
+    alternatives: &'static [Alternative],
+}
+
+const fn ratio(share: f64, item: &'static str) -> Part {
+    Part {
+        amount: Amount::Ratio(share),
+        item,
+    }
+}
+
+const fn each(amount: f64, unit: Unit, item: &'static str) -> Part {
+    Part {
+        amount: Amount::Each(amount, unit),
+        item,
+    }
+}
+
+/// Well-known kitchen substitutions. Ratios keep the total volume of the
+/// original, so 1/16 stands for a tablespoon per cup.
+const SUBSTITUTIONS: &[Entry] = &[
+    Entry {
+        names: &["buttermilk"],
+        exceptions: &[],
+        alternatives: &[
+            Alternative {
+                parts: &[ratio(15.0 / 16.0, "milk"), ratio(1.0 / 16.0, "lemon juice")],
+                note: "Stir the lemon juice into the milk and let it stand for 5 minutes.",
+            },
+            Alternative {
+                parts: &[ratio(0.75, "plain yogurt"), ratio(0.25, "milk")],
+                note: "Whisk until smooth.",
+            },
+        ],
+    },
+    Entry {
+        names: &["sour cream"],
+        exceptions: &[],
+        alternatives: &[Alternative {
+            parts: &[ratio(1.0, "plain Greek yogurt")],
+            note: "Add off the heat so it does not split.",
+        }],
+    },
+    Entry {
+        names: &["heavy cream", "double cream", "whipping cream"],
+        exceptions: &[],
+        alternatives: &[
+            Alternative {
+                parts: &[ratio(0.75, "milk"), ratio(0.25, "melted butter")],
+                note: "Works in sauces and baking, but will not whip.",
+            },
+            Alternative {
+                parts: &[ratio(1.0, "coconut cream")],
+                note: "Adds a mild coconut flavour.",
+            },
+        ],
+    },
+    Entry {
+        names: &["butter"],
+        exceptions: &[
+            "peanut butter",
+            "nut butter",
+            "almond butter",
+            "apple butter",
+        ],
+        alternatives: &[
+            Alternative {
+                parts: &[ratio(0.75, "olive oil")],
+                note: "Best where the butter is melted; not for pastry or creaming with sugar.",
+            },
+            Alternative {
+                parts: &[ratio(1.0, "coconut oil")],
+                note: "Use solid for pastry and melted for batters.",
+            },
+        ],
+    },
+    Entry {
+        names: &["milk"],
+        exceptions: &[
+            "buttermilk",
+            "coconut milk",
+            "almond milk",
+            "oat milk",
+            "soy milk",
+            "rice milk",
+        ],
+        alternatives: &[
+            Alternative {
+                parts: &[ratio(1.0, "oat milk")],
+                note: "Choose an unsweetened one for savoury dishes.",
+            },
+            Alternative {
+                parts: &[ratio(0.5, "evaporated milk"), ratio(0.5, "water")],
+                note: "Richer than fresh milk.",
+            },
+        ],
+    },
+    Entry {
+        names: &["egg"],
+        exceptions: &["eggplant", "egg noodle", "egg white", "egg yolk"],
+        alternatives: &[
+            Alternative {
+                parts: &[
+                    each(1.0, Unit::Tablespoon, "ground flaxseed"),
+                    each(3.0, Unit::Tablespoon, "water"),
+                ],
+                note: "Mix and let thicken for 5 minutes. Binds well, but does not help baked goods rise.",
+            },
+            Alternative {
+                parts: &[each(0.25, Unit::Cup, "unsweetened applesauce")],
+                note: "Adds moisture and a little sweetness; best in cakes and muffins.",
+            },
+        ],
+    },
+    Entry {
+        names: &["brown sugar"],
+        exceptions: &[],
+        alternatives: &[Alternative {
+            parts: &[
+                ratio(15.0 / 16.0, "white sugar"),
+                ratio(1.0 / 16.0, "molasses"),
+            ],
+            note: "Mix the molasses into the sugar until evenly coloured.",
+        }],
+    },
+    Entry {
+        names: &["honey"],
+        exceptions: &[],
+        alternatives: &[Alternative {
+            parts: &[ratio(1.0, "maple syrup")],
+            note: "Slightly thinner, with a different flavour.",
+        }],
+    },
+    Entry {
+        names: &["lemon juice"],
+        exceptions: &[],
+        alternatives: &[
+            Alternative {
+                parts: &[ratio(1.0, "lime juice")],
+                note: "Swaps one for one.",
+            },
+            Alternative {
+                parts: &[ratio(0.5, "white wine vinegar")],
+                note: "Sharper than lemon, so use half as much.",
+            },
+        ],
+    },
+    Entry {
+        names: &["soy sauce"],
+        exceptions: &[],
+        alternatives: &[
+            Alternative {
+                parts: &[ratio(1.0, "tamari")],
+                note: "Usually gluten-free; check the label.",
+            },
+            Alternative {
+                parts: &[ratio(1.0, "coconut aminos")],
+                note: "Sweeter and less salty.",
+            },
+        ],
+    },
+    Entry {
+        names: &["cornstarch", "corn starch", "cornflour"],
+        exceptions: &[],
+        alternatives: &[Alternative {
+            parts: &[ratio(2.0, "all-purpose flour")],
+            note: "Thickens less, so use twice as much and simmer a little longer.",
+        }],
+    },
+    Entry {
+        names: &["breadcrumbs", "bread crumbs"],
+        exceptions: &[],
+        alternatives: &[
+            Alternative {
+                parts: &[ratio(1.0, "rolled oats")],
+                note: "Pulse briefly in a blender for a finer crumb.",
+            },
+            Alternative {
+                parts: &[ratio(1.0, "crushed crackers")],
+                note: "Saltier, so season less.",
+            },
+        ],
+    },
+    Entry {
+        names: &["white wine"],
+        exceptions: &["white wine vinegar"],
+        alternatives: &[Alternative {
+            parts: &[
+                ratio(15.0 / 16.0, "vegetable broth"),
+                ratio(1.0 / 16.0, "white wine vinegar"),
+            ],
+            note: "The vinegar stands in for the wine's acidity.",
+        }],
+    },
+    Entry {
+        names: &["garlic"],
+        exceptions: &["garlic powder", "garlic salt"],
+        alternatives: &[Alternative {
+            parts: &[each(0.125, Unit::Teaspoon, "garlic powder")],
+            note: "Add with the other dry seasonings.",
+        }],
+    },
+    Entry {
+        names: &["mayonnaise", "mayo"],
+        exceptions: &[],
+        alternatives: &[Alternative {
+            parts: &[ratio(1.0, "plain Greek yogurt")],
+            note: "Tangier and lighter.",
+        }],
+    },
+];
+
+/// A replacement for one ingredient line: the lines to use instead, already
+/// sized to the amount being replaced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuggestedSubstitute {
+    pub ingredients: Vec<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubstituteSource {
+    /// From the built-in substitution table.
+    Curated,
+    /// Suggested by the AI, for ingredients the table does not cover.
+    Ai,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substitute {
+    pub ingredients: Vec<String>,
+    pub note: Option<String>,
+    pub source: SubstituteSource,
+    /// Ways the substitute breaks the recipe's dietary restrictions.
+    pub violations: Vec<DietaryViolation>,
+}
+
+impl Substitute {
+    pub fn new(
+        suggestion: SuggestedSubstitute,
+        source: SubstituteSource,
+        restrictions: &[DietaryRestriction],
+    ) -> Self {
+        Self {
+            violations: find_violations(&suggestion.ingredients, restrictions),
+            ingredients: suggestion.ingredients,
+            note: suggestion.note,
+            source,
+        }
+    }
+}
+
+/// Alternatives for one ingredient line of a recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substitutions {
+    /// The recipe's ingredient line being replaced.
+    pub ingredient: String,
+    /// The recipe's dietary restrictions the substitutes were checked against.
+    pub restrictions: Vec<DietaryRestriction>,
+    pub substitutes: Vec<Substitute>,
+}
+
+fn is_named(item: &[String], entry: &Entry) -> bool {
+    entry.names.iter().any(|name| mentions(item, &words(name)))
+        && !entry
+            .exceptions
+            .iter()
+            .any(|exception| mentions(item, &words(exception)))
+}
+
+impl Recipe {
+    /// The ingredient line for `name`: one that is exactly that ingredient if
+    /// there is one, otherwise the first that mentions it.
+    pub fn find_ingredient(&self, name: &str) -> Option<&String> {
+        let name = words(name);
+        if name.is_empty() {
+            return None;
+        }
+        let items: Vec<Vec<String>> = self
+            .structured_ingredients()
+            .iter()
+            .map(|ingredient| words(&ingredient.item))
+            .collect();
+
+        let index = items
+            .iter()
+            .position(|item| item.len() == name.len() && mentions(item, &name))
+            .or_else(|| items.iter().position(|item| mentions(item, &name)))?;
+        self.ingredients.get(index)
+    }
+
+    /// Restrictions named in the recipe's tags, such as "vegan" or
+    /// "gluten-free". Saved recipes do not record what they were generated
+    /// for, so the tags are the only statement of what they must respect.
+    pub fn tagged_restrictions(&self) -> Vec<DietaryRestriction> {
+        DietaryRestriction::parse_all(&self.tags)
+    }
+}
+
+/// Substitutes from the built-in table for an ingredient line, sized to its
+/// quantity. Empty when the table has nothing for it.
+pub fn curated_substitutes(line: &str) -> Vec<SuggestedSubstitute> {
+    let original = ParsedIngredient::parse(line);
+    let item = words(&original.item);
+
+    SUBSTITUTIONS
+        .iter()
+        .filter(|entry| is_named(&item, entry))
+        .flat_map(|entry| entry.alternatives)
+        .map(|alternative| SuggestedSubstitute {
+            ingredients: alternative
+                .parts
+                .iter()
+                .map(|part| part_line(part, &original))
+                .collect(),
+            note: Some(alternative.note.to_string()),
+        })
+        .collect()
+}
+
+/// The replaced amount, with sticks of butter counted in tablespoons so they
+/// can be shared out.
+fn replaced_amount(original: &ParsedIngredient) -> Option<(Quantity, Option<Unit>)> {
+    let quantity = original.quantity?;
+    Some(match original.unit {
+        Some(Unit::Stick) => (scale(quantity, 8.0), Some(Unit::Tablespoon)),
+        unit => (quantity, unit),
+    })
+}
+
+fn scale(quantity: Quantity, factor: f64) -> Quantity {
+    match quantity {
+        Quantity::Exact { value } => Quantity::Exact {
+            value: value * factor,
+        },
+        Quantity::Range { min, max } => Quantity::Range {
+            min: min * factor,
+            max: max * factor,
+        },
+    }
+}
+
+fn part_line(part: &Part, original: &ParsedIngredient) -> String {
+    let (quantity, unit) = match (part.amount, replaced_amount(original)) {
+        (Amount::Ratio(share), Some((quantity, unit))) => (Some(scale(quantity, share)), unit),
+        // Without a readable amount the part can only be named.
+        (Amount::Ratio(_), None) => (None, None),
+        (Amount::Each(amount, unit), replaced) => {
+            let count = replaced
+                .map(|(quantity, _)| quantity)
+                .unwrap_or(Quantity::Exact { value: 1.0 });
+            (Some(scale(count, amount)), Some(unit))
+        }
+    };
+
+    let (quantity, unit) = match quantity {
+        Some(quantity) => {
+            let (quantity, unit) = smaller_measure(quantity, unit);
+            (Some(round_quantity(quantity, unit)), unit)
+        }
+        None => (None, unit),
+    };
+
+    format_ingredient(&ParsedIngredient {
+        quantity,
+        unit,
+        item: part.item.to_string(),
+        preparation: None,
+    })
+}
+
+/// Moves small amounts to the next spoon down, so a sixteenth of a cup reads
+/// as a tablespoon.
+fn smaller_measure(quantity: Quantity, unit: Option<Unit>) -> (Quantity, Option<Unit>) {
+    let step = match unit {
+        Some(Unit::Cup) if quantity.min() < 0.25 => Some((16.0, Unit::Tablespoon)),
+        Some(Unit::Tablespoon) if quantity.min() < 1.0 => Some((3.0, Unit::Teaspoon)),
+        _ => None,
+    };
+    match step {
+        Some((factor, smaller)) => smaller_measure(scale(quantity, factor), Some(smaller)),
+        None => (quantity, unit),
+    }
+}
+
+fn round_quantity(quantity: Quantity, unit: Option<Unit>) -> Quantity {
+    match quantity {
+        Quantity::Exact { value } => Quantity::Exact {
+            value: round_for_kitchen(value, unit),
+        },
+        Quantity::Range { min, max } => Quantity::Range {
+            min: round_for_kitchen(min, unit),
+            max: round_for_kitchen(max, unit),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipes::domain::Allergen;
+
+    fn lines(substitutes: &[SuggestedSubstitute]) -> Vec<Vec<&str>> {
+        substitutes
+            .iter()
+            .map(|s| s.ingredients.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn sizes_curated_substitutes_to_the_replaced_amount() {
+        let substitutes = curated_substitutes("2 cups buttermilk");
+
+        assert_eq!(
+            lines(&substitutes),
+            vec![
+                vec!["1 7/8 cups milk", "2 tbsp lemon juice"],
+                vec!["1 1/2 cups plain yogurt", "1/2 cup milk"],
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_per_item_for_counted_ingredients() {
+        let substitutes = curated_substitutes("2 large eggs, beaten");
+
+        assert_eq!(
+            lines(&substitutes),
+            vec![
+                vec!["2 tbsp ground flaxseed", "6 tbsp water"],
+                vec!["1/2 cup unsweetened applesauce"],
+            ]
+        );
+        assert_eq!(
+            lines(&curated_substitutes("1 stick butter")),
+            vec![vec!["6 tbsp olive oil"], vec!["8 tbsp coconut oil"]]
+        );
+    }
+
+    #[test]
+    fn names_parts_when_the_amount_is_unknown() {
+        assert_eq!(
+            lines(&curated_substitutes("Buttermilk, to loosen")),
+            vec![vec!["milk", "lemon juice"], vec!["plain yogurt", "milk"]]
+        );
+    }
+
+    #[test]
+    fn skips_ingredients_that_only_share_a_name() {
+        assert!(curated_substitutes("2 tbsp peanut butter").is_empty());
+        assert!(curated_substitutes("1 eggplant").is_empty());
+        assert!(curated_substitutes("1 cup quinoa").is_empty());
+    }
+
+    #[test]
+    fn flags_substitutes_breaking_restrictions() {
+        let restrictions = [
+            DietaryRestriction::Vegan,
+            DietaryRestriction::Avoid(Allergen::Gluten),
+        ];
+        let substitutes: Vec<Substitute> = curated_substitutes("1 cup heavy cream")
+            .into_iter()
+            .map(|s| Substitute::new(s, SubstituteSource::Curated, &restrictions))
+            .collect();
+
+        assert!(!substitutes[0].violations.is_empty());
+        assert!(substitutes[1].violations.is_empty());
+    }
+}
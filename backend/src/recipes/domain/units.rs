@@ -0,0 +1,572 @@
+use super::entities::{GeneratedRecipe, Recipe};
+use super::ingredients::{ParsedIngredient, Quantity, Unit, mentions, words};
+use super::scaling::{format_ingredient, round_for_kitchen};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+const ML_PER_TSP: f64 = 4.929;
+const ML_PER_TBSP: f64 = 14.787;
+const ML_PER_FL_OZ: f64 = 29.574;
+pub(super) const ML_PER_CUP: f64 = 236.588;
+const ML_PER_PINT: f64 = 473.176;
+const ML_PER_QUART: f64 = 946.353;
+const ML_PER_GALLON: f64 = 3785.41;
+const G_PER_OZ: f64 = 28.3495;
+const G_PER_LB: f64 = 453.592;
+pub(super) const G_PER_BUTTER_STICK: f64 = 113.0;
+
+/// Grams per US cup for common dry and solid ingredients, so that volume
+/// measures can become weights in metric recipes. Names are singular and
+/// match whole words, plurals included. Liquids are left out on purpose:
+/// metric cooks measure those in millilitres.
+const DENSITIES: [(&str, f64); 30] = [
+    ("whole wheat flour", 120.0),
+    ("bread flour", 130.0),
+    ("almond flour", 96.0),
+    ("flour", 125.0),
+    ("brown sugar", 220.0),
+    ("powdered sugar", 120.0),
+    ("icing sugar", 120.0),
+    ("sugar", 200.0),
+    ("butter", 227.0),
+    ("cocoa", 85.0),
+    ("cornstarch", 128.0),
+    ("rolled oat", 90.0),
+    ("oat", 90.0),
+    ("quinoa", 170.0),
+    ("lentil", 190.0),
+    ("rice", 185.0),
+    ("breadcrumb", 110.0),
+    ("chocolate chip", 170.0),
+    ("parmesan", 100.0),
+    ("cheese", 113.0),
+    ("peanut butter", 258.0),
+    ("walnut", 120.0),
+    ("pecan", 110.0),
+    ("almond", 143.0),
+    ("raisin", 145.0),
+    ("yogurt", 245.0),
+    ("salt", 273.0),
+    ("spinach", 30.0),
+    ("pea", 145.0),
+    ("corn", 165.0),
+];
+
+/// Words that make an item a liquid even when it names a dry ingredient,
+/// as in "rice vinegar" or "corn syrup".
+const LIQUIDS: [&str; 11] = [
+    "milk", "water", "stock", "broth", "juice", "vinegar", "oil", "syrup", "sauce", "wine",
+    "extract",
+];
+
+pub(super) fn grams_per_cup(item: &str) -> Option<f64> {
+    let item_words = words(item);
+    if LIQUIDS
+        .iter()
+        .any(|liquid| mentions(&item_words, &[liquid]))
+    {
+        return None;
+    }
+    // The longest matching name wins, so "peanut butter" is not read as "butter".
+    DENSITIES
+        .iter()
+        .filter(|(name, _)| mentions(&item_words, &words(name)))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, density)| *density)
+}
+
+/// Volume of one unit in millilitres, for volume units only.
+pub(super) fn millilitres_per(unit: Unit) -> Option<f64> {
+    match unit {
+        Unit::Teaspoon => Some(ML_PER_TSP),
+        Unit::Tablespoon => Some(ML_PER_TBSP),
+        Unit::FluidOunce => Some(ML_PER_FL_OZ),
+        Unit::Cup => Some(ML_PER_CUP),
+        Unit::Pint => Some(ML_PER_PINT),
+        Unit::Quart => Some(ML_PER_QUART),
+        Unit::Gallon => Some(ML_PER_GALLON),
+        Unit::Milliliter => Some(1.0),
+        Unit::Liter => Some(1000.0),
+        _ => None,
+    }
+}
+
+/// Weight of one unit in grams, for mass units only.
+pub(super) fn grams_per(unit: Unit) -> Option<f64> {
+    match unit {
+        Unit::Gram => Some(1.0),
+        Unit::Kilogram => Some(1000.0),
+        Unit::Ounce => Some(G_PER_OZ),
+        Unit::Pound => Some(G_PER_LB),
+        _ => None,
+    }
+}
+
+impl Recipe {
+    /// Returns a copy with ingredient measures and oven temperatures in the
+    /// given system. Lines that are already in that system, or that carry no
+    /// convertible unit (counts, pinches, cloves), keep their original text.
+    pub fn convert_units(&self, system: UnitSystem) -> Recipe {
+        let parsed = self.structured_ingredients();
+        let (ingredients, parsed) = convert_lines(&self.ingredients, &parsed, system);
+
+        let mut recipe = self.clone();
+        recipe.ingredients = ingredients;
+        recipe.parsed_ingredients = parsed;
+        recipe.instructions = convert_instructions(&self.instructions, system);
+        recipe
+    }
+}
+
+impl GeneratedRecipe {
+    pub fn convert_units(&self, system: UnitSystem) -> GeneratedRecipe {
+        let parsed: Vec<ParsedIngredient> = self
+            .ingredients
+            .iter()
+            .map(|l| ParsedIngredient::parse(l))
+            .collect();
+        let (ingredients, _) = convert_lines(&self.ingredients, &parsed, system);
+
+        let mut recipe = self.clone();
+        recipe.ingredients = ingredients;
+        recipe.instructions = convert_instructions(&self.instructions, system);
+        recipe
+    }
+}
+
+fn convert_lines(
+    lines: &[String],
+    parsed: &[ParsedIngredient],
+    system: UnitSystem,
+) -> (Vec<String>, Vec<ParsedIngredient>) {
+    lines
+        .iter()
+        .zip(parsed)
+        .map(|(line, ingredient)| match ingredient.convert_to(system) {
+            Some(converted) => (format_ingredient(&converted), converted),
+            None => (line.clone(), ingredient.clone()),
+        })
+        .unzip()
+}
+
+fn convert_instructions(instructions: &[String], system: UnitSystem) -> Vec<String> {
+    instructions
+        .iter()
+        .map(|step| convert_temperatures(step, system))
+        .collect()
+}
+
+impl ParsedIngredient {
+    /// Converts the measure to the given system, or `None` when there is
+    /// nothing to convert.
+    pub fn convert_to(&self, system: UnitSystem) -> Option<ParsedIngredient> {
+        let quantity = self.quantity?;
+        let unit = self.unit?;
+        let amount = quantity.max().unwrap_or(quantity.min());
+
+        let (target, factor) = match system {
+            UnitSystem::Metric => to_metric(unit, amount, &self.item)?,
+            UnitSystem::Imperial => to_imperial(unit, amount, &self.item)?,
+        };
+
+        let convert = |value: f64| round_for_kitchen(value * factor, Some(target));
+        let quantity = match quantity {
+            Quantity::Exact { value } => Quantity::Exact {
+                value: convert(value),
+            },
+            Quantity::Range { min, max } => Quantity::Range {
+                min: convert(min),
+                max: convert(max),
+            },
+        };
+
+        Some(ParsedIngredient {
+            quantity: Some(quantity),
+            unit: Some(target),
+            ..self.clone()
+        })
+    }
+}
+
+/// Picks the metric unit for an amount and the factor to multiply by.
+/// Spoon measures are used the same way in metric kitchens, so they stay.
+fn to_metric(unit: Unit, amount: f64, item: &str) -> Option<(Unit, f64)> {
+    let millilitres = match unit {
+        Unit::FluidOunce => ML_PER_FL_OZ,
+        Unit::Cup => ML_PER_CUP,
+        Unit::Pint => ML_PER_PINT,
+        Unit::Quart => ML_PER_QUART,
+        Unit::Gallon => ML_PER_GALLON,
+        Unit::Ounce => return Some(metric_mass(amount * G_PER_OZ, G_PER_OZ)),
+        Unit::Pound => return Some(metric_mass(amount * G_PER_LB, G_PER_LB)),
+        Unit::Stick if item.to_lowercase().contains("butter") => {
+            return Some((Unit::Gram, G_PER_BUTTER_STICK));
+        }
+        _ => return None,
+    };
+
+    if let Some(density) = grams_per_cup(item) {
+        let grams_per_unit = millilitres / ML_PER_CUP * density;
+        return Some(metric_mass(amount * grams_per_unit, grams_per_unit));
+    }
+
+    if amount * millilitres >= 1000.0 {
+        Some((Unit::Liter, millilitres / 1000.0))
+    } else {
+        Some((Unit::Milliliter, millilitres))
+    }
+}
+
+fn metric_mass(grams: f64, grams_per_unit: f64) -> (Unit, f64) {
+    if grams >= 1000.0 {
+        (Unit::Kilogram, grams_per_unit / 1000.0)
+    } else {
+        (Unit::Gram, grams_per_unit)
+    }
+}
+
+fn to_imperial(unit: Unit, amount: f64, item: &str) -> Option<(Unit, f64)> {
+    match unit {
+        Unit::Milliliter => Some(imperial_volume(amount, 1.0)),
+        Unit::Liter => Some(imperial_volume(amount * 1000.0, 1000.0)),
+        Unit::Gram => Some(imperial_mass(amount, 1.0, item)),
+        Unit::Kilogram => Some(imperial_mass(amount * 1000.0, 1000.0, item)),
+        _ => None,
+    }
+}
+
+fn imperial_volume(millilitres: f64, ml_per_unit: f64) -> (Unit, f64) {
+    let (target, ml_per_target) = if millilitres < ML_PER_TBSP {
+        (Unit::Teaspoon, ML_PER_TSP)
+    } else if millilitres < ML_PER_CUP / 4.0 {
+        (Unit::Tablespoon, ML_PER_TBSP)
+    } else if millilitres <= ML_PER_CUP * 4.0 {
+        (Unit::Cup, ML_PER_CUP)
+    } else {
+        (Unit::Quart, ML_PER_QUART)
+    };
+    (target, ml_per_unit / ml_per_target)
+}
+
+/// Dry goods with a known density go back to cups, which is how they are
+/// measured in imperial recipes; everything else is weighed in ounces/pounds.
+fn imperial_mass(grams: f64, grams_per_unit: f64, item: &str) -> (Unit, f64) {
+    if let Some(density) = grams_per_cup(item) {
+        let millilitres = grams / density * ML_PER_CUP;
+        let (target, factor) = imperial_volume(millilitres, ML_PER_CUP / density);
+        return (target, factor * grams_per_unit);
+    }
+
+    if grams >= G_PER_LB {
+        (Unit::Pound, grams_per_unit / G_PER_LB)
+    } else {
+        (Unit::Ounce, grams_per_unit / G_PER_OZ)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scale {
+    Celsius,
+    Fahrenheit,
+}
+
+/// Rewrites temperatures such as "350°F", "180 °C" or "400 degrees F" found
+/// in free text, including ranges such as "350-375°F" whose scale follows
+/// the second number. Anything that does not look like a temperature is
+/// copied through untouched.
+pub fn convert_temperatures(text: &str, system: UnitSystem) -> String {
+    let target = match system {
+        UnitSystem::Metric => Scale::Celsius,
+        UnitSystem::Imperial => Scale::Fahrenheit,
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        out.push_str(&rest[..start]);
+        let from_number = &rest[start..];
+
+        let number_len = number_len(from_number);
+        let number = &from_number[..number_len];
+        let tail = &from_number[number_len..];
+
+        if let (Ok(low), Some((separator, high, scale, consumed))) =
+            (number.parse::<f64>(), read_range_end(tail))
+        {
+            if scale == target {
+                out.push_str(&from_number[..number_len + consumed]);
+            } else {
+                out.push_str(&format!(
+                    "{}{}{}",
+                    degrees_in(low, scale, target),
+                    &tail[..separator],
+                    format_temperature(high, scale, target)
+                ));
+            }
+            rest = &tail[consumed..];
+            continue;
+        }
+
+        match (number.parse::<f64>(), read_scale(tail)) {
+            (Ok(value), Some((scale, consumed))) if scale != target => {
+                out.push_str(&format_temperature(value, scale, target));
+                rest = &tail[consumed..];
+            }
+            (Ok(_), Some((_, consumed))) => {
+                out.push_str(&from_number[..number_len + consumed]);
+                rest = &tail[consumed..];
+            }
+            _ => {
+                out.push_str(number);
+                rest = tail;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Length of the number `text` starts with, leaving out a trailing full stop.
+fn number_len(text: &str) -> usize {
+    let mut len = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    while text[..len].ends_with('.') {
+        len -= 1;
+    }
+    len
+}
+
+/// Reads the end of a temperature range right after its first number, such
+/// as "-375°F" or " to 200 °C". Returns how many bytes of `text` the
+/// separator spans, the second number, its scale, and how many bytes of
+/// `text` the whole end spans.
+fn read_range_end(text: &str) -> Option<(usize, f64, Scale, usize)> {
+    let trimmed = text.trim_start_matches(' ');
+    let marker = ["-", "–", "to "]
+        .iter()
+        .find(|m| starts_with_ignore_case(trimmed, m))?;
+    let number = trimmed[marker.len()..].trim_start_matches(' ');
+    let separator = text.len() - number.len();
+
+    let len = number_len(number);
+    let value = number[..len].parse::<f64>().ok()?;
+    let (scale, consumed) = read_scale(&number[len..])?;
+    Some((separator, value, scale, separator + len + consumed))
+}
+
+/// Reads a temperature scale right after a number: a degree sign or the
+/// word "degrees", followed by the scale letter or name. Returns the scale and
+/// how many bytes of `text` it spans.
+fn read_scale(text: &str) -> Option<(Scale, usize)> {
+    let trimmed = text.trim_start_matches(' ');
+    let mut consumed = text.len() - trimmed.len();
+
+    let marker = ["°", "º", "degrees", "degree"]
+        .iter()
+        .find(|m| starts_with_ignore_case(trimmed, m))?;
+    consumed += marker.len();
+
+    let after_marker = &text[consumed..];
+    let name = after_marker.trim_start_matches(' ');
+    consumed += after_marker.len() - name.len();
+
+    let (scale, len) = [
+        ("fahrenheit", Scale::Fahrenheit),
+        ("celsius", Scale::Celsius),
+        ("centigrade", Scale::Celsius),
+        ("f", Scale::Fahrenheit),
+        ("c", Scale::Celsius),
+    ]
+    .iter()
+    .find(|(word, _)| {
+        starts_with_ignore_case(name, word)
+            && !name[word.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric())
+    })
+    .map(|(word, scale)| (*scale, word.len()))?;
+
+    Some((scale, consumed + len))
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+}
+
+/// Oven temperatures are rounded to the steps found on oven dials: 5°C, and
+/// 25°F once we are in baking territory.
+fn format_temperature(value: f64, from: Scale, to: Scale) -> String {
+    format!(
+        "{}°{}",
+        degrees_in(value, from, to),
+        if to == Scale::Celsius { "C" } else { "F" }
+    )
+}
+
+/// `value` degrees on the `from` scale in degrees on the `to` scale,
+/// rounded as for [`format_temperature`].
+fn degrees_in(value: f64, from: Scale, to: Scale) -> f64 {
+    match (from, to) {
+        (Scale::Fahrenheit, Scale::Celsius) => {
+            let celsius = (value - 32.0) * 5.0 / 9.0;
+            if celsius >= 100.0 {
+                (celsius / 5.0).round() * 5.0
+            } else {
+                celsius.round()
+            }
+        }
+        (Scale::Celsius, Scale::Fahrenheit) => {
+            let fahrenheit = value * 9.0 / 5.0 + 32.0;
+            if fahrenheit >= 250.0 {
+                (fahrenheit / 25.0).round() * 25.0
+            } else {
+                (fahrenheit / 5.0).round() * 5.0
+            }
+        }
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(line: &str, system: UnitSystem) -> String {
+        let parsed = ParsedIngredient::parse(line);
+        match parsed.convert_to(system) {
+            Some(converted) => format_ingredient(&converted),
+            None => line.to_string(),
+        }
+    }
+
+    #[test]
+    fn converts_dry_cups_to_grams_using_density() {
+        assert_eq!(convert("2 cups flour", UnitSystem::Metric), "250 g flour");
+        assert_eq!(
+            convert("1 cup brown sugar", UnitSystem::Metric),
+            "220 g brown sugar"
+        );
+        assert_eq!(
+            convert("1 stick butter, softened", UnitSystem::Metric),
+            "115 g butter, softened"
+        );
+    }
+
+    #[test]
+    fn converts_liquids_and_weights_to_metric() {
+        assert_eq!(convert("1 cup milk", UnitSystem::Metric), "235 ml milk");
+        assert_eq!(convert("6 cups water", UnitSystem::Metric), "1.4 l water");
+        assert_eq!(
+            convert("1 lb ground beef", UnitSystem::Metric),
+            "455 g ground beef"
+        );
+        assert_eq!(
+            convert("1 tbsp olive oil", UnitSystem::Metric),
+            "1 tbsp olive oil"
+        );
+    }
+
+    #[test]
+    fn keeps_liquids_named_after_dry_ingredients_in_millilitres() {
+        assert_eq!(
+            convert("1 cup buttermilk", UnitSystem::Metric),
+            "235 ml buttermilk"
+        );
+        assert_eq!(
+            convert("2 tbsp rice vinegar", UnitSystem::Metric),
+            "2 tbsp rice vinegar"
+        );
+        assert_eq!(
+            convert("1/4 cup corn syrup", UnitSystem::Metric),
+            "59 ml corn syrup"
+        );
+        assert_eq!(
+            convert("1 cup coconut milk", UnitSystem::Metric),
+            "235 ml coconut milk"
+        );
+        assert_eq!(
+            convert("1 cup walnuts", UnitSystem::Metric),
+            "120 g walnuts"
+        );
+    }
+
+    #[test]
+    fn converts_metric_to_imperial() {
+        assert_eq!(
+            convert("500 g chicken", UnitSystem::Imperial),
+            "1 1/8 lb chicken"
+        );
+        assert_eq!(convert("250 ml milk", UnitSystem::Imperial), "1 cup milk");
+        assert_eq!(
+            convert("5 ml vanilla", UnitSystem::Imperial),
+            "1 tsp vanilla"
+        );
+        assert_eq!(convert("250 g flour", UnitSystem::Imperial), "2 cups flour");
+    }
+
+    #[test]
+    fn leaves_counts_and_matching_units_alone() {
+        assert!(
+            ParsedIngredient::parse("2 eggs")
+                .convert_to(UnitSystem::Metric)
+                .is_none()
+        );
+        assert!(
+            ParsedIngredient::parse("200 g rice")
+                .convert_to(UnitSystem::Metric)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn converts_temperatures_in_text() {
+        assert_eq!(
+            convert_temperatures(
+                "Preheat oven to 350°F and bake 20 minutes.",
+                UnitSystem::Metric
+            ),
+            "Preheat oven to 175°C and bake 20 minutes."
+        );
+        assert_eq!(
+            convert_temperatures("Bake at 200 degrees C until golden", UnitSystem::Imperial),
+            "Bake at 400°F until golden"
+        );
+        assert_eq!(
+            convert_temperatures("Heat to 180 °C", UnitSystem::Metric),
+            "Heat to 180 °C"
+        );
+        assert_eq!(
+            convert_temperatures("Add 2 cups and cook 3 minutes", UnitSystem::Metric),
+            "Add 2 cups and cook 3 minutes"
+        );
+    }
+
+    #[test]
+    fn converts_both_ends_of_temperature_ranges() {
+        assert_eq!(
+            convert_temperatures("Bake at 350-375°F", UnitSystem::Metric),
+            "Bake at 175-190°C"
+        );
+        assert_eq!(
+            convert_temperatures("Roast at 180 to 200°C", UnitSystem::Imperial),
+            "Roast at 350 to 400°F"
+        );
+        assert_eq!(
+            convert_temperatures("Roast at 180 to 200°C", UnitSystem::Metric),
+            "Roast at 180 to 200°C"
+        );
+        assert_eq!(
+            convert_temperatures("Bake 20-25 minutes at 350°F", UnitSystem::Metric),
+            "Bake 20-25 minutes at 175°C"
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use std::ops::AddAssign;
+use std::sync::Mutex;
+
+/// Tokens a provider billed for, as reported in its responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> i64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+    }
+}
+
+/// The last reply a model gave that could be read, and what produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelOutput {
+    /// Provider and model, e.g. `openai/gpt-4o-mini`.
+    pub model: String,
+    pub prompt_version: String,
+    /// The reply's text as the model wrote it.
+    pub raw: String,
+}
+
+/// Adds up the model calls made while serving one request, repairs and
+/// failed attempts included, so they can be accounted for afterwards.
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    state: Mutex<(u32, TokenUsage)>,
+    output: Mutex<Option<ModelOutput>>,
+}
+
+impl UsageMeter {
+    /// Counts one call to the model and the tokens it used.
+    pub fn record(&self, usage: TokenUsage) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.0 += 1;
+        state.1 += usage;
+    }
+
+    /// Whether the model was called at all; cached answers cost nothing.
+    pub fn called_model(&self) -> bool {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).0 > 0
+    }
+
+    pub fn usage(&self) -> TokenUsage {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).1
+    }
+
+    /// Keeps `output`, replacing any earlier reply.
+    pub fn record_output(&self, output: ModelOutput) {
+        *self.output.lock().unwrap_or_else(|e| e.into_inner()) = Some(output);
+    }
+
+    /// The last reply recorded, or `None` if the model was never read from.
+    pub fn output(&self) -> Option<ModelOutput> {
+        self.output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// Who a generation is made for: a signed-in user, or an anonymous visitor
+/// known only by their IP address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Requester {
+    User(String),
+    Anonymous(String),
+}
+
+impl Requester {
+    /// The key usage is recorded under, e.g. `user:user_123` or `ip:10.0.0.1`.
+    pub fn subject(&self) -> String {
+        match self {
+            Requester::User(user_id) => format!("user:{}", user_id),
+            Requester::Anonymous(ip) => format!("ip:{}", ip),
+        }
+    }
+}
+
+/// Generations made in a period and the tokens they used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsageTotals {
+    pub generations: i64,
+    pub tokens: TokenUsage,
+}
+
+/// How many generations may be made per UTC day and per UTC month. `None`
+/// is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaLimits {
+    pub daily: Option<i64>,
+    pub monthly: Option<i64>,
+}
+
+/// Quotas for signed-in users and, usually stricter, for anonymous visitors.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuotaPolicy {
+    pub user: QuotaLimits,
+    pub anonymous: QuotaLimits,
+}
+
+impl QuotaPolicy {
+    pub fn limits_for(&self, requester: &Requester) -> QuotaLimits {
+        match requester {
+            Requester::User(_) => self.user,
+            Requester::Anonymous(_) => self.anonymous,
+        }
+    }
+}
+
+/// Provider prices in US dollars per million tokens, for cost estimates.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TokenPrices {
+    pub prompt_per_million: f64,
+    pub completion_per_million: f64,
+}
+
+impl TokenPrices {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt_per_million
+            + usage.completion_tokens as f64 * self.completion_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// A requester's consumption in the current quota periods, against their
+/// limits.
+#[derive(Debug, Clone, Copy)]
+pub struct UsageSummary {
+    pub today: UsageTotals,
+    pub this_month: UsageTotals,
+    pub limits: QuotaLimits,
+    pub prices: TokenPrices,
+}
+
+impl UsageSummary {
+    pub fn remaining_today(&self) -> Option<i64> {
+        self.limits
+            .daily
+            .map(|limit| (limit - self.today.generations).max(0))
+    }
+
+    pub fn remaining_this_month(&self) -> Option<i64> {
+        self.limits
+            .monthly
+            .map(|limit| (limit - self.this_month.generations).max(0))
+    }
+
+    /// Whether either quota is used up, so no further generation is allowed.
+    pub fn exhausted(&self) -> bool {
+        self.remaining_today() == Some(0) || self.remaining_this_month() == Some(0)
+    }
+
+    /// Counts one more generation that used `tokens`.
+    pub fn add_generation(&mut self, tokens: TokenUsage) {
+        for totals in [&mut self.today, &mut self.this_month] {
+            totals.generations += 1;
+            totals.tokens += tokens;
+        }
+    }
+}
+
+/// Midnight UTC at the start of `now`'s day, when the daily quota resets.
+pub fn day_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0)
+        .unwrap()
+}
+
+/// Midnight UTC on the first of `now`'s month, when the monthly quota resets.
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(today: i64, this_month: i64) -> UsageSummary {
+        UsageSummary {
+            today: UsageTotals {
+                generations: today,
+                ..UsageTotals::default()
+            },
+            this_month: UsageTotals {
+                generations: this_month,
+                ..UsageTotals::default()
+            },
+            limits: QuotaLimits {
+                daily: Some(5),
+                monthly: Some(20),
+            },
+            prices: TokenPrices::default(),
+        }
+    }
+
+    #[test]
+    fn meters_add_up_every_call() {
+        let meter = UsageMeter::default();
+        assert!(!meter.called_model());
+
+        meter.record(TokenUsage {
+            prompt_tokens: 100,
+            completion_tokens: 40,
+        });
+        meter.record(TokenUsage {
+            prompt_tokens: 180,
+            completion_tokens: 60,
+        });
+
+        assert!(meter.called_model());
+        assert_eq!(meter.usage().prompt_tokens, 280);
+        assert_eq!(meter.usage().total_tokens(), 380);
+    }
+
+    #[test]
+    fn meters_keep_the_last_output() {
+        let meter = UsageMeter::default();
+        assert_eq!(meter.output(), None);
+
+        for raw in ["{\"title\": \"First\"}", "{\"title\": \"Second\"}"] {
+            meter.record_output(ModelOutput {
+                model: "openai/gpt-4o-mini".to_string(),
+                prompt_version: "1".to_string(),
+                raw: raw.to_string(),
+            });
+        }
+
+        assert_eq!(meter.output().unwrap().raw, "{\"title\": \"Second\"}");
+    }
+
+    #[test]
+    fn either_quota_can_run_out() {
+        assert_eq!(summary(3, 10).remaining_today(), Some(2));
+        assert!(!summary(3, 10).exhausted());
+        assert!(summary(5, 10).exhausted());
+        assert!(summary(1, 20).exhausted());
+        assert_eq!(summary(7, 25).remaining_this_month(), Some(0));
+
+        let mut unlimited = summary(100, 1000);
+        unlimited.limits = QuotaLimits::default();
+        assert!(!unlimited.exhausted());
+        assert_eq!(unlimited.remaining_today(), None);
+    }
+
+    #[test]
+    fn added_generations_count_against_both_quotas() {
+        let mut summary = summary(4, 10);
+
+        summary.add_generation(TokenUsage {
+            prompt_tokens: 10,
+            completion_tokens: 5,
+        });
+
+        assert_eq!(summary.remaining_today(), Some(0));
+        assert_eq!(summary.remaining_this_month(), Some(9));
+        assert_eq!(summary.this_month.tokens.total_tokens(), 15);
+    }
+
+    #[test]
+    fn periods_start_at_midnight_utc() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 15, 9, 26).unwrap();
+
+        assert_eq!(
+            day_start(now),
+            Utc.with_ymd_and_hms(2026, 3, 14, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            month_start(now),
+            Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn costs_are_priced_per_million_tokens() {
+        let prices = TokenPrices {
+            prompt_per_million: 0.15,
+            completion_per_million: 0.6,
+        };
+        let usage = TokenUsage {
+            prompt_tokens: 2_000_000,
+            completion_tokens: 500_000,
+        };
+
+        assert!((prices.cost(&usage) - 0.6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,263 @@
+use super::entities::GeneratedRecipe;
+use super::ingredients::{ParsedIngredient, mentions, words};
+
+pub const MAX_SERVINGS: i32 = 100;
+/// Longest believable prep or cook time: a day.
+pub const MAX_MINUTES: i32 = 24 * 60;
+
+/// Ingredients a recipe may use without them being provided, matching the
+/// staples the generation prompt allows.
+const STAPLES: &[&str] = &[
+    "salt",
+    "pepper",
+    "peppercorn",
+    "oil",
+    "butter",
+    "water",
+    "sugar",
+    "herb",
+    "spice",
+    "seasoning",
+    "basil",
+    "oregano",
+    "thyme",
+    "rosemary",
+    "parsley",
+    "cilantro",
+    "coriander",
+    "dill",
+    "mint",
+    "sage",
+    "tarragon",
+    "chives",
+    "bay leaf",
+    "cumin",
+    "paprika",
+    "chili flakes",
+    "chili powder",
+    "cayenne",
+    "cinnamon",
+    "nutmeg",
+    "turmeric",
+    "curry powder",
+    "garlic powder",
+    "onion powder",
+    "red pepper flakes",
+    "vanilla",
+    "cooking spray",
+];
+
+/// Something that makes a generated recipe unusable even though it parsed.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RecipeViolation {
+    #[error("the recipe has no title")]
+    MissingTitle,
+    #[error("the recipe has no ingredients")]
+    NoIngredients,
+    #[error("the recipe has no instructions")]
+    NoInstructions,
+    #[error("{field} entry {} is blank", .index + 1)]
+    BlankEntry { field: &'static str, index: usize },
+    #[error("servings must be between 1 and {max}, not {0}", max = MAX_SERVINGS)]
+    InvalidServings(i32),
+    #[error("{field} must be between 0 and {max} minutes, not {minutes}", max = MAX_MINUTES)]
+    InvalidTime { field: &'static str, minutes: i32 },
+    #[error("\"{0}\" is neither a provided ingredient nor a basic staple")]
+    UnlistedIngredient(String),
+    #[error("the recipe takes {minutes} minutes, more than the {max} allowed")]
+    TooLong { minutes: i32, max: i32 },
+    #[error("the recipe serves {servings}, not the {expected} asked for")]
+    WrongServings { servings: i32, expected: i32 },
+    #[error("\"{0}\" uses an excluded ingredient")]
+    ExcludedIngredient(String),
+}
+
+impl RecipeViolation {
+    /// The recipe field the violation is about.
+    pub fn field(&self) -> &'static str {
+        match self {
+            RecipeViolation::MissingTitle => "title",
+            RecipeViolation::NoIngredients
+            | RecipeViolation::UnlistedIngredient(_)
+            | RecipeViolation::ExcludedIngredient(_) => "ingredients",
+            RecipeViolation::NoInstructions => "instructions",
+            RecipeViolation::BlankEntry { field, .. }
+            | RecipeViolation::InvalidTime { field, .. } => field,
+            RecipeViolation::InvalidServings(_) | RecipeViolation::WrongServings { .. } => {
+                "servings"
+            }
+            RecipeViolation::TooLong { .. } => "total_time",
+        }
+    }
+}
+
+fn blank_entries(field: &'static str, lines: &[String]) -> Vec<RecipeViolation> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim().is_empty())
+        .map(|(index, _)| RecipeViolation::BlankEntry { field, index })
+        .collect()
+}
+
+/// Whether an ingredient line names one of the provided ingredients or a
+/// staple. Either name may be the more specific one: "chicken" covers
+/// "chicken thighs", and "chicken breasts" covers "chicken".
+fn is_allowed(line: &str, provided: &[Vec<String>]) -> bool {
+    let item = words(&ParsedIngredient::parse(line).item);
+    provided
+        .iter()
+        .any(|name| mentions(&item, name) || mentions(name, &item))
+        || STAPLES.iter().any(|staple| mentions(&item, &words(staple)))
+}
+
+impl GeneratedRecipe {
+    /// Everything wrong with the recipe, given the ingredients the user
+    /// provided. Empty when the recipe can be served as is.
+    pub fn validate(&self, provided_ingredients: &[String]) -> Vec<RecipeViolation> {
+        let mut violations = Vec::new();
+
+        if self.title.trim().is_empty() {
+            violations.push(RecipeViolation::MissingTitle);
+        }
+        if self.ingredients.iter().all(|line| line.trim().is_empty()) {
+            violations.push(RecipeViolation::NoIngredients);
+        } else {
+            violations.extend(blank_entries("ingredients", &self.ingredients));
+        }
+        if self.instructions.iter().all(|line| line.trim().is_empty()) {
+            violations.push(RecipeViolation::NoInstructions);
+        } else {
+            violations.extend(blank_entries("instructions", &self.instructions));
+        }
+
+        if let Some(servings) = self.servings
+            && !(1..=MAX_SERVINGS).contains(&servings)
+        {
+            violations.push(RecipeViolation::InvalidServings(servings));
+        }
+        for (field, minutes) in [
+            ("prep_time_minutes", self.prep_time_minutes),
+            ("cook_time_minutes", self.cook_time_minutes),
+        ] {
+            if let Some(minutes) = minutes
+                && !(0..=MAX_MINUTES).contains(&minutes)
+            {
+                violations.push(RecipeViolation::InvalidTime { field, minutes });
+            }
+        }
+
+        let provided: Vec<Vec<String>> = provided_ingredients
+            .iter()
+            .map(|line| words(&ParsedIngredient::parse(line).item))
+            .filter(|name| !name.is_empty())
+            .collect();
+        violations.extend(
+            self.ingredients
+                .iter()
+                .filter(|line| !line.trim().is_empty() && !is_allowed(line, &provided))
+                .map(|line| RecipeViolation::UnlistedIngredient(line.clone())),
+        );
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn recipe(ingredients: &[&str]) -> GeneratedRecipe {
+        GeneratedRecipe {
+            title: "Chicken and Rice".to_string(),
+            ingredients: lines(ingredients),
+            instructions: lines(&["Cook the rice", "Fry the chicken"]),
+            prep_time_minutes: Some(10),
+            cook_time_minutes: Some(25),
+            servings: Some(4),
+        }
+    }
+
+    #[test]
+    fn accepts_provided_ingredients_and_staples() {
+        let recipe = recipe(&[
+            "2 chicken thighs, diced",
+            "1 1/2 cups cooked jasmine rice",
+            "2 tbsp olive oil",
+            "Salt and pepper to taste",
+            "1 tsp dried oregano",
+            "Tomatoes (optional)",
+        ]);
+
+        assert_eq!(
+            recipe.validate(&lines(&["chicken", "rice", "2 large tomatoes"])),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn flags_ingredients_that_were_not_provided() {
+        let recipe = recipe(&["2 chicken breasts", "1 cup rice", "200 g chorizo"]);
+
+        assert_eq!(
+            recipe.validate(&lines(&["chicken breast", "rice"])),
+            vec![RecipeViolation::UnlistedIngredient(
+                "200 g chorizo".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn flags_missing_parts_and_impossible_numbers() {
+        let recipe = GeneratedRecipe {
+            title: " ".to_string(),
+            ingredients: Vec::new(),
+            instructions: lines(&["Mix", ""]),
+            prep_time_minutes: Some(-5),
+            cook_time_minutes: Some(MAX_MINUTES + 1),
+            servings: Some(0),
+        };
+
+        let violations = recipe.validate(&lines(&["rice"]));
+
+        assert_eq!(
+            violations,
+            vec![
+                RecipeViolation::MissingTitle,
+                RecipeViolation::NoIngredients,
+                RecipeViolation::BlankEntry {
+                    field: "instructions",
+                    index: 1
+                },
+                RecipeViolation::InvalidServings(0),
+                RecipeViolation::InvalidTime {
+                    field: "prep_time_minutes",
+                    minutes: -5
+                },
+                RecipeViolation::InvalidTime {
+                    field: "cook_time_minutes",
+                    minutes: MAX_MINUTES + 1
+                },
+            ]
+        );
+        assert_eq!(violations[2].to_string(), "instructions entry 2 is blank");
+        assert_eq!(
+            violations[3].to_string(),
+            "servings must be between 1 and 100, not 0"
+        );
+    }
+
+    #[test]
+    fn unknown_times_and_servings_are_allowed() {
+        let mut recipe = recipe(&["1 cup rice"]);
+        recipe.prep_time_minutes = None;
+        recipe.cook_time_minutes = None;
+        recipe.servings = None;
+
+        assert_eq!(recipe.validate(&lines(&["rice"])), vec![]);
+    }
+}
@@ -1,7 +1,26 @@
+mod anthropic_client;
+mod circuit_breaker;
+mod configured_llm;
+mod generation_cache;
+mod llm_client;
+mod llm_transport;
+mod local_llm;
+mod memory_generation_cache;
+mod ollama_client;
 mod openai_client;
+mod pg_collection_repository;
+mod pg_generation_cache;
+mod pg_generation_repository;
 mod pg_recipe_repository;
 mod pg_recipe_share_repository;
+mod pg_usage_repository;
+mod prompt;
+mod prompt_templates;
 
-pub use openai_client::OpenAiClient;
+pub use configured_llm::ConfiguredLlm;
+pub use generation_cache::GenerationCacheStore;
+pub use pg_collection_repository::PgCollectionRepository;
+pub use pg_generation_repository::PgGenerationRepository;
 pub use pg_recipe_repository::PgRecipeRepository;
 pub use pg_recipe_share_repository::PgRecipeShareRepository;
+pub use pg_usage_repository::PgUsageRepository;
@@ -0,0 +1,310 @@
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::llm_client::{ChatMessage, ChatModel};
+use super::llm_transport::{LlmTransport, StreamUpdate, sse_data};
+use crate::recipes::domain::{LlmError, TokenUsage, UsageMeter};
+use crate::shared::config::LlmConfig;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// The Messages API has no JSON mode, so the recipe is requested as the
+/// input of a tool the model is made to call.
+const RECIPE_TOOL: &str = "record_recipe";
+
+/// Client for Anthropic's Messages API.
+pub struct AnthropicClient {
+    transport: LlmTransport,
+    url: String,
+    api_key: String,
+    model: String,
+    temperature: Option<f32>,
+}
+
+impl AnthropicClient {
+    pub fn new(config: &LlmConfig) -> Self {
+        Self {
+            transport: LlmTransport::new(config),
+            url: format!("{}/messages", config.base_url),
+            api_key: config.api_key.clone().unwrap_or_default(),
+            model: config.model.clone(),
+            temperature: config.temperature,
+        }
+    }
+
+    fn request(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        stream: bool,
+    ) -> RequestBuilder {
+        let request = MessagesRequest {
+            model: &self.model,
+            max_tokens,
+            temperature: self.temperature,
+            messages,
+            stream,
+            tools: json!([{
+                "name": RECIPE_TOOL,
+                "description": "Record the generated recipe.",
+                "input_schema": schema,
+            }]),
+            tool_choice: json!({ "type": "tool", "name": RECIPE_TOOL }),
+        };
+
+        self.transport
+            .post(&self.url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&request)
+    }
+}
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    messages: &'a [ChatMessage],
+    stream: bool,
+    tools: Value,
+    tool_choice: Value,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Usage,
+}
+
+#[derive(Deserialize, Default)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: i64,
+    #[serde(default)]
+    output_tokens: i64,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        TokenUsage {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    input: Option<Value>,
+}
+
+impl MessagesResponse {
+    /// The reply's text: text blocks as written and tool calls as their JSON
+    /// input.
+    fn text(&self) -> String {
+        self.content
+            .iter()
+            .map(|block| match (block.kind.as_str(), &block.input) {
+                ("text", _) => block.text.clone(),
+                ("tool_use", Some(input)) => input.to_string(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct StreamEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    delta: Option<StreamDelta>,
+    #[serde(default)]
+    error: Option<StreamError>,
+    /// Sent with `message_start`, carrying the prompt's token count.
+    #[serde(default)]
+    message: Option<StreamMessage>,
+    /// Sent with `message_delta`, carrying the reply's token count so far.
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: Usage,
+}
+
+#[derive(Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    text: Option<String>,
+    /// A piece of a tool call's JSON input.
+    #[serde(default)]
+    partial_json: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamError {
+    message: String,
+}
+
+/// The text and token counts added by one line of a streamed reply. Errors
+/// the API reports mid-stream are returned as `LlmError::ApiError`.
+fn stream_update(line: &str) -> Result<StreamUpdate, LlmError> {
+    let Some(data) = sse_data(line) else {
+        return Ok(StreamUpdate::default());
+    };
+
+    let event: StreamEvent =
+        serde_json::from_str(data).map_err(|e| LlmError::ParseError(e.to_string()))?;
+    match event.kind.as_str() {
+        "content_block_delta" => Ok(StreamUpdate {
+            text: event
+                .delta
+                .and_then(|delta| delta.text.or(delta.partial_json)),
+            usage: None,
+        }),
+        // The start's output count is a placeholder; the final delta's is
+        // the reply's total.
+        "message_start" => Ok(StreamUpdate {
+            text: None,
+            usage: event.message.map(|message| TokenUsage {
+                prompt_tokens: message.usage.input_tokens,
+                completion_tokens: 0,
+            }),
+        }),
+        "message_delta" => Ok(StreamUpdate {
+            text: None,
+            usage: event.usage.map(|usage| TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: usage.output_tokens,
+            }),
+        }),
+        "error" => Err(LlmError::ApiError(
+            event
+                .error
+                .map(|error| error.message)
+                .unwrap_or_else(|| "Stream error".to_string()),
+        )),
+        _ => Ok(StreamUpdate::default()),
+    }
+}
+
+impl ChatModel for AnthropicClient {
+    async fn complete(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+    ) -> Result<String, LlmError> {
+        let mut response: MessagesResponse = self
+            .transport
+            .send_json(self.request(messages, schema, max_tokens, false))
+            .await?;
+        usage.record(std::mem::take(&mut response.usage).into());
+
+        let text = response.text();
+        if text.is_empty() {
+            return Err(LlmError::ParseError(
+                "No text content in response".to_string(),
+            ));
+        }
+
+        Ok(text)
+    }
+
+    async fn complete_streaming<F: FnMut(&str) + Send>(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+        mut on_text: F,
+    ) -> Result<String, LlmError> {
+        let mut text = String::new();
+        let mut tokens = TokenUsage::default();
+        self.transport
+            .send_lines(self.request(messages, schema, max_tokens, true), |line| {
+                let update = stream_update(line)?;
+                if let Some(delta) = update.text {
+                    on_text(&delta);
+                    text.push_str(&delta);
+                }
+                tokens += update.usage.unwrap_or_default();
+                Ok(())
+            })
+            .await?;
+        usage.record(tokens);
+
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_text_and_tool_input_blocks() {
+        let response: MessagesResponse = serde_json::from_str(
+            r#"{"content": [
+                {"type": "text", "text": "Here it is: "},
+                {"type": "tool_use", "id": "x", "name": "record_recipe", "input": {"title": "Soup"}},
+                {"type": "thinking", "thinking": "..."}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(response.text(), r#"Here it is: {"title":"Soup"}"#);
+    }
+
+    #[test]
+    fn reads_text_deltas_and_errors_from_stream_events() {
+        let text = r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Soup"}}"#;
+        let json = r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"ti"}}"#;
+
+        assert_eq!(stream_update(text).unwrap().text.as_deref(), Some("Soup"));
+        assert_eq!(stream_update(json).unwrap().text.as_deref(), Some("{\"ti"));
+        assert_eq!(
+            stream_update("event: content_block_delta").unwrap(),
+            StreamUpdate::default()
+        );
+        assert_eq!(
+            stream_update(r#"data: {"type":"message_stop"}"#).unwrap(),
+            StreamUpdate::default()
+        );
+        assert!(matches!(
+            stream_update(r#"data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#),
+            Err(LlmError::ApiError(message)) if message == "Overloaded"
+        ));
+    }
+
+    #[test]
+    fn reads_token_counts_from_stream_events() {
+        let start = r#"data: {"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":250,"output_tokens":1}}}"#;
+        let delta = r#"data: {"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":410}}"#;
+
+        let mut tokens = TokenUsage::default();
+        tokens += stream_update(start).unwrap().usage.unwrap();
+        tokens += stream_update(delta).unwrap().usage.unwrap();
+
+        assert_eq!(
+            tokens,
+            TokenUsage {
+                prompt_tokens: 250,
+                completion_tokens: 410,
+            }
+        );
+    }
+}
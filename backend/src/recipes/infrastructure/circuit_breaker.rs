@@ -0,0 +1,104 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Stops calls to an upstream that keeps failing. After `threshold` failed
+/// calls in a row the breaker opens and turns calls away for `cooldown`.
+/// Calls are then let through again; one success closes the breaker, while
+/// another failure opens it for a further `cooldown`.
+pub(super) struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub(super) fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether a call may go upstream at `now`.
+    pub(super) fn allows(&self, now: Instant) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.open_until.is_none_or(|until| now >= until)
+    }
+
+    pub(super) fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = BreakerState::default();
+    }
+
+    pub(super) fn record_failure(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.failures = state.failures.saturating_add(1);
+        if state.failures >= self.threshold {
+            if state.open_until.is_none() {
+                tracing::warn!(
+                    failures = state.failures,
+                    cooldown_secs = self.cooldown.as_secs(),
+                    "Opening LLM circuit breaker"
+                );
+            }
+            state.open_until = Some(now + self.cooldown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn opens_after_threshold_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        let now = Instant::now();
+
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+        assert!(breaker.allows(now));
+
+        breaker.record_failure(now);
+        assert!(!breaker.allows(now));
+        assert!(!breaker.allows(now + COOLDOWN - Duration::from_secs(1)));
+        assert!(breaker.allows(now + COOLDOWN));
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let breaker = CircuitBreaker::new(2, COOLDOWN);
+        let now = Instant::now();
+
+        breaker.record_failure(now);
+        breaker.record_success();
+        breaker.record_failure(now);
+
+        assert!(breaker.allows(now));
+    }
+
+    #[test]
+    fn failure_after_cooldown_reopens_immediately() {
+        let breaker = CircuitBreaker::new(2, COOLDOWN);
+        let now = Instant::now();
+        breaker.record_failure(now);
+        breaker.record_failure(now);
+
+        let later = now + COOLDOWN;
+        assert!(breaker.allows(later));
+        breaker.record_failure(later);
+        assert!(!breaker.allows(later));
+
+        breaker.record_success();
+        assert!(breaker.allows(later));
+    }
+}
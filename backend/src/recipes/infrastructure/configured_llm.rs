@@ -0,0 +1,179 @@
+use super::llm_client::LlmClient;
+use super::local_llm::LocalLlm;
+use crate::recipes::domain::{
+    GeneratedRecipe, LlmError, LlmService, PromptChoice, RecipeConstraints, SuggestedSubstitute,
+    UsageMeter,
+};
+use crate::shared::config::LlmConfig;
+
+/// The LLM service chosen in the configuration: a provider's API, or the
+/// offline stand-in.
+enum Backend {
+    Client(Box<LlmClient>),
+    Local(LocalLlm),
+}
+
+pub struct ConfiguredLlm {
+    backend: Backend,
+}
+
+impl ConfiguredLlm {
+    pub fn from_config(config: &LlmConfig) -> Self {
+        let backend = match LlmClient::from_config(config) {
+            Some(client) => Backend::Client(Box::new(client)),
+            None => Backend::Local(LocalLlm::new(&config.local)),
+        };
+        Self { backend }
+    }
+}
+
+impl LlmService for ConfiguredLlm {
+    fn version(&self) -> String {
+        match &self.backend {
+            Backend::Client(llm) => llm.version(),
+            Backend::Local(llm) => llm.version(),
+        }
+    }
+
+    fn choose_prompt(&self) -> PromptChoice {
+        match &self.backend {
+            Backend::Client(llm) => llm.choose_prompt(),
+            Backend::Local(llm) => llm.choose_prompt(),
+        }
+    }
+
+    async fn generate_recipe(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        match &self.backend {
+            Backend::Client(llm) => {
+                llm.generate_recipe(
+                    ingredients,
+                    dietary_restrictions,
+                    constraints,
+                    prompt,
+                    usage,
+                )
+                .await
+            }
+            Backend::Local(llm) => {
+                llm.generate_recipe(
+                    ingredients,
+                    dietary_restrictions,
+                    constraints,
+                    prompt,
+                    usage,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn refine_recipe(
+        &self,
+        recipe: GeneratedRecipe,
+        instruction: String,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        match &self.backend {
+            Backend::Client(llm) => llm.refine_recipe(recipe, instruction, prompt, usage).await,
+            Backend::Local(llm) => llm.refine_recipe(recipe, instruction, prompt, usage).await,
+        }
+    }
+
+    async fn suggest_substitutes(
+        &self,
+        recipe: GeneratedRecipe,
+        ingredient: String,
+        dietary_restrictions: Option<Vec<String>>,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<Vec<SuggestedSubstitute>, LlmError> {
+        match &self.backend {
+            Backend::Client(llm) => {
+                llm.suggest_substitutes(recipe, ingredient, dietary_restrictions, prompt, usage)
+                    .await
+            }
+            Backend::Local(llm) => {
+                llm.suggest_substitutes(recipe, ingredient, dietary_restrictions, prompt, usage)
+                    .await
+            }
+        }
+    }
+
+    async fn stream_recipe<F: FnMut(&str) + Send>(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+        on_text: F,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        match &self.backend {
+            Backend::Client(llm) => {
+                llm.stream_recipe(
+                    ingredients,
+                    dietary_restrictions,
+                    constraints,
+                    prompt,
+                    usage,
+                    on_text,
+                )
+                .await
+            }
+            Backend::Local(llm) => {
+                llm.stream_recipe(
+                    ingredients,
+                    dietary_restrictions,
+                    constraints,
+                    prompt,
+                    usage,
+                    on_text,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn generate_recipes(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        count: usize,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<Vec<GeneratedRecipe>, LlmError> {
+        match &self.backend {
+            Backend::Client(llm) => {
+                llm.generate_recipes(
+                    ingredients,
+                    dietary_restrictions,
+                    constraints,
+                    count,
+                    prompt,
+                    usage,
+                )
+                .await
+            }
+            Backend::Local(llm) => {
+                llm.generate_recipes(
+                    ingredients,
+                    dietary_restrictions,
+                    constraints,
+                    count,
+                    prompt,
+                    usage,
+                )
+                .await
+            }
+        }
+    }
+}
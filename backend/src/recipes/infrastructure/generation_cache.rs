@@ -0,0 +1,51 @@
+use sqlx::PgPool;
+
+use super::memory_generation_cache::InMemoryGenerationCache;
+use super::pg_generation_cache::PgGenerationCache;
+use crate::recipes::domain::{GeneratedRecipe, GenerationCache, GenerationKey};
+use crate::shared::config::{GenerationCacheBackend, GenerationCacheConfig};
+
+/// The cache backend chosen in the configuration. `GenerationCache` is not
+/// object safe, so the backends are dispatched through an enum.
+enum Backend {
+    Off,
+    Memory(InMemoryGenerationCache),
+    Postgres(PgGenerationCache),
+}
+
+pub struct GenerationCacheStore {
+    backend: Backend,
+}
+
+impl GenerationCacheStore {
+    pub fn from_config(config: &GenerationCacheConfig, pool: PgPool) -> Self {
+        let backend = match config.backend {
+            GenerationCacheBackend::Off => Backend::Off,
+            GenerationCacheBackend::Memory => {
+                Backend::Memory(InMemoryGenerationCache::new(config.capacity, config.ttl))
+            }
+            GenerationCacheBackend::Postgres => {
+                Backend::Postgres(PgGenerationCache::new(pool, config.ttl))
+            }
+        };
+        Self { backend }
+    }
+}
+
+impl GenerationCache for GenerationCacheStore {
+    async fn get(&self, key: &GenerationKey) -> Option<Vec<GeneratedRecipe>> {
+        match &self.backend {
+            Backend::Off => None,
+            Backend::Memory(cache) => cache.get(key).await,
+            Backend::Postgres(cache) => cache.get(key).await,
+        }
+    }
+
+    async fn put(&self, key: GenerationKey, recipes: Vec<GeneratedRecipe>) {
+        match &self.backend {
+            Backend::Off => {}
+            Backend::Memory(cache) => cache.put(key, recipes).await,
+            Backend::Postgres(cache) => cache.put(key, recipes).await,
+        }
+    }
+}
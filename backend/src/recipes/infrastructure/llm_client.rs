@@ -0,0 +1,547 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+
+use super::anthropic_client::AnthropicClient;
+use super::ollama_client::OllamaClient;
+use super::openai_client::OpenAiClient;
+use super::prompt::{
+    parse_recipe, parse_recipes, parse_substitutes, recipe_schema, recipes_schema, repair_prompt,
+    substitutes_schema,
+};
+use super::prompt_templates::{PromptSet, PromptTemplates};
+use crate::recipes::domain::{
+    GeneratedRecipe, LlmError, LlmService, ModelOutput, PromptChoice, RecipeConstraints,
+    SuggestedSubstitute, UsageMeter,
+};
+use crate::shared::config::{LlmConfig, LlmProvider};
+
+/// One turn of a conversation with the model. All supported APIs accept
+/// messages in this shape.
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct ChatMessage {
+    pub role: &'static str,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn user(content: String) -> Self {
+        Self {
+            role: "user",
+            content,
+        }
+    }
+
+    pub fn assistant(content: String) -> Self {
+        Self {
+            role: "assistant",
+            content,
+        }
+    }
+}
+
+/// A provider's chat API, asked to reply with JSON matching a schema from
+/// `prompt` in at most `max_tokens` tokens. The tokens each reply cost are
+/// recorded in `usage`.
+pub(super) trait ChatModel: Send + Sync {
+    /// The reply's text.
+    fn complete(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+    ) -> impl Future<Output = Result<String, LlmError>> + Send;
+
+    /// The reply's text, also handed to `on_text` piece by piece as it arrives.
+    fn complete_streaming<F: FnMut(&str) + Send>(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+        on_text: F,
+    ) -> impl Future<Output = Result<String, LlmError>> + Send;
+}
+
+/// The LLM provider chosen in the configuration. `LlmService` is not object
+/// safe, so the providers are dispatched through an enum.
+enum Provider {
+    OpenAi(OpenAiClient),
+    Anthropic(AnthropicClient),
+    Ollama(OllamaClient),
+}
+
+impl ChatModel for Provider {
+    async fn complete(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+    ) -> Result<String, LlmError> {
+        match self {
+            Self::OpenAi(client) => client.complete(messages, schema, max_tokens, usage).await,
+            Self::Anthropic(client) => client.complete(messages, schema, max_tokens, usage).await,
+            Self::Ollama(client) => client.complete(messages, schema, max_tokens, usage).await,
+        }
+    }
+
+    async fn complete_streaming<F: FnMut(&str) + Send>(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+        on_text: F,
+    ) -> Result<String, LlmError> {
+        match self {
+            Self::OpenAi(client) => {
+                client
+                    .complete_streaming(messages, schema, max_tokens, usage, on_text)
+                    .await
+            }
+            Self::Anthropic(client) => {
+                client
+                    .complete_streaming(messages, schema, max_tokens, usage, on_text)
+                    .await
+            }
+            Self::Ollama(client) => {
+                client
+                    .complete_streaming(messages, schema, max_tokens, usage, on_text)
+                    .await
+            }
+        }
+    }
+}
+
+pub struct LlmClient {
+    provider: Provider,
+    /// Token budget for a reply holding one recipe.
+    max_tokens: u32,
+    max_repairs: u32,
+    /// Provider and model, e.g. `openai/gpt-4o-mini`.
+    model: String,
+    prompts: PromptTemplates,
+    version: String,
+}
+
+impl LlmClient {
+    /// The client for the provider in `config`, or `None` for the local
+    /// stand-in, which has no API to call.
+    pub fn from_config(config: &LlmConfig) -> Option<Self> {
+        let (provider, name) = match config.provider {
+            LlmProvider::OpenAi => (Provider::OpenAi(OpenAiClient::new(config)), "openai"),
+            LlmProvider::Anthropic => (
+                Provider::Anthropic(AnthropicClient::new(config)),
+                "anthropic",
+            ),
+            LlmProvider::Ollama => (Provider::Ollama(OllamaClient::new(config)), "ollama"),
+            LlmProvider::Local => return None,
+        };
+        let model = format!("{}/{}", name, config.model);
+        let prompts =
+            PromptTemplates::from_config(&config.prompts).unwrap_or_else(|e| panic!("{}", e));
+        Some(Self {
+            provider,
+            max_tokens: config.max_tokens,
+            max_repairs: config.max_repairs,
+            version: format!("{}/prompt-{}", model, prompts.version()),
+            model,
+            prompts,
+        })
+    }
+
+    /// Reads `reply` in `format`, repairing it if need be, and records the
+    /// reply that was finally read in `usage`, along with the `prompts` that
+    /// asked for it.
+    async fn read_reply<T>(
+        &self,
+        prompts: &PromptSet,
+        format: &ReplyFormat<T>,
+        messages: Vec<ChatMessage>,
+        reply: String,
+        usage: &UsageMeter,
+    ) -> Result<T, LlmError> {
+        let (parsed, raw) = parse_with_repairs(
+            &self.provider,
+            format,
+            messages,
+            reply,
+            self.max_repairs,
+            usage,
+        )
+        .await?;
+        usage.record_output(ModelOutput {
+            model: self.model.clone(),
+            prompt_version: prompts.version.clone(),
+            raw,
+        });
+        Ok(parsed)
+    }
+}
+
+/// A reply format: the schema the model is held to, how many tokens its
+/// reply may take, and how it is read.
+struct ReplyFormat<T> {
+    schema: Value,
+    max_tokens: u32,
+    parse: fn(&str) -> Result<T, String>,
+}
+
+impl ReplyFormat<GeneratedRecipe> {
+    fn recipe(max_tokens: u32) -> Self {
+        Self {
+            schema: recipe_schema(),
+            max_tokens,
+            parse: parse_recipe,
+        }
+    }
+}
+
+impl ReplyFormat<Vec<GeneratedRecipe>> {
+    /// `count` recipes, each given the `max_tokens` budget of a single one.
+    fn recipes(count: usize, max_tokens: u32) -> Self {
+        let count = u32::try_from(count).unwrap_or(u32::MAX).max(1);
+        Self {
+            schema: recipes_schema(),
+            max_tokens: max_tokens.saturating_mul(count),
+            parse: parse_recipes,
+        }
+    }
+}
+
+impl ReplyFormat<Vec<SuggestedSubstitute>> {
+    fn substitutes(max_tokens: u32) -> Self {
+        Self {
+            schema: substitutes_schema(),
+            max_tokens,
+            parse: parse_substitutes,
+        }
+    }
+}
+
+/// Parses `reply`, and while it cannot be read in `format`, shows the model
+/// what was wrong and asks for a corrected one, at most `max_repairs` times.
+/// Returns what was read along with the reply it was read from.
+async fn parse_with_repairs<M: ChatModel, T>(
+    model: &M,
+    format: &ReplyFormat<T>,
+    mut messages: Vec<ChatMessage>,
+    mut reply: String,
+    max_repairs: u32,
+    usage: &UsageMeter,
+) -> Result<(T, String), LlmError> {
+    let mut repairs = 0;
+    loop {
+        let error = match (format.parse)(&reply) {
+            Ok(recipe) => {
+                if repairs > 0 {
+                    tracing::info!(repairs, "Repaired malformed recipe JSON from the model");
+                }
+                return Ok((recipe, reply));
+            }
+            Err(error) => error,
+        };
+        if repairs >= max_repairs {
+            tracing::warn!(repairs, error = %error, "Giving up on malformed recipe JSON from the model");
+            return Err(LlmError::ParseError(error));
+        }
+
+        repairs += 1;
+        messages.push(ChatMessage::assistant(reply));
+        messages.push(ChatMessage::user(repair_prompt(&error)));
+        reply = model
+            .complete(&messages, &format.schema, format.max_tokens, usage)
+            .await?;
+    }
+}
+
+impl LlmService for LlmClient {
+    fn version(&self) -> String {
+        self.version.clone()
+    }
+
+    fn choose_prompt(&self) -> PromptChoice {
+        let prompts = self.prompts.choose();
+        PromptChoice {
+            version: format!("{}/prompt-{}", self.model, prompts.version),
+            prompt: prompts.version.clone(),
+        }
+    }
+
+    async fn generate_recipe(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        let prompts = self.prompts.get(&prompt.prompt);
+        let messages = vec![ChatMessage::user(prompts.recipe(
+            &ingredients,
+            &dietary_restrictions,
+            &constraints,
+        ))];
+        let format = ReplyFormat::recipe(self.max_tokens);
+        let reply = self
+            .provider
+            .complete(&messages, &format.schema, format.max_tokens, usage)
+            .await?;
+
+        self.read_reply(prompts, &format, messages, reply, usage)
+            .await
+    }
+
+    async fn refine_recipe(
+        &self,
+        recipe: GeneratedRecipe,
+        instruction: String,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        let prompts = self.prompts.get(&prompt.prompt);
+        let messages = vec![ChatMessage::user(prompts.refine(&recipe, &instruction))];
+        let format = ReplyFormat::recipe(self.max_tokens);
+        let reply = self
+            .provider
+            .complete(&messages, &format.schema, format.max_tokens, usage)
+            .await?;
+
+        self.read_reply(prompts, &format, messages, reply, usage)
+            .await
+    }
+
+    async fn suggest_substitutes(
+        &self,
+        recipe: GeneratedRecipe,
+        ingredient: String,
+        dietary_restrictions: Option<Vec<String>>,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<Vec<SuggestedSubstitute>, LlmError> {
+        let prompts = self.prompts.get(&prompt.prompt);
+        let messages = vec![ChatMessage::user(prompts.substitutes(
+            &recipe,
+            &ingredient,
+            &dietary_restrictions,
+        ))];
+        let format = ReplyFormat::substitutes(self.max_tokens);
+        let reply = self
+            .provider
+            .complete(&messages, &format.schema, format.max_tokens, usage)
+            .await?;
+
+        self.read_reply(prompts, &format, messages, reply, usage)
+            .await
+    }
+
+    async fn stream_recipe<F: FnMut(&str) + Send>(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+        on_text: F,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        let prompts = self.prompts.get(&prompt.prompt);
+        let messages = vec![ChatMessage::user(prompts.recipe(
+            &ingredients,
+            &dietary_restrictions,
+            &constraints,
+        ))];
+        let format = ReplyFormat::recipe(self.max_tokens);
+        let reply = self
+            .provider
+            .complete_streaming(&messages, &format.schema, format.max_tokens, usage, on_text)
+            .await?;
+
+        // Repairs are not streamed; the corrected recipe only arrives whole.
+        self.read_reply(prompts, &format, messages, reply, usage)
+            .await
+    }
+
+    async fn generate_recipes(
+        &self,
+        ingredients: Vec<String>,
+        dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        count: usize,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<Vec<GeneratedRecipe>, LlmError> {
+        let prompts = self.prompts.get(&prompt.prompt);
+        let messages = vec![ChatMessage::user(prompts.recipes(
+            &ingredients,
+            &dietary_restrictions,
+            &constraints,
+            count,
+        ))];
+        let format = ReplyFormat::recipes(count, self.max_tokens);
+        let reply = self
+            .provider
+            .complete(&messages, &format.schema, format.max_tokens, usage)
+            .await?;
+
+        let mut recipes = self
+            .read_reply(prompts, &format, messages, reply, usage)
+            .await?;
+        recipes.truncate(count);
+        Ok(recipes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Replies with canned answers in order and records what it was sent,
+    /// along with the token budget it was given.
+    struct ScriptedModel {
+        replies: Mutex<Vec<String>>,
+        requests: Mutex<Vec<Vec<ChatMessage>>>,
+        budgets: Mutex<Vec<u32>>,
+    }
+
+    impl ScriptedModel {
+        fn new(replies: &[&str]) -> Self {
+            Self {
+                replies: Mutex::new(replies.iter().rev().map(|r| r.to_string()).collect()),
+                requests: Mutex::new(Vec::new()),
+                budgets: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl ChatModel for ScriptedModel {
+        async fn complete(
+            &self,
+            messages: &[ChatMessage],
+            _schema: &Value,
+            max_tokens: u32,
+            _usage: &UsageMeter,
+        ) -> Result<String, LlmError> {
+            self.requests.lock().unwrap().push(messages.to_vec());
+            self.budgets.lock().unwrap().push(max_tokens);
+            Ok(self.replies.lock().unwrap().pop().unwrap())
+        }
+
+        async fn complete_streaming<F: FnMut(&str) + Send>(
+            &self,
+            messages: &[ChatMessage],
+            schema: &Value,
+            max_tokens: u32,
+            usage: &UsageMeter,
+            _on_text: F,
+        ) -> Result<String, LlmError> {
+            self.complete(messages, schema, max_tokens, usage).await
+        }
+    }
+
+    const VALID: &str = r#"{"title": "Soup", "ingredients": ["water"], "instructions": ["Boil"], "prep_time_minutes": 1, "cook_time_minutes": 5, "servings": 1}"#;
+
+    fn prompt() -> Vec<ChatMessage> {
+        vec![ChatMessage::user("make soup".to_string())]
+    }
+
+    #[tokio::test]
+    async fn asks_the_model_to_fix_invalid_json() {
+        let model = ScriptedModel::new(&[VALID]);
+
+        let (recipe, raw) = parse_with_repairs(
+            &model,
+            &ReplyFormat::recipe(1024),
+            prompt(),
+            r#"{"title": "Soup"}"#.to_string(),
+            2,
+            &UsageMeter::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(recipe.title, "Soup");
+        assert_eq!(raw, VALID);
+        let requests = model.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let roles: Vec<_> = requests[0].iter().map(|m| m.role).collect();
+        assert_eq!(roles, vec!["user", "assistant", "user"]);
+        assert!(
+            requests[0][2]
+                .content
+                .contains("missing field `ingredients`")
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_repairs() {
+        let model = ScriptedModel::new(&["nope", "still nope"]);
+
+        let result = parse_with_repairs(
+            &model,
+            &ReplyFormat::recipe(1024),
+            prompt(),
+            "not json".to_string(),
+            2,
+            &UsageMeter::default(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(LlmError::ParseError(_))));
+        assert_eq!(model.requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn valid_replies_need_no_repair() {
+        let model = ScriptedModel::new(&[]);
+
+        let (recipe, _) = parse_with_repairs(
+            &model,
+            &ReplyFormat::recipe(1024),
+            prompt(),
+            VALID.to_string(),
+            2,
+            &UsageMeter::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(recipe.servings, Some(1));
+        assert!(model.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn repairs_lists_of_recipes_too() {
+        let list = format!(r#"{{"recipes": [{}, {}]}}"#, VALID, VALID);
+        let model = ScriptedModel::new(&[&list]);
+
+        let (recipes, _) = parse_with_repairs(
+            &model,
+            &ReplyFormat::recipes(3, 1024),
+            prompt(),
+            r#"{"recipes": []}"#.to_string(),
+            2,
+            &UsageMeter::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(recipes.len(), 2);
+        assert!(
+            model.requests.lock().unwrap()[0][2]
+                .content
+                .contains("`recipes` is empty")
+        );
+        assert_eq!(*model.budgets.lock().unwrap(), vec![3072]);
+    }
+
+    #[test]
+    fn recipe_lists_get_a_budget_per_recipe() {
+        assert_eq!(ReplyFormat::recipe(1024).max_tokens, 1024);
+        assert_eq!(ReplyFormat::recipes(1, 1024).max_tokens, 1024);
+        assert_eq!(ReplyFormat::recipes(3, 1024).max_tokens, 3072);
+        assert_eq!(ReplyFormat::recipes(5, 1024).max_tokens, 5120);
+    }
+}
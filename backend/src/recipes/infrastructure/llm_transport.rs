@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use super::circuit_breaker::CircuitBreaker;
+use crate::recipes::domain::{LlmError, TokenUsage};
+use crate::shared::config::LlmConfig;
+
+/// HTTP plumbing shared by the LLM clients: timeouts, retries with jittered
+/// exponential backoff, and a circuit breaker around the provider.
+pub(super) struct LlmTransport {
+    client: Client,
+    timeout: Duration,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    max_retry_delay: Duration,
+    breaker: CircuitBreaker,
+}
+
+/// Why a single attempt failed, and whether another one may succeed.
+struct AttemptError {
+    error: LlmError,
+    retryable: bool,
+    retry_after: Option<Duration>,
+}
+
+impl AttemptError {
+    fn fatal(error: LlmError) -> Self {
+        Self {
+            error,
+            retryable: false,
+            retry_after: None,
+        }
+    }
+
+    fn retryable(error: LlmError, retry_after: Option<Duration>) -> Self {
+        Self {
+            error,
+            retryable: true,
+            retry_after,
+        }
+    }
+
+    fn from_reqwest(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::retryable(LlmError::Timeout(e.to_string()), None)
+        } else if e.is_connect() || e.is_request() {
+            Self::retryable(LlmError::Unavailable(e.to_string()), None)
+        } else {
+            Self::fatal(LlmError::ApiError(e.to_string()))
+        }
+    }
+
+    fn timed_out(timeout: Duration) -> Self {
+        Self::retryable(
+            LlmError::Timeout(format!("No response within {}s", timeout.as_secs())),
+            None,
+        )
+    }
+
+    fn from_status(status: StatusCode, retry_after: Option<Duration>, body: &str) -> Self {
+        let message = format!("{}: {}", status, body);
+        match status.as_u16() {
+            408 => Self::retryable(LlmError::Timeout(message), retry_after),
+            429 => Self::retryable(LlmError::RateLimited(message), retry_after),
+            // 529 is Anthropic's "overloaded".
+            500 | 502 | 503 | 504 | 529 => {
+                Self::retryable(LlmError::Unavailable(message), retry_after)
+            }
+            _ => Self::fatal(LlmError::ApiError(message)),
+        }
+    }
+}
+
+impl LlmTransport {
+    pub(super) fn new(config: &LlmConfig) -> Self {
+        Self {
+            client: Client::builder()
+                .connect_timeout(config.timeout)
+                .build()
+                .expect("Failed to build HTTP client"),
+            timeout: config.timeout,
+            max_retries: config.max_retries,
+            retry_base_delay: config.retry_base_delay,
+            max_retry_delay: config.max_retry_delay,
+            breaker: CircuitBreaker::new(config.breaker_threshold, config.breaker_cooldown),
+        }
+    }
+
+    pub(super) fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Sends the request and decodes a JSON body, retrying as needed.
+    pub(super) async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, LlmError> {
+        self.with_retries(|| {
+            let request = request.try_clone();
+            async move {
+                let response = self.send_once(request).await?;
+                tokio::time::timeout(self.timeout, response.json())
+                    .await
+                    .map_err(|_| AttemptError::timed_out(self.timeout))?
+                    .map_err(AttemptError::from_reqwest)
+            }
+        })
+        .await
+    }
+
+    /// Sends the request and hands each line of the streamed body to
+    /// `on_line` as soon as it has fully arrived. Only getting the response
+    /// started is retried, as lines already handed over cannot be taken back.
+    pub(super) async fn send_lines<F: FnMut(&str) -> Result<(), LlmError>>(
+        &self,
+        request: RequestBuilder,
+        mut on_line: F,
+    ) -> Result<(), LlmError> {
+        let mut response = self
+            .with_retries(|| self.send_once(request.try_clone()))
+            .await?;
+        // Bytes rather than text, as a chunk can end inside a UTF-8 sequence.
+        let mut buffer = Vec::new();
+
+        loop {
+            let chunk = tokio::time::timeout(self.timeout, response.chunk())
+                .await
+                .map_err(|_| AttemptError::timed_out(self.timeout).error)?
+                .map_err(|e| AttemptError::from_reqwest(e).error)?;
+            let Some(chunk) = chunk else {
+                break;
+            };
+
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                on_line(String::from_utf8_lossy(&line).trim_end())?;
+            }
+        }
+        if !buffer.is_empty() {
+            on_line(String::from_utf8_lossy(&buffer).trim_end())?;
+        }
+
+        Ok(())
+    }
+
+    async fn send_once(&self, request: Option<RequestBuilder>) -> Result<Response, AttemptError> {
+        let request = request.ok_or_else(|| {
+            AttemptError::fatal(LlmError::ApiError(
+                "Request body cannot be resent".to_string(),
+            ))
+        })?;
+        let response = tokio::time::timeout(self.timeout, request.send())
+            .await
+            .map_err(|_| AttemptError::timed_out(self.timeout))?
+            .map_err(AttemptError::from_reqwest)?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let retry_after = retry_after(response.headers(), Utc::now());
+        let body = response.text().await.unwrap_or_default();
+        Err(AttemptError::from_status(status, retry_after, &body))
+    }
+
+    async fn with_retries<T, Fut>(&self, mut attempt: impl FnMut() -> Fut) -> Result<T, LlmError>
+    where
+        Fut: Future<Output = Result<T, AttemptError>>,
+    {
+        if !self.breaker.allows(Instant::now()) {
+            return Err(LlmError::Unavailable(
+                "Circuit breaker is open after repeated failures".to_string(),
+            ));
+        }
+
+        let mut retries = 0;
+        loop {
+            let failure = match attempt().await {
+                Ok(value) => {
+                    self.breaker.record_success();
+                    return Ok(value);
+                }
+                Err(failure) => failure,
+            };
+
+            let delay = failure.retry_after.unwrap_or_else(|| {
+                backoff_delay(
+                    self.retry_base_delay,
+                    self.max_retry_delay,
+                    retries,
+                    rand::random(),
+                )
+            });
+            if !failure.retryable || retries >= self.max_retries || delay > self.max_retry_delay {
+                if matches!(
+                    failure.error,
+                    LlmError::Timeout(_) | LlmError::Unavailable(_)
+                ) {
+                    self.breaker.record_failure(Instant::now());
+                }
+                return Err(failure.error);
+            }
+
+            retries += 1;
+            tracing::warn!(
+                retry = retries,
+                delay_ms = delay.as_millis() as u64,
+                error = ?failure.error,
+                "Retrying LLM request"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// "Full jitter" backoff: a random delay up to `base * 2^retry`, capped at
+/// `max`. `jitter` is a random number in `[0, 1)`.
+fn backoff_delay(base: Duration, max: Duration, retry: u32, jitter: f64) -> Duration {
+    let ceiling = base.saturating_mul(2u32.saturating_pow(retry)).min(max);
+    ceiling.mul_f64(jitter.clamp(0.0, 1.0))
+}
+
+/// How long a `Retry-After` header asks us to wait, given as either seconds
+/// or an HTTP date.
+fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+/// What one line of a streamed reply carries: a piece of its text, the
+/// tokens it used, both or neither.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct StreamUpdate {
+    pub text: Option<String>,
+    pub usage: Option<TokenUsage>,
+}
+
+/// The payload of a server-sent `data:` line.
+pub(super) fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let base = Duration::from_millis(500);
+        let max = Duration::from_secs(3);
+
+        assert_eq!(backoff_delay(base, max, 0, 1.0), base);
+        assert_eq!(backoff_delay(base, max, 2, 1.0), Duration::from_secs(2));
+        assert_eq!(backoff_delay(base, max, 5, 1.0), max);
+        assert_eq!(backoff_delay(base, max, 40, 1.0), max);
+        assert_eq!(backoff_delay(base, max, 1, 0.5), Duration::from_millis(500));
+        assert_eq!(backoff_delay(base, max, 1, 0.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn reads_retry_after_as_seconds_or_date() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers, now), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(12)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:30 GMT"),
+        );
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(30)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:27:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers, now), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn classifies_statuses() {
+        let classify = |code: u16| {
+            let failure = AttemptError::from_status(StatusCode::from_u16(code).unwrap(), None, "");
+            (failure.error, failure.retryable)
+        };
+
+        assert!(matches!(classify(429), (LlmError::RateLimited(_), true)));
+        assert!(matches!(classify(408), (LlmError::Timeout(_), true)));
+        assert!(matches!(classify(503), (LlmError::Unavailable(_), true)));
+        assert!(matches!(classify(529), (LlmError::Unavailable(_), true)));
+        assert!(matches!(classify(401), (LlmError::ApiError(_), false)));
+        assert!(matches!(classify(400), (LlmError::ApiError(_), false)));
+    }
+}
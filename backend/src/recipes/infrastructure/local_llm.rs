@@ -0,0 +1,613 @@
+use std::time::Duration;
+
+use crate::recipes::domain::{
+    Equipment, GeneratedRecipe, LlmError, LlmService, ModelOutput, ParsedIngredient, PromptChoice,
+    RecipeConstraints, SuggestedSubstitute, TokenUsage, UsageMeter,
+};
+use crate::shared::config::{LocalLlmConfig, SimulatedFailure};
+
+/// Prefix of an ingredient, instruction or substitute request that makes
+/// one call fail on purpose, e.g. `simulate:outage`.
+const TRIGGER_PREFIX: &str = "simulate:";
+
+/// Pieces a streamed recipe is sent in.
+const STREAM_CHUNKS: usize = 8;
+
+/// A way of cooking the dish, giving it its title and steps.
+struct Method {
+    /// Title around the dish's name, e.g. `{} Skillet`.
+    title: fn(&str) -> String,
+    /// Where the cooking happens.
+    vessel: &'static str,
+    /// How the main ingredient is cooked, e.g. `Sear`.
+    verb: &'static str,
+    cook_minutes: i32,
+    /// Staples used on top of oil, salt and pepper.
+    staples: &'static [&'static str],
+    uses_oven: bool,
+    air_fryer: bool,
+}
+
+const METHODS: &[Method] = &[
+    Method {
+        title: |dish| format!("{} Skillet", dish),
+        vessel: "a large skillet over medium-high heat",
+        verb: "Sear",
+        cook_minutes: 15,
+        staples: &["1 tsp paprika"],
+        uses_oven: false,
+        air_fryer: false,
+    },
+    Method {
+        title: |dish| format!("Hearty {} Stew", dish),
+        vessel: "a heavy pot over medium heat",
+        verb: "Brown",
+        cook_minutes: 35,
+        staples: &["500 ml water", "1 bay leaf"],
+        uses_oven: false,
+        air_fryer: false,
+    },
+    Method {
+        title: |dish| format!("{} Stir-Fry", dish),
+        vessel: "a wok or large pan over high heat",
+        verb: "Stir-fry",
+        cook_minutes: 10,
+        staples: &["1 tsp chili flakes"],
+        uses_oven: false,
+        air_fryer: false,
+    },
+    Method {
+        title: |dish| format!("Roasted {} Traybake", dish),
+        vessel: "a baking tray in an oven preheated to 200°C",
+        verb: "Roast",
+        cook_minutes: 30,
+        staples: &["1 tsp dried thyme"],
+        uses_oven: true,
+        air_fryer: false,
+    },
+    Method {
+        title: |dish| format!("Crispy Air-Fried {}", dish),
+        vessel: "an air fryer basket at 200°C",
+        verb: "Air-fry",
+        cook_minutes: 15,
+        staples: &["1 tsp garlic powder"],
+        uses_oven: false,
+        air_fryer: true,
+    },
+];
+
+/// Amounts given to ingredients, picked by each ingredient's name and the
+/// method.
+const AMOUNTS: &[&str] = &["200 g", "1 cup", "2", "150 g", "1/2 cup", "300 g", "1"];
+
+/// A stand-in for a real model that runs offline. Recipes are made up from
+/// the ingredients, the same ones every time for the same request, and
+/// respect the requested time limit, equipment, servings and exclusions.
+/// Latency, unreadable replies and outages can be simulated for every call
+/// through the configuration, or for one call by passing a
+/// `simulate:<failure>` ingredient, instruction or substitute request.
+pub struct LocalLlm {
+    latency: Duration,
+    failure: Option<SimulatedFailure>,
+}
+
+impl LocalLlm {
+    pub fn new(config: &LocalLlmConfig) -> Self {
+        Self {
+            latency: config.latency,
+            failure: config.failure,
+        }
+    }
+
+    /// Waits out the simulated latency, then fails if asked to, either by
+    /// the configuration or by a trigger among `inputs`.
+    async fn call<'a>(&self, inputs: impl IntoIterator<Item = &'a String>) -> Result<(), LlmError> {
+        tokio::time::sleep(self.latency).await;
+        match self.requested_failure(inputs) {
+            Some(failure) => Err(failure_error(failure)),
+            None => Ok(()),
+        }
+    }
+
+    fn requested_failure<'a>(
+        &self,
+        inputs: impl IntoIterator<Item = &'a String>,
+    ) -> Option<SimulatedFailure> {
+        let triggered = inputs.into_iter().find_map(|input| {
+            input
+                .trim()
+                .to_lowercase()
+                .strip_prefix(TRIGGER_PREFIX)
+                .and_then(SimulatedFailure::parse)
+        });
+        triggered.or(self.failure)
+    }
+
+    /// Records a reply as if the model had written it, with a rough token
+    /// count, so usage and history work as they would with a real model.
+    fn record(&self, usage: &UsageMeter, prompt: &[String], reply: String) {
+        usage.record(TokenUsage {
+            prompt_tokens: prompt.iter().map(|p| count_words(p)).sum::<i64>() + 200,
+            completion_tokens: count_words(&reply),
+        });
+        usage.record_output(ModelOutput {
+            model: "local/stand-in".to_string(),
+            prompt_version: "local".to_string(),
+            raw: reply,
+        });
+    }
+}
+
+fn failure_error(failure: SimulatedFailure) -> LlmError {
+    match failure {
+        SimulatedFailure::ParseError => {
+            LlmError::ParseError("Simulated unreadable reply from the local model".to_string())
+        }
+        SimulatedFailure::Outage => {
+            LlmError::Unavailable("Simulated outage of the local model".to_string())
+        }
+        SimulatedFailure::Timeout => {
+            LlmError::Timeout("Simulated timeout of the local model".to_string())
+        }
+        SimulatedFailure::RateLimit => {
+            LlmError::RateLimited("Simulated rate limit of the local model".to_string())
+        }
+    }
+}
+
+fn count_words(text: &str) -> i64 {
+    text.split_whitespace().count() as i64
+}
+
+/// FNV-1a, which unlike the standard hasher is stable across releases.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn title_case(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// The ways of cooking that fit the equipment limits, with their
+/// positions in `METHODS`.
+fn methods_for(constraints: &RecipeConstraints) -> Vec<(usize, &'static Method)> {
+    let equipment = &constraints.equipment;
+    let air_fryer_only = equipment.contains(&Equipment::AirFryerOnly);
+    let no_oven = equipment.contains(&Equipment::NoOven) || equipment.contains(&Equipment::OnePot);
+    METHODS
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.air_fryer == air_fryer_only)
+        .filter(|(_, m)| !(m.uses_oven && no_oven))
+        .collect()
+}
+
+/// Recipes for `ingredients`, one per way of cooking that fits the
+/// constraints and at most `count`, starting from one picked by the
+/// ingredients so different requests get different dishes.
+fn make_recipes(
+    ingredients: &[String],
+    constraints: &RecipeConstraints,
+    count: usize,
+) -> Vec<GeneratedRecipe> {
+    let names: Vec<String> = ingredients
+        .iter()
+        .map(|i| ParsedIngredient::parse(i).item)
+        .filter(|name| !name.trim().is_empty())
+        .collect();
+    let methods = methods_for(constraints);
+    let start = stable_hash(&names.join(",").to_lowercase()) as usize % METHODS.len();
+
+    (0..count.min(methods.len()))
+        .map(|n| {
+            let (variant, method) = methods[(start + n) % methods.len()];
+            make_recipe(&names, constraints, method, variant)
+        })
+        .collect()
+}
+
+fn make_recipe(
+    names: &[String],
+    constraints: &RecipeConstraints,
+    method: &Method,
+    variant: usize,
+) -> GeneratedRecipe {
+    let dish = match names {
+        [] => "Pantry".to_string(),
+        [only] => title_case(only),
+        [first, second, ..] => format!("{} and {}", title_case(first), title_case(second)),
+    };
+    let title = match &constraints.cuisine {
+        Some(cuisine) => format!("{}-Style {}", title_case(cuisine), (method.title)(&dish)),
+        None => (method.title)(&dish),
+    };
+
+    let excluded = |staple: &str| {
+        constraints
+            .excluded_ingredients
+            .iter()
+            .any(|e| staple.contains(&e.trim().to_lowercase()))
+    };
+    let mut lines: Vec<String> = names
+        .iter()
+        .map(|name| {
+            // Every method gets different amounts, so its recipes are not
+            // taken for duplicates of the others.
+            let amount =
+                AMOUNTS[(stable_hash(name) as usize % AMOUNTS.len() + variant) % AMOUNTS.len()];
+            format!("{} {}", amount, name)
+        })
+        .collect();
+    let oil = !excluded("oil");
+    if oil {
+        lines.push("2 tbsp oil".to_string());
+    }
+    lines.extend(
+        method
+            .staples
+            .iter()
+            .filter(|staple| !excluded(staple))
+            .map(|staple| staple.to_string()),
+    );
+    let seasoning = !excluded("salt") && !excluded("pepper");
+    if seasoning {
+        lines.push("Salt and pepper to taste".to_string());
+    }
+
+    let mut steps: Vec<String> = names
+        .iter()
+        .map(|name| {
+            format!(
+                "Prepare the {}: rinse, trim and cut into bite-sized pieces.",
+                name
+            )
+        })
+        .collect();
+    steps.push(if oil {
+        format!("Heat the oil in {}.", method.vessel)
+    } else {
+        format!("Heat {}.", method.vessel)
+    });
+    if let Some(main) = names.first() {
+        steps.push(format!(
+            "{} the {} until cooked through and golden.",
+            method.verb, main
+        ));
+    }
+    if names.len() > 1 {
+        steps.push(format!(
+            "Add the {} and cook until tender.",
+            names[1..].join(", ")
+        ));
+    }
+    steps.push(if seasoning {
+        "Season with salt and pepper, then serve hot.".to_string()
+    } else {
+        "Taste, adjust and serve hot.".to_string()
+    });
+
+    let (prep, cook) = fit_times(
+        5 + 3 * names.len() as i32,
+        method.cook_minutes + 2 * names.len() as i32,
+        constraints.max_total_minutes,
+    );
+    GeneratedRecipe {
+        title,
+        ingredients: lines,
+        instructions: steps,
+        prep_time_minutes: Some(prep),
+        cook_time_minutes: Some(cook),
+        servings: Some(constraints.servings.unwrap_or(4)),
+    }
+}
+
+/// Prep and cook times shortened in proportion to fit `max_total` minutes.
+fn fit_times(prep: i32, cook: i32, max_total: Option<i32>) -> (i32, i32) {
+    match max_total {
+        Some(max) if prep + cook > max => {
+            let prep = (max * prep / (prep + cook)).clamp(1, max.max(1));
+            (prep, (max - prep).max(0))
+        }
+        _ => (prep, cook),
+    }
+}
+
+impl LlmService for LocalLlm {
+    fn version(&self) -> String {
+        "local/stand-in".to_string()
+    }
+
+    async fn generate_recipe(
+        &self,
+        ingredients: Vec<String>,
+        _dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        self.generate_recipes(ingredients, None, constraints, 1, prompt, usage)
+            .await?
+            .pop()
+            .ok_or_else(|| LlmError::ApiError("The local model made no recipe".to_string()))
+    }
+
+    async fn refine_recipe(
+        &self,
+        mut recipe: GeneratedRecipe,
+        instruction: String,
+        _prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        self.call([&instruction]).await?;
+        let instruction = instruction.trim().trim_end_matches('.');
+        recipe.title = format!("{} (revised)", recipe.title.trim_end_matches(" (revised)"));
+        recipe
+            .instructions
+            .push(format!("Finally, adjust the dish: {}.", instruction));
+        let reply = serde_json::to_string(&recipe).expect("recipes serialize to JSON");
+        self.record(usage, &[instruction.to_string()], reply);
+        Ok(recipe)
+    }
+
+    async fn suggest_substitutes(
+        &self,
+        _recipe: GeneratedRecipe,
+        ingredient: String,
+        _dietary_restrictions: Option<Vec<String>>,
+        _prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<Vec<SuggestedSubstitute>, LlmError> {
+        self.call([&ingredient]).await?;
+        let parsed = ParsedIngredient::parse(&ingredient);
+        let amount = ingredient
+            .trim()
+            .strip_suffix(parsed.item.as_str())
+            .map(str::trim)
+            .filter(|amount| !amount.is_empty())
+            .unwrap_or("the same amount of");
+        let substitutes = vec![SuggestedSubstitute {
+            ingredients: vec![format!("{} another {}", amount, parsed.item)],
+            note: Some(format!(
+                "Any {} you have on hand works the same way.",
+                parsed.item
+            )),
+        }];
+        let reply = serde_json::json!({ "substitutes": substitutes
+            .iter()
+            .map(|s| serde_json::json!({ "ingredients": s.ingredients, "note": s.note }))
+            .collect::<Vec<_>>() })
+        .to_string();
+        self.record(usage, &[ingredient], reply);
+        Ok(substitutes)
+    }
+
+    async fn stream_recipe<F: FnMut(&str) + Send>(
+        &self,
+        ingredients: Vec<String>,
+        _dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        _prompt: &PromptChoice,
+        usage: &UsageMeter,
+        mut on_text: F,
+    ) -> Result<GeneratedRecipe, LlmError> {
+        let failure = self.requested_failure(&ingredients);
+        let recipe = make_recipes(&ingredients, &constraints, 1)
+            .pop()
+            .ok_or_else(|| LlmError::ApiError("The local model made no recipe".to_string()))?;
+        let reply = serde_json::to_string(&recipe).expect("recipes serialize to JSON");
+
+        // An unreadable reply breaks off halfway; other failures send nothing.
+        let sent = match failure {
+            None => reply.len(),
+            Some(SimulatedFailure::ParseError) => reply.len() / 2,
+            Some(failure) => {
+                tokio::time::sleep(self.latency).await;
+                return Err(failure_error(failure));
+            }
+        };
+        let chars: Vec<char> = reply.chars().collect();
+        let chunk_len = chars.len().div_ceil(STREAM_CHUNKS).max(1);
+        let mut streamed = 0;
+        for chunk in chars.chunks(chunk_len) {
+            if streamed >= sent {
+                break;
+            }
+            tokio::time::sleep(self.latency / STREAM_CHUNKS as u32).await;
+            let text: String = chunk.iter().collect();
+            streamed += text.len();
+            on_text(&text);
+        }
+
+        if let Some(failure) = failure {
+            return Err(failure_error(failure));
+        }
+        self.record(usage, &ingredients, reply);
+        Ok(recipe)
+    }
+
+    async fn generate_recipes(
+        &self,
+        ingredients: Vec<String>,
+        _dietary_restrictions: Option<Vec<String>>,
+        constraints: RecipeConstraints,
+        count: usize,
+        _prompt: &PromptChoice,
+        usage: &UsageMeter,
+    ) -> Result<Vec<GeneratedRecipe>, LlmError> {
+        self.call(&ingredients).await?;
+        let recipes = make_recipes(&ingredients, &constraints, count);
+        let reply = serde_json::to_string(&recipes).expect("recipes serialize to JSON");
+        self.record(usage, &ingredients, reply);
+        Ok(recipes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn local(failure: Option<SimulatedFailure>) -> LocalLlm {
+        LocalLlm::new(&LocalLlmConfig {
+            latency: Duration::ZERO,
+            failure,
+        })
+    }
+
+    #[test]
+    fn recipes_are_deterministic_and_valid() {
+        let ingredients = names(&["chicken breast", "rice", "broccoli"]);
+        let constraints = RecipeConstraints::default();
+
+        let first = make_recipes(&ingredients, &constraints, 1);
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::to_value(make_recipes(&ingredients, &constraints, 1)).unwrap()
+        );
+
+        let recipe = &first[0];
+        assert!(
+            recipe.title.contains("Chicken Breast and Rice"),
+            "{}",
+            recipe.title
+        );
+        assert_eq!(recipe.validate(&ingredients), vec![]);
+        assert_eq!(recipe.constraint_violations(&constraints), vec![]);
+        assert!(recipe.instructions.iter().any(|s| s.contains("broccoli")));
+    }
+
+    #[test]
+    fn recipes_respect_constraints() {
+        let ingredients = names(&["tofu", "noodles"]);
+        let constraints = RecipeConstraints {
+            cuisine: Some("thai".to_string()),
+            max_total_minutes: Some(12),
+            equipment: vec![Equipment::NoOven],
+            servings: Some(2),
+            excluded_ingredients: names(&["salt"]),
+            ..Default::default()
+        };
+
+        let recipes = make_recipes(&ingredients, &constraints, 5);
+
+        assert_eq!(recipes.len(), 3);
+        for (i, recipe) in recipes.iter().enumerate() {
+            for other in &recipes[i + 1..] {
+                assert!(!recipe.is_near_duplicate_of(other), "{}", other.title);
+            }
+        }
+        for recipe in &recipes {
+            assert!(recipe.title.starts_with("Thai-Style "), "{}", recipe.title);
+            assert!(!recipe.title.contains("Roasted"));
+            assert_eq!(recipe.constraint_violations(&constraints), vec![]);
+            assert_eq!(recipe.validate(&ingredients), vec![]);
+        }
+    }
+
+    #[test]
+    fn air_fryer_only_allows_one_recipe() {
+        let constraints = RecipeConstraints {
+            equipment: vec![Equipment::AirFryerOnly],
+            ..Default::default()
+        };
+
+        let recipes = make_recipes(&names(&["potatoes"]), &constraints, 3);
+
+        assert_eq!(recipes.len(), 1);
+        assert_eq!(recipes[0].title, "Crispy Air-Fried Potatoes");
+    }
+
+    #[test]
+    fn times_shrink_to_fit_the_limit() {
+        assert_eq!(fit_times(10, 20, None), (10, 20));
+        assert_eq!(fit_times(10, 20, Some(15)), (5, 10));
+        assert_eq!(fit_times(10, 20, Some(1)), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn failures_are_simulated_on_demand() {
+        let usage = UsageMeter::default();
+        let prompt = local(None).choose_prompt();
+        let constraints = RecipeConstraints::default();
+
+        let result = local(None)
+            .generate_recipe(
+                names(&["rice", "simulate:outage"]),
+                None,
+                constraints.clone(),
+                &prompt,
+                &usage,
+            )
+            .await;
+        assert!(matches!(result, Err(LlmError::Unavailable(_))));
+
+        let result = local(Some(SimulatedFailure::Timeout))
+            .generate_recipe(names(&["rice"]), None, constraints.clone(), &prompt, &usage)
+            .await;
+        assert!(matches!(result, Err(LlmError::Timeout(_))));
+        assert!(!usage.called_model());
+
+        let recipe = local(None)
+            .generate_recipe(names(&["rice"]), None, constraints, &prompt, &usage)
+            .await
+            .unwrap();
+        assert!(!recipe.title.is_empty());
+        assert!(usage.called_model());
+        assert_eq!(usage.output().unwrap().model, "local/stand-in");
+    }
+
+    #[tokio::test]
+    async fn streamed_parse_errors_break_off_halfway() {
+        let usage = UsageMeter::default();
+        let prompt = local(None).choose_prompt();
+        let mut text = String::new();
+
+        let result = local(None)
+            .stream_recipe(
+                names(&["rice", "simulate:parse_error"]),
+                None,
+                RecipeConstraints::default(),
+                &prompt,
+                &usage,
+                |chunk| text.push_str(chunk),
+            )
+            .await;
+
+        assert!(matches!(result, Err(LlmError::ParseError(_))));
+        assert!(text.starts_with("{\"title\""));
+        assert!(serde_json::from_str::<GeneratedRecipe>(&text).is_err());
+
+        let mut text = String::new();
+        let recipe = local(None)
+            .stream_recipe(
+                names(&["rice"]),
+                None,
+                RecipeConstraints::default(),
+                &prompt,
+                &usage,
+                |chunk| text.push_str(chunk),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<GeneratedRecipe>(&text)
+                .unwrap()
+                .title,
+            recipe.title
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::recipes::domain::{GeneratedRecipe, GenerationCache, GenerationKey};
+
+struct Entry {
+    recipes: Vec<GeneratedRecipe>,
+    expires_at: Instant,
+}
+
+/// Keeps the most recently used entries in this process, up to a fixed
+/// number of requests.
+pub(super) struct InMemoryGenerationCache {
+    entries: Mutex<LruCache<GenerationKey, Entry>>,
+    ttl: Duration,
+}
+
+impl InMemoryGenerationCache {
+    pub(super) fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+}
+
+impl GenerationCache for InMemoryGenerationCache {
+    async fn get(&self, key: &GenerationKey) -> Option<Vec<GeneratedRecipe>> {
+        let mut entries = self.entries.lock().expect("generation cache lock poisoned");
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.recipes.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn put(&self, key: GenerationKey, recipes: Vec<GeneratedRecipe>) {
+        let entry = Entry {
+            recipes,
+            expires_at: Instant::now() + self.ttl,
+        };
+        self.entries
+            .lock()
+            .expect("generation cache lock poisoned")
+            .put(key, entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipes::domain::RecipeConstraints;
+
+    fn key(ingredient: &str) -> GenerationKey {
+        GenerationKey::new(
+            "test",
+            &[ingredient.to_string()],
+            None,
+            &RecipeConstraints::default(),
+            1,
+        )
+    }
+
+    fn recipes(title: &str) -> Vec<GeneratedRecipe> {
+        vec![GeneratedRecipe {
+            title: title.to_string(),
+            ingredients: vec!["1 cup rice".to_string()],
+            instructions: vec!["Cook".to_string()],
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            servings: None,
+        }]
+    }
+
+    async fn cached_title(cache: &InMemoryGenerationCache, ingredient: &str) -> Option<String> {
+        cache
+            .get(&key(ingredient))
+            .await
+            .map(|recipes| recipes[0].title.clone())
+    }
+
+    #[tokio::test]
+    async fn evicts_the_least_recently_used_entry() {
+        let cache = InMemoryGenerationCache::new(2, Duration::from_secs(60));
+        cache.put(key("rice"), recipes("Rice")).await;
+        cache.put(key("eggs"), recipes("Eggs")).await;
+        cache.get(&key("rice")).await;
+        cache.put(key("beans"), recipes("Beans")).await;
+
+        assert_eq!(cached_title(&cache, "rice").await.as_deref(), Some("Rice"));
+        assert_eq!(cached_title(&cache, "eggs").await, None);
+        assert_eq!(
+            cached_title(&cache, "beans").await.as_deref(),
+            Some("Beans")
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_misses() {
+        let cache = InMemoryGenerationCache::new(2, Duration::ZERO);
+        cache.put(key("rice"), recipes("Rice")).await;
+
+        assert_eq!(cached_title(&cache, "rice").await, None);
+    }
+}
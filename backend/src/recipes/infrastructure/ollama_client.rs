@@ -0,0 +1,203 @@
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::llm_client::{ChatMessage, ChatModel};
+use super::llm_transport::{LlmTransport, StreamUpdate};
+use crate::recipes::domain::{LlmError, TokenUsage, UsageMeter};
+use crate::shared::config::LlmConfig;
+
+/// Client for Ollama's native chat API, which runs models locally.
+pub struct OllamaClient {
+    transport: LlmTransport,
+    url: String,
+    model: String,
+    temperature: Option<f32>,
+}
+
+impl OllamaClient {
+    pub fn new(config: &LlmConfig) -> Self {
+        Self {
+            transport: LlmTransport::new(config),
+            url: format!("{}/api/chat", config.base_url),
+            model: config.model.clone(),
+            temperature: config.temperature,
+        }
+    }
+
+    fn request(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        stream: bool,
+    ) -> RequestBuilder {
+        let request = ChatRequest {
+            model: &self.model,
+            messages,
+            stream,
+            format: schema,
+            options: Options {
+                temperature: self.temperature,
+                num_predict: max_tokens,
+            },
+        };
+
+        self.transport.post(&self.url).json(&request)
+    }
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    stream: bool,
+    /// Constrains the output to JSON matching this schema.
+    format: &'a Value,
+    options: Options,
+}
+
+#[derive(Serialize)]
+struct Options {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    num_predict: u32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ResponseMessage,
+    #[serde(flatten)]
+    counts: EvalCounts,
+}
+
+/// Token counts, sent with the whole reply or its last streamed line.
+/// Ollama leaves out the prompt's count when it was cached.
+#[derive(Deserialize, Default)]
+struct EvalCounts {
+    #[serde(default)]
+    prompt_eval_count: Option<i64>,
+    #[serde(default)]
+    eval_count: Option<i64>,
+}
+
+impl EvalCounts {
+    fn usage(&self) -> Option<TokenUsage> {
+        if self.prompt_eval_count.is_none() && self.eval_count.is_none() {
+            return None;
+        }
+        Some(TokenUsage {
+            prompt_tokens: self.prompt_eval_count.unwrap_or(0),
+            completion_tokens: self.eval_count.unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+/// One line of a streamed reply. Ollama streams newline-delimited JSON
+/// rather than server-sent events.
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    message: Option<ResponseMessage>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(flatten)]
+    counts: EvalCounts,
+}
+
+/// The text and token counts added by one line of a streamed reply.
+fn stream_update(line: &str) -> Result<StreamUpdate, LlmError> {
+    if line.is_empty() {
+        return Ok(StreamUpdate::default());
+    }
+
+    let chunk: ChatChunk =
+        serde_json::from_str(line).map_err(|e| LlmError::ParseError(e.to_string()))?;
+    if let Some(error) = chunk.error {
+        return Err(LlmError::ApiError(error));
+    }
+    Ok(StreamUpdate {
+        usage: chunk.counts.usage(),
+        text: chunk.message.map(|message| message.content),
+    })
+}
+
+impl ChatModel for OllamaClient {
+    async fn complete(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+    ) -> Result<String, LlmError> {
+        let response: ChatResponse = self
+            .transport
+            .send_json(self.request(messages, schema, max_tokens, false))
+            .await?;
+        usage.record(response.counts.usage().unwrap_or_default());
+
+        Ok(response.message.content)
+    }
+
+    async fn complete_streaming<F: FnMut(&str) + Send>(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+        mut on_text: F,
+    ) -> Result<String, LlmError> {
+        let mut content = String::new();
+        let mut tokens = TokenUsage::default();
+        self.transport
+            .send_lines(self.request(messages, schema, max_tokens, true), |line| {
+                let update = stream_update(line)?;
+                if let Some(delta) = update.text {
+                    on_text(&delta);
+                    content.push_str(&delta);
+                }
+                tokens += update.usage.unwrap_or_default();
+                Ok(())
+            })
+            .await?;
+        usage.record(tokens);
+
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_text_and_errors_from_stream_lines() {
+        let line =
+            r#"{"model":"llama3.1","message":{"role":"assistant","content":"{\""},"done":false}"#;
+
+        assert_eq!(stream_update(line).unwrap().text.as_deref(), Some("{\""));
+        assert_eq!(stream_update("").unwrap(), StreamUpdate::default());
+        assert!(matches!(
+            stream_update(r#"{"error":"model not found"}"#),
+            Err(LlmError::ApiError(message)) if message == "model not found"
+        ));
+    }
+
+    #[test]
+    fn reads_token_counts_from_the_last_stream_line() {
+        let line = r#"{"model":"llama3.1","message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":26,"eval_count":290}"#;
+
+        assert_eq!(
+            stream_update(line).unwrap().usage,
+            Some(TokenUsage {
+                prompt_tokens: 26,
+                completion_tokens: 290,
+            })
+        );
+    }
+}
@@ -1,77 +1,100 @@
-use crate::recipes::domain::{GeneratedRecipe, LlmError, LlmService};
-use reqwest::Client;
+use reqwest::RequestBuilder;
 use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 
-const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+use super::llm_client::{ChatMessage, ChatModel};
+use super::llm_transport::{LlmTransport, StreamUpdate, sse_data};
+use crate::recipes::domain::{LlmError, TokenUsage, UsageMeter};
+use crate::shared::config::LlmConfig;
 
+/// Client for OpenAI's chat completions API and the many servers that mimic
+/// it, such as llama.cpp's `llama-server`, vLLM or LM Studio.
 pub struct OpenAiClient {
-    client: Client,
-    api_key: String,
+    transport: LlmTransport,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+    temperature: Option<f32>,
 }
 
 impl OpenAiClient {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(config: &LlmConfig) -> Self {
         Self {
-            client: Client::new(),
-            api_key,
+            transport: LlmTransport::new(config),
+            url: format!("{}/chat/completions", config.base_url),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+            temperature: config.temperature,
         }
     }
 
-    /// Builds the LLM prompt for recipe generation.
-    /// Prompt design choices:
-    /// 1. **JSON-only response**: We explicitly request "valid JSON only, no markdown" to ensure
-    ///    the response can be directly parsed by serde_json without stripping markdown code fences.
-    /// 2. **Exact schema provided**: Including the full JSON structure as an example ensures
-    ///    consistent field names and types across all responses, making parsing reliable.
-    /// 3. **Dietary restrictions inline**: Placed directly in the prompt context so the model
-    ///    considers them when selecting ingredients and cooking methods.
-    /// 4. **Use only listed ingredients**: The model must use primarily the provided ingredients,
-    ///    with only basic kitchen staples (salt, pepper, herbs, sugar, etc.) allowed.
-    /// 5. **LLM determines times and servings**: The model should calculate realistic prep_time_minutes,
-    ///    cook_time_minutes, and servings based on the recipe complexity and yield.
-    fn build_prompt(ingredients: &[String], dietary_restrictions: &Option<Vec<String>>) -> String {
-        let restrictions = dietary_restrictions
-            .as_ref()
-            .map(|r| format!("Dietary restrictions: {}", r.join(", ")))
-            .unwrap_or_default();
-
-        format!(
-            r#"Generate a recipe using ONLY these ingredients: {}.
-You may also use basic kitchen staples that are commonly available such as salt, pepper, herbs, spices, sugar, oil, butter, or water. Do not add ingredients that are not in this list.
-
-{}
-IMPORTANT: Calculate realistic prep_time_minutes, cook_time_minutes, and servings based on the actual recipe you create. These should be integers that reflect the real complexity, cooking time, and yield of your recipe.
-
-Respond with valid JSON only, no markdown, in this exact format (the numbers shown are examples - calculate appropriate values for your recipe):
-{{
-  "title": "Recipe Name",
-  "ingredients": ["ingredient 1 with amount", "ingredient 2 with amount"],
-  "instructions": ["step 1", "step 2"],
-  "prep_time_minutes": 0,
-  "cook_time_minutes": 0,
-  "servings": 0
-}}"#,
-            ingredients.join(", "),
-            restrictions
-        )
-    }
-}
+    fn request(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        stream: bool,
+    ) -> RequestBuilder {
+        let request = ChatRequest {
+            model: &self.model,
+            messages,
+            temperature: self.temperature,
+            max_tokens,
+            stream,
+            stream_options: stream.then(|| json!({ "include_usage": true })),
+            response_format: json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "recipe",
+                    "strict": true,
+                    "schema": schema,
+                },
+            }),
+        };
 
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<Message>,
+        let builder = self.transport.post(&self.url).json(&request);
+        match &self.api_key {
+            Some(api_key) => builder.bearer_auth(api_key),
+            None => builder,
+        }
+    }
 }
 
 #[derive(Serialize)]
-struct Message {
-    role: String,
-    content: String,
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    max_tokens: u32,
+    stream: bool,
+    /// Asks for a final chunk reporting the tokens used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<Value>,
+    /// Structured output: the reply must match the recipe schema.
+    response_format: Value,
 }
 
 #[derive(Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    prompt_tokens: i64,
+    completion_tokens: i64,
+}
+
+impl From<Usage> for TokenUsage {
+    fn from(usage: Usage) -> Self {
+        TokenUsage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -84,45 +107,97 @@ struct ResponseMessage {
     content: String,
 }
 
-impl LlmService for OpenAiClient {
-    async fn generate_recipe(
-        &self,
-        ingredients: Vec<String>,
-        dietary_restrictions: Option<Vec<String>>,
-    ) -> Result<GeneratedRecipe, LlmError> {
-        let prompt = Self::build_prompt(&ingredients, &dietary_restrictions);
+#[derive(Deserialize)]
+struct ChatChunk {
+    choices: Vec<ChunkChoice>,
+    /// Only on the last chunk, which has no choices.
+    #[serde(default)]
+    usage: Option<Usage>,
+}
 
-        let request = ChatRequest {
-            model: "gpt-4o-mini".to_string(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt,
-            }],
-        };
+#[derive(Deserialize)]
+struct ChunkChoice {
+    delta: Delta,
+}
+
+#[derive(Deserialize)]
+struct Delta {
+    #[serde(default)]
+    content: Option<String>,
+}
 
-        let response = self
-            .client
-            .post(OPENAI_API_URL)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| LlmError::ApiError(e.to_string()))?;
-
-        let chat_response: ChatResponse = response
-            .json()
-            .await
-            .map_err(|e| LlmError::ApiError(e.to_string()))?;
-
-        let content = chat_response
+/// The text and token counts added by one line of a streamed completion.
+fn stream_update(line: &str) -> Result<StreamUpdate, LlmError> {
+    let Some(data) = sse_data(line) else {
+        return Ok(StreamUpdate::default());
+    };
+    if data == "[DONE]" {
+        return Ok(StreamUpdate::default());
+    }
+
+    let chunk: ChatChunk =
+        serde_json::from_str(data).map_err(|e| LlmError::ParseError(e.to_string()))?;
+    Ok(StreamUpdate {
+        text: chunk
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.delta.content),
+        usage: chunk.usage.map(TokenUsage::from),
+    })
+}
+
+impl ChatModel for OpenAiClient {
+    async fn complete(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+    ) -> Result<String, LlmError> {
+        let chat_response: ChatResponse = self
+            .transport
+            .send_json(self.request(messages, schema, max_tokens, false))
+            .await?;
+        usage.record(
+            chat_response
+                .usage
+                .map(TokenUsage::from)
+                .unwrap_or_default(),
+        );
+
+        chat_response
             .choices
-            .first()
-            .ok_or_else(|| LlmError::ParseError("No choices in response".to_string()))?
-            .message
-            .content
-            .clone();
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or_else(|| LlmError::ParseError("No choices in response".to_string()))
+    }
 
-        serde_json::from_str(&content).map_err(|e| LlmError::ParseError(e.to_string()))
+    async fn complete_streaming<F: FnMut(&str) + Send>(
+        &self,
+        messages: &[ChatMessage],
+        schema: &Value,
+        max_tokens: u32,
+        usage: &UsageMeter,
+        mut on_text: F,
+    ) -> Result<String, LlmError> {
+        let mut content = String::new();
+        let mut tokens = TokenUsage::default();
+        self.transport
+            .send_lines(self.request(messages, schema, max_tokens, true), |line| {
+                let update = stream_update(line)?;
+                if let Some(delta) = update.text {
+                    on_text(&delta);
+                    content.push_str(&delta);
+                }
+                tokens += update.usage.unwrap_or_default();
+                Ok(())
+            })
+            .await?;
+        usage.record(tokens);
+
+        Ok(content)
     }
 }
 
@@ -131,38 +206,31 @@ mod tests {
     use super::*;
 
     #[test]
-    fn build_prompt_includes_ingredients() {
-        let ingredients = vec!["chicken".to_string(), "rice".to_string()];
-        let prompt = OpenAiClient::build_prompt(&ingredients, &None);
-
-        assert!(prompt.contains("chicken, rice"));
-    }
-
-    #[test]
-    fn build_prompt_includes_dietary_restrictions() {
-        let ingredients = vec!["tofu".to_string()];
-        let restrictions = Some(vec!["vegan".to_string(), "gluten-free".to_string()]);
-        let prompt = OpenAiClient::build_prompt(&ingredients, &restrictions);
-
-        assert!(prompt.contains("Dietary restrictions: vegan, gluten-free"));
-    }
-
-    #[test]
-    fn build_prompt_omits_restrictions_when_none() {
-        let ingredients = vec!["beef".to_string()];
-        let prompt = OpenAiClient::build_prompt(&ingredients, &None);
-
-        assert!(!prompt.contains("Dietary restrictions"));
+    fn reads_text_from_stream_chunks() {
+        let line = r#"data: {"choices":[{"index":0,"delta":{"content":"{\"ti"}}]}"#;
+
+        assert_eq!(stream_update(line).unwrap().text.as_deref(), Some("{\"ti"));
+        assert_eq!(
+            stream_update(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#).unwrap(),
+            StreamUpdate::default()
+        );
+        assert_eq!(
+            stream_update("data: [DONE]").unwrap(),
+            StreamUpdate::default()
+        );
+        assert_eq!(stream_update("").unwrap(), StreamUpdate::default());
     }
 
     #[test]
-    fn build_prompt_requests_json_format() {
-        let ingredients = vec!["pasta".to_string()];
-        let prompt = OpenAiClient::build_prompt(&ingredients, &None);
-
-        assert!(prompt.contains("valid JSON"));
-        assert!(prompt.contains("\"title\""));
-        assert!(prompt.contains("\"ingredients\""));
-        assert!(prompt.contains("\"instructions\""));
+    fn reads_usage_from_the_last_stream_chunk() {
+        let line = r#"data: {"choices":[],"usage":{"prompt_tokens":120,"completion_tokens":340,"total_tokens":460}}"#;
+
+        assert_eq!(
+            stream_update(line).unwrap().usage,
+            Some(TokenUsage {
+                prompt_tokens: 120,
+                completion_tokens: 340,
+            })
+        );
     }
 }
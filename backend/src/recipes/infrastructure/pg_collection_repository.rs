@@ -0,0 +1,246 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::recipes::domain::{
+    Collection, CollectionRepository, CollectionShare, Recipe, RepositoryError,
+};
+
+pub struct PgCollectionRepository {
+    pool: PgPool,
+}
+
+impl PgCollectionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl CollectionRepository for PgCollectionRepository {
+    async fn save(&self, collection: &Collection) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "INSERT INTO collections (id, owner_id, name, created_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(collection.id)
+        .bind(&collection.owner_id)
+        .bind(&collection.name)
+        .bind(collection.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn rename(&self, collection: &Collection) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE collections SET name = $2 WHERE id = $1")
+            .bind(collection.id)
+            .bind(&collection.name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::CollectionNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepositoryError> {
+        let result = sqlx::query("DELETE FROM collections WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::CollectionNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Collection, RepositoryError> {
+        sqlx::query_as::<_, Collection>(
+            "SELECT id, owner_id, name, created_at FROM collections WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?
+        .ok_or(RepositoryError::CollectionNotFound)
+    }
+
+    async fn find_by_owner(&self, owner_id: &str) -> Result<Vec<Collection>, RepositoryError> {
+        sqlx::query_as::<_, Collection>(
+            "SELECT id, owner_id, name, created_at FROM collections WHERE owner_id = $1 ORDER BY lower(name), id",
+        )
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
+    }
+
+    async fn find_shared_with_user(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<Collection>, RepositoryError> {
+        sqlx::query_as::<_, Collection>(
+            r#"
+            SELECT c.id, c.owner_id, c.name, c.created_at
+            FROM collections c
+            INNER JOIN collection_shares cs ON c.id = cs.collection_id
+            WHERE cs.user_id = $1
+            ORDER BY cs.created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
+    }
+
+    async fn add_recipe(
+        &self,
+        collection_id: Uuid,
+        recipe_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO collection_recipes (collection_id, recipe_id)
+            VALUES ($1, $2)
+            ON CONFLICT (collection_id, recipe_id) DO NOTHING
+            "#,
+        )
+        .bind(collection_id)
+        .bind(recipe_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn remove_recipe(
+        &self,
+        collection_id: Uuid,
+        recipe_id: Uuid,
+    ) -> Result<(), RepositoryError> {
+        let result = sqlx::query(
+            "DELETE FROM collection_recipes WHERE collection_id = $1 AND recipe_id = $2",
+        )
+        .bind(collection_id)
+        .bind(recipe_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn find_recipes(
+        &self,
+        collection_id: Uuid,
+        viewer_id: &str,
+    ) -> Result<Vec<Recipe>, RepositoryError> {
+        // The owner's own recipes are visible to everyone the collection is
+        // shared with. Anything else in it, such as recipes shared with the
+        // owner, stays visible only to users it was shared with directly.
+        sqlx::query_as::<_, Recipe>(
+            r#"
+            SELECT r.id, r.owner_id, r.title, r.ingredients, r.parsed_ingredients, r.instructions, r.prep_time_minutes, r.cook_time_minutes, r.servings, r.tags, r.created_at
+            FROM collection_recipes cr
+            INNER JOIN collections c ON c.id = cr.collection_id
+            INNER JOIN recipes r ON r.id = cr.recipe_id
+            WHERE cr.collection_id = $1
+            AND (
+                r.owner_id = $2
+                OR EXISTS (
+                    SELECT 1 FROM recipe_shares rs WHERE rs.recipe_id = r.id AND rs.user_id = $2
+                )
+                OR (r.owner_id = c.owner_id AND EXISTS (
+                    SELECT 1 FROM collection_shares cs WHERE cs.collection_id = c.id AND cs.user_id = $2
+                ))
+            )
+            ORDER BY cr.added_at DESC, r.id
+            "#,
+        )
+        .bind(collection_id)
+        .bind(viewer_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
+    }
+
+    async fn create_share(&self, share: &CollectionShare) -> Result<(), RepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO collection_shares (collection_id, user_id, created_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (collection_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(share.collection_id)
+        .bind(&share.user_id)
+        .bind(share.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_share(
+        &self,
+        collection_id: Uuid,
+        user_id: &str,
+    ) -> Result<(), RepositoryError> {
+        let result =
+            sqlx::query("DELETE FROM collection_shares WHERE collection_id = $1 AND user_id = $2")
+                .bind(collection_id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn is_shared_with_user(
+        &self,
+        collection_id: Uuid,
+        user_id: &str,
+    ) -> Result<bool, RepositoryError> {
+        let (exists,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM collection_shares WHERE collection_id = $1 AND user_id = $2)",
+        )
+        .bind(collection_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(exists)
+    }
+
+    async fn find_shares(
+        &self,
+        collection_id: Uuid,
+    ) -> Result<Vec<CollectionShare>, RepositoryError> {
+        sqlx::query_as::<_, CollectionShare>(
+            "SELECT collection_id, user_id, created_at FROM collection_shares WHERE collection_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(collection_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
+    }
+}
@@ -0,0 +1,65 @@
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::recipes::domain::{GeneratedRecipe, GenerationCache, GenerationKey};
+
+/// Keeps entries in the `generation_cache` table, shared by every instance.
+/// Expired rows are ignored when read and removed whenever a new entry is
+/// stored.
+pub(super) struct PgGenerationCache {
+    pool: PgPool,
+    ttl: Duration,
+}
+
+impl PgGenerationCache {
+    pub(super) fn new(pool: PgPool, ttl: Duration) -> Self {
+        Self { pool, ttl }
+    }
+}
+
+impl GenerationCache for PgGenerationCache {
+    async fn get(&self, key: &GenerationKey) -> Option<Vec<GeneratedRecipe>> {
+        let (recipes,): (String,) = sqlx::query_as(
+            "SELECT recipes FROM generation_cache WHERE key = $1 AND expires_at > NOW()",
+        )
+        .bind(key.as_str())
+        .fetch_optional(&self.pool)
+        .await
+        .inspect_err(|e| tracing::warn!(error = %e, "Failed to read the generation cache"))
+        .ok()??;
+
+        serde_json::from_str(&recipes)
+            .inspect_err(
+                |e| tracing::warn!(error = %e, "Ignoring unreadable generation cache entry"),
+            )
+            .ok()
+    }
+
+    async fn put(&self, key: GenerationKey, recipes: Vec<GeneratedRecipe>) {
+        let recipes = serde_json::to_string(&recipes).expect("recipes serialize to JSON");
+        let ttl_secs = i64::try_from(self.ttl.as_secs()).unwrap_or(i64::MAX);
+
+        let result = sqlx::query(
+            r#"
+            WITH expired AS (
+                DELETE FROM generation_cache WHERE expires_at <= NOW()
+            )
+            INSERT INTO generation_cache (key, recipes, created_at, expires_at)
+            VALUES ($1, $2, NOW(), NOW() + make_interval(secs => $3))
+            ON CONFLICT (key) DO UPDATE
+            SET recipes = EXCLUDED.recipes,
+                created_at = EXCLUDED.created_at,
+                expires_at = EXCLUDED.expires_at
+            "#,
+        )
+        .bind(key.as_str())
+        .bind(recipes)
+        .bind(ttl_secs as f64)
+        .execute(&self.pool)
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Failed to store recipes in the generation cache");
+        }
+    }
+}
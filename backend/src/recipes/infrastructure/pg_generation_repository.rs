@@ -0,0 +1,128 @@
+use sqlx::PgPool;
+use sqlx::types::Json;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::recipes::domain::{
+    Generation, GenerationRepository, Page, PageRequest, RecipeCursor, RepositoryError, SortKey,
+};
+
+const GENERATION_COLUMNS: &str = "id, user_id, ingredients, dietary_restrictions, constraints, model, prompt_version, raw_output, recipes, latency_ms, created_at";
+
+/// Keeps generations in the `generations` table. Rows older than the
+/// retention period are ignored when read and removed whenever a new
+/// generation is stored; without a retention period they are kept forever.
+pub struct PgGenerationRepository {
+    pool: PgPool,
+    retention: Option<Duration>,
+}
+
+impl PgGenerationRepository {
+    pub fn new(pool: PgPool, retention: Option<Duration>) -> Self {
+        Self { pool, retention }
+    }
+
+    /// The retention period in seconds, as bound to queries; null keeps
+    /// everything.
+    fn retention_secs(&self) -> Option<f64> {
+        self.retention.map(|retention| retention.as_secs_f64())
+    }
+}
+
+/// Matches rows still within the retention period bound as `$n`.
+fn retained(n: u8) -> String {
+    format!("(${n}::DOUBLE PRECISION IS NULL OR created_at > NOW() - make_interval(secs => ${n}))")
+}
+
+impl GenerationRepository for PgGenerationRepository {
+    async fn save(&self, generation: &Generation) -> Result<(), RepositoryError> {
+        let sql = format!(
+            r#"
+            WITH expired AS (
+                DELETE FROM generations WHERE NOT {}
+            )
+            INSERT INTO generations ({GENERATION_COLUMNS})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            retained(12)
+        );
+        sqlx::query(&sql)
+            .bind(generation.id)
+            .bind(&generation.user_id)
+            .bind(&generation.ingredients)
+            .bind(&generation.dietary_restrictions)
+            .bind(Json(&generation.constraints))
+            .bind(&generation.model)
+            .bind(&generation.prompt_version)
+            .bind(&generation.raw_output)
+            .bind(Json(&generation.recipes))
+            .bind(generation.latency_ms)
+            .bind(generation.created_at)
+            .bind(self.retention_secs())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Generation, RepositoryError> {
+        let sql = format!(
+            "SELECT {GENERATION_COLUMNS} FROM generations WHERE id = $1 AND {}",
+            retained(2)
+        );
+        sqlx::query_as::<_, Generation>(&sql)
+            .bind(id)
+            .bind(self.retention_secs())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?
+            .ok_or(RepositoryError::GenerationNotFound)
+    }
+
+    async fn find_by_user(
+        &self,
+        user_id: &str,
+        page: &PageRequest,
+    ) -> Result<Page<Generation>, RepositoryError> {
+        let after = match page.after {
+            Some(_) => "AND (created_at, id) < ($4, $5)",
+            None => "",
+        };
+        let sql = format!(
+            "SELECT {GENERATION_COLUMNS} FROM generations WHERE user_id = $1 AND {} {after} ORDER BY created_at DESC, id DESC LIMIT $2",
+            retained(3)
+        );
+
+        // One extra row tells us whether there is a next page.
+        let mut query = sqlx::query_as::<_, Generation>(&sql)
+            .bind(user_id)
+            .bind(page.limit + 1)
+            .bind(self.retention_secs());
+        if let Some(cursor) = &page.after {
+            let SortKey::CreatedAt(at) = cursor.key else {
+                return Err(RepositoryError::DatabaseError(
+                    "generations are only sorted by creation time".to_string(),
+                ));
+            };
+            query = query.bind(at).bind(cursor.id);
+        }
+
+        let mut items = query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        let has_more = items.len() as i64 > page.limit;
+        items.truncate(page.limit as usize);
+        let next = match items.last() {
+            Some(last) if has_more => Some(RecipeCursor {
+                key: SortKey::CreatedAt(last.created_at),
+                id: last.id,
+            }),
+            _ => None,
+        };
+
+        Ok(Page { items, next })
+    }
+}
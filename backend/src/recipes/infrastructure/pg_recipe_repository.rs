@@ -1,7 +1,17 @@
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool, Postgres, Row, Transaction, postgres::PgRow, types::Json};
 use uuid::Uuid;
 
-use crate::recipes::domain::{Recipe, RecipeRepository, RepositoryError};
+use super::pg_recipe_share_repository::shared_at;
+use crate::recipes::domain::{
+    Page, PageRequest, Recipe, RecipeCursor, RecipeRepository, RecipeRevision, RecipeSearch,
+    RecipeSort, RepositoryError, SearchScope, SortKey,
+};
+
+const RECIPE_COLUMNS: &str = "r.id, r.owner_id, r.title, r.ingredients, r.parsed_ingredients, r.instructions, r.prep_time_minutes, r.cook_time_minutes, r.servings, r.tags, r.created_at";
+
+/// Prep + cook time, with recipes that state neither sorting after all others.
+const TOTAL_TIME_KEY: &str = "CASE WHEN r.prep_time_minutes IS NULL AND r.cook_time_minutes IS NULL THEN 2147483647 ELSE COALESCE(r.prep_time_minutes, 0) + COALESCE(r.cook_time_minutes, 0) END";
 
 pub struct PgRecipeRepository {
     pool: PgPool,
@@ -11,35 +21,240 @@ impl PgRecipeRepository {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    /// Keyset-paginated listing. `source` is the FROM/WHERE clause, with the
+    /// user id bound as `$1`; `created_at` is the column or expression
+    /// newest-first sorts on.
+    async fn find_page(
+        &self,
+        source: &str,
+        created_at: &str,
+        user_id: &str,
+        page: &PageRequest,
+    ) -> Result<Page<Recipe>, RepositoryError> {
+        let (key, direction, comparison) = match page.sort {
+            RecipeSort::CreatedAt => (created_at, "DESC", "<"),
+            RecipeSort::Title => ("lower(r.title)", "ASC", ">"),
+            RecipeSort::TotalTime => (TOTAL_TIME_KEY, "ASC", ">"),
+        };
+        let after = match page.after {
+            Some(_) => format!("AND ({key}, r.id) {comparison} ($3, $4)"),
+            None => String::new(),
+        };
+        let sql = format!(
+            "SELECT {RECIPE_COLUMNS}, {key} AS sort_key {source} {after} ORDER BY sort_key {direction}, r.id {direction} LIMIT $2"
+        );
+
+        // One extra row tells us whether there is a next page.
+        let mut query = sqlx::query(&sql).bind(user_id).bind(page.limit + 1);
+        if let Some(cursor) = &page.after {
+            query = match &cursor.key {
+                SortKey::CreatedAt(at) => query.bind(*at),
+                SortKey::Title(title) => query.bind(title.as_str()),
+                SortKey::TotalTime(minutes) => query.bind(*minutes),
+            }
+            .bind(cursor.id);
+        }
+
+        let mut rows = query
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        let has_more = rows.len() as i64 > page.limit;
+        rows.truncate(page.limit as usize);
+
+        let next = match rows.last() {
+            Some(row) if has_more => Some(
+                cursor_after(row, page.sort)
+                    .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?,
+            ),
+            _ => None,
+        };
+        let items = rows
+            .iter()
+            .map(Recipe::from_row)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(Page { items, next })
+    }
+}
+
+fn cursor_after(row: &PgRow, sort: RecipeSort) -> Result<RecipeCursor, sqlx::Error> {
+    let key = match sort {
+        RecipeSort::CreatedAt => SortKey::CreatedAt(row.try_get("sort_key")?),
+        RecipeSort::Title => SortKey::Title(row.try_get("sort_key")?),
+        RecipeSort::TotalTime => SortKey::TotalTime(row.try_get("sort_key")?),
+    };
+
+    Ok(RecipeCursor {
+        key,
+        id: row.try_get("id")?,
+    })
+}
+
+/// Case-insensitive Postgres pattern matching `term` as whole words, plurals
+/// included, so "egg" finds "eggs" but not "eggplant" and "nut" leaves out
+/// "nutmeg" and "coconut".
+fn ingredient_pattern(term: &str) -> String {
+    let mut escaped = String::with_capacity(term.len());
+    for c in term.chars() {
+        if r"\.^$|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    format!(r"\m{}(e?s)?\M", escaped)
+}
+
+async fn insert_revision(
+    tx: &mut Transaction<'_, Postgres>,
+    recipe: &Recipe,
+    revision: i32,
+    editor_id: &str,
+    created_at: DateTime<Utc>,
+) -> Result<(), RepositoryError> {
+    sqlx::query(
+        r#"
+        INSERT INTO recipe_revisions (recipe_id, revision, editor_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+    )
+    .bind(recipe.id)
+    .bind(revision)
+    .bind(editor_id)
+    .bind(&recipe.title)
+    .bind(&recipe.ingredients)
+    .bind(&recipe.instructions)
+    .bind(recipe.prep_time_minutes)
+    .bind(recipe.cook_time_minutes)
+    .bind(recipe.servings)
+    .bind(created_at)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+    Ok(())
 }
 
 impl RecipeRepository for PgRecipeRepository {
     async fn save(&self, recipe: &Recipe) -> Result<(), RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
         sqlx::query(
             r#"
-            INSERT INTO recipes (id, owner_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO recipes (id, owner_id, title, ingredients, parsed_ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, tags, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             "#,
         )
-        .bind(&recipe.id)
+        .bind(recipe.id)
         .bind(&recipe.owner_id)
         .bind(&recipe.title)
         .bind(&recipe.ingredients)
+        .bind(Json(&recipe.parsed_ingredients))
+        .bind(&recipe.instructions)
+        .bind(recipe.prep_time_minutes)
+        .bind(recipe.cook_time_minutes)
+        .bind(recipe.servings)
+        .bind(&recipe.tags)
+        .bind(recipe.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        insert_revision(&mut tx, recipe, 1, &recipe.owner_id, recipe.created_at).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
+    }
+
+    async fn update(&self, recipe: &Recipe, editor_id: &str) -> Result<(), RepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        // Lock the recipe row so concurrent edits get consecutive revision numbers.
+        sqlx::query("SELECT id FROM recipes WHERE id = $1 FOR UPDATE")
+            .bind(recipe.id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?
+            .ok_or(RepositoryError::NotFound)?;
+
+        sqlx::query(
+            r#"
+            UPDATE recipes
+            SET title = $2, ingredients = $3, parsed_ingredients = $4, instructions = $5, prep_time_minutes = $6, cook_time_minutes = $7, servings = $8
+            WHERE id = $1
+            "#,
+        )
+        .bind(recipe.id)
+        .bind(&recipe.title)
+        .bind(&recipe.ingredients)
+        .bind(Json(&recipe.parsed_ingredients))
         .bind(&recipe.instructions)
-        .bind(&recipe.prep_time_minutes)
-        .bind(&recipe.cook_time_minutes)
-        .bind(&recipe.servings)
-        .bind(&recipe.created_at)
-        .execute(&self.pool)
+        .bind(recipe.prep_time_minutes)
+        .bind(recipe.cook_time_minutes)
+        .bind(recipe.servings)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        let (latest,): (i32,) = sqlx::query_as(
+            "SELECT COALESCE(MAX(revision), 0) FROM recipe_revisions WHERE recipe_id = $1",
+        )
+        .bind(recipe.id)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
 
+        insert_revision(&mut tx, recipe, latest + 1, editor_id, Utc::now()).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), RepositoryError> {
+        let result = sqlx::query("DELETE FROM recipes WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn update_tags(&self, recipe: &Recipe) -> Result<(), RepositoryError> {
+        let result = sqlx::query("UPDATE recipes SET tags = $2 WHERE id = $1")
+            .bind(recipe.id)
+            .bind(&recipe.tags)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
         Ok(())
     }
 
     async fn find_by_id(&self, id: Uuid) -> Result<Recipe, RepositoryError> {
         sqlx::query_as::<_, Recipe>(
-            "SELECT id, owner_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at FROM recipes WHERE id = $1",
+            "SELECT id, owner_id, title, ingredients, parsed_ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, tags, created_at FROM recipes WHERE id = $1",
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -48,29 +263,129 @@ impl RecipeRepository for PgRecipeRepository {
         .ok_or(RepositoryError::NotFound)
     }
 
-    async fn find_by_owner(&self, owner_id: &str) -> Result<Vec<Recipe>, RepositoryError> {
-        sqlx::query_as::<_, Recipe>(
-            "SELECT id, owner_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at FROM recipes WHERE owner_id = $1 ORDER BY created_at DESC",
+    async fn find_by_owner(
+        &self,
+        owner_id: &str,
+        page: &PageRequest,
+    ) -> Result<Page<Recipe>, RepositoryError> {
+        self.find_page(
+            "FROM recipes r WHERE r.owner_id = $1",
+            "r.created_at",
+            owner_id,
+            page,
         )
-        .bind(owner_id)
-        .fetch_all(&self.pool)
         .await
-        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
     }
 
-    async fn find_shared_with_user(&self, user_id: &str) -> Result<Vec<Recipe>, RepositoryError> {
-        sqlx::query_as::<_, Recipe>(
+    async fn find_shared_with_user(
+        &self,
+        user_id: &str,
+        page: &PageRequest,
+    ) -> Result<Page<Recipe>, RepositoryError> {
+        let shared_at = shared_at("r.id", "$1");
+        self.find_page(
+            &format!("FROM recipes r WHERE {shared_at} IS NOT NULL"),
+            &shared_at,
+            user_id,
+            page,
+        )
+        .await
+    }
+
+    async fn search(&self, search: &RecipeSearch) -> Result<Vec<Recipe>, RepositoryError> {
+        let (owned, shared) = match search.scope {
+            SearchScope::All => (true, true),
+            SearchScope::Owned => (true, false),
+            SearchScope::Shared => (false, true),
+        };
+        let lowercase = |terms: &[String]| -> Vec<String> {
+            terms.iter().map(|t| t.trim().to_lowercase()).collect()
+        };
+        let patterns = |terms: &[String]| -> Vec<String> {
+            terms
+                .iter()
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .map(ingredient_pattern)
+                .collect()
+        };
+
+        let sql = format!(
             r#"
-            SELECT r.id, r.owner_id, r.title, r.ingredients, r.instructions, r.prep_time_minutes, r.cook_time_minutes, r.servings, r.created_at
+            SELECT {RECIPE_COLUMNS}
             FROM recipes r
-            INNER JOIN recipe_shares rs ON r.id = rs.recipe_id
-            WHERE rs.user_id = $1
-            ORDER BY rs.created_at DESC
+            WHERE (
+                ($3 AND r.owner_id = $1)
+                OR ($4 AND {shared_at} IS NOT NULL)
+            )
+            AND ($2 = '' OR r.search_vector @@ websearch_to_tsquery('english', $2))
+            AND ($5::INTEGER IS NULL OR (
+                (r.prep_time_minutes IS NOT NULL OR r.cook_time_minutes IS NOT NULL)
+                AND COALESCE(r.prep_time_minutes, 0) + COALESCE(r.cook_time_minutes, 0) <= $5
+            ))
+            AND NOT EXISTS (
+                SELECT 1 FROM unnest($6::TEXT[]) AS wanted(pattern)
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM unnest(r.ingredients) AS line
+                    WHERE line ~* wanted.pattern
+                )
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM unnest(r.ingredients) AS line, unnest($7::TEXT[]) AS unwanted(pattern)
+                WHERE line ~* unwanted.pattern
+            )
+            AND r.tags @> $9
+            ORDER BY
+                CASE WHEN $2 = '' THEN 0
+                     ELSE ts_rank_cd(r.search_vector, websearch_to_tsquery('english', $2))
+                END DESC,
+                r.created_at DESC
+            LIMIT $8
             "#,
+            shared_at = shared_at("r.id", "$1"),
+        );
+
+        sqlx::query_as::<_, Recipe>(&sql)
+            .bind(&search.user_id)
+            .bind(search.text.trim())
+            .bind(owned)
+            .bind(shared)
+            .bind(search.max_total_minutes)
+            .bind(patterns(&search.include_ingredients))
+            .bind(patterns(&search.exclude_ingredients))
+            .bind(search.limit)
+            .bind(lowercase(&search.tags))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
+    }
+
+    async fn find_revisions(
+        &self,
+        recipe_id: Uuid,
+    ) -> Result<Vec<RecipeRevision>, RepositoryError> {
+        sqlx::query_as::<_, RecipeRevision>(
+            "SELECT recipe_id, revision, editor_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at FROM recipe_revisions WHERE recipe_id = $1 ORDER BY revision DESC",
         )
-        .bind(user_id)
+        .bind(recipe_id)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| RepositoryError::DatabaseError(e.to_string()))
     }
+
+    async fn find_revision(
+        &self,
+        recipe_id: Uuid,
+        revision: i32,
+    ) -> Result<RecipeRevision, RepositoryError> {
+        sqlx::query_as::<_, RecipeRevision>(
+            "SELECT recipe_id, revision, editor_id, title, ingredients, instructions, prep_time_minutes, cook_time_minutes, servings, created_at FROM recipe_revisions WHERE recipe_id = $1 AND revision = $2",
+        )
+        .bind(recipe_id)
+        .bind(revision)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?
+        .ok_or(RepositoryError::RevisionNotFound)
+    }
 }
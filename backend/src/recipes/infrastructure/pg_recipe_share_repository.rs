@@ -13,6 +13,31 @@ impl PgRecipeShareRepository {
     }
 }
 
+/// SQL expression for when the recipe `recipe_id` was shared with `user_id`,
+/// directly or through a shared collection, or NULL if it was not. Both
+/// arguments are SQL, such as a column or a bind parameter.
+///
+/// A shared collection only grants access to the recipes its owner owns,
+/// not to recipes that were themselves shared with the owner.
+pub(super) fn shared_at(recipe_id: &str, user_id: &str) -> String {
+    format!(
+        r#"(
+            SELECT max(shared.at) FROM (
+                SELECT rs.created_at AS at
+                FROM recipe_shares rs
+                WHERE rs.recipe_id = {recipe_id} AND rs.user_id = {user_id}
+                UNION ALL
+                SELECT GREATEST(cs.created_at, cr.added_at)
+                FROM collection_recipes cr
+                INNER JOIN collections c ON c.id = cr.collection_id
+                INNER JOIN recipes owned ON owned.id = cr.recipe_id AND owned.owner_id = c.owner_id
+                INNER JOIN collection_shares cs ON cs.collection_id = c.id
+                WHERE cr.recipe_id = {recipe_id} AND cs.user_id = {user_id}
+            ) shared
+        )"#
+    )
+}
+
 impl RecipeShareRepository for PgRecipeShareRepository {
     async fn create(&self, share: &RecipeShare) -> Result<(), RepositoryError> {
         sqlx::query(
@@ -22,9 +47,9 @@ impl RecipeShareRepository for PgRecipeShareRepository {
             ON CONFLICT (recipe_id, user_id) DO NOTHING
             "#,
         )
-        .bind(&share.recipe_id)
+        .bind(share.recipe_id)
         .bind(&share.user_id)
-        .bind(&share.created_at)
+        .bind(share.created_at)
         .execute(&self.pool)
         .await
         .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
@@ -52,16 +77,15 @@ impl RecipeShareRepository for PgRecipeShareRepository {
         recipe_id: Uuid,
         user_id: &str,
     ) -> Result<bool, RepositoryError> {
-        let result: Option<(bool,)> = sqlx::query_as(
-            "SELECT EXISTS(SELECT 1 FROM recipe_shares WHERE recipe_id = $1 AND user_id = $2)",
-        )
-        .bind(recipe_id)
-        .bind(user_id)
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+        let (shared,): (bool,) =
+            sqlx::query_as(&format!("SELECT {} IS NOT NULL", shared_at("$1", "$2")))
+                .bind(recipe_id)
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
 
-        Ok(result.map(|(exists,)| exists).unwrap_or(false))
+        Ok(shared)
     }
 
     async fn find_by_recipe_id(
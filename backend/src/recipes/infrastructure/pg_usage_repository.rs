@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::recipes::domain::{
+    QuotaLimits, RepositoryError, Requester, TokenUsage, UsageRepository, UsageTotals,
+};
+
+pub struct PgUsageRepository {
+    pool: PgPool,
+}
+
+impl PgUsageRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl UsageRepository for PgUsageRepository {
+    async fn reserve(
+        &self,
+        requester: &Requester,
+        limits: QuotaLimits,
+        day: DateTime<Utc>,
+        month: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, RepositoryError> {
+        let subject = requester.subject();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        // Held until the transaction ends, so the count below cannot go stale
+        // before the new row is in.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+            .bind(&subject)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        let (today, this_month): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE created_at >= $2),
+                   COUNT(*) FILTER (WHERE created_at >= $3)
+            FROM generation_usage
+            WHERE subject = $1 AND created_at >= LEAST($2, $3)
+            "#,
+        )
+        .bind(&subject)
+        .bind(day)
+        .bind(month)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        let reached = |limit: Option<i64>, used: i64| limit.is_some_and(|limit| used >= limit);
+        if reached(limits.daily, today) || reached(limits.monthly, this_month) {
+            return Ok(None);
+        }
+
+        let (id,): (Uuid,) = sqlx::query_as(
+            r#"
+            INSERT INTO generation_usage (subject, prompt_tokens, completion_tokens)
+            VALUES ($1, 0, 0)
+            RETURNING id
+            "#,
+        )
+        .bind(&subject)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(Some(id))
+    }
+
+    async fn settle(&self, reservation: Uuid, tokens: TokenUsage) -> Result<(), RepositoryError> {
+        sqlx::query(
+            "UPDATE generation_usage SET prompt_tokens = $2, completion_tokens = $3 WHERE id = $1",
+        )
+        .bind(reservation)
+        .bind(tokens.prompt_tokens)
+        .bind(tokens.completion_tokens)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn release(&self, reservation: Uuid) -> Result<(), RepositoryError> {
+        sqlx::query("DELETE FROM generation_usage WHERE id = $1")
+            .bind(reservation)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn totals_since(
+        &self,
+        requester: &Requester,
+        since: DateTime<Utc>,
+    ) -> Result<UsageTotals, RepositoryError> {
+        let (generations, prompt_tokens, completion_tokens): (i64, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*),
+                   COALESCE(SUM(prompt_tokens), 0)::BIGINT,
+                   COALESCE(SUM(completion_tokens), 0)::BIGINT
+            FROM generation_usage
+            WHERE subject = $1 AND created_at >= $2
+            "#,
+        )
+        .bind(requester.subject())
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
+
+        Ok(UsageTotals {
+            generations,
+            tokens: TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+            },
+        })
+    }
+}
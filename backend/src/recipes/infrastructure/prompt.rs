@@ -0,0 +1,299 @@
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::recipes::domain::{
+    Difficulty, Equipment, GeneratedRecipe, RecipeConstraints, SuggestedSubstitute,
+};
+
+fn equipment_text(equipment: Equipment) -> &'static str {
+    match equipment {
+        Equipment::NoOven => "no oven is available",
+        Equipment::AirFryerOnly => "cook only in an air fryer",
+        Equipment::OnePot => "cook everything in a single pot or pan",
+    }
+}
+
+fn difficulty_text(difficulty: Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Easy => "easy, with simple techniques a beginner can follow",
+        Difficulty::Medium => "medium, for a cook with some experience",
+        Difficulty::Hard => "hard, a challenge for an experienced cook",
+    }
+}
+
+/// One prompt line for each constraint the user set.
+pub(super) fn constraint_lines(constraints: &RecipeConstraints) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(cuisine) = &constraints.cuisine {
+        lines.push(format!("Cuisine: {}", cuisine));
+    }
+    if let Some(minutes) = constraints.max_total_minutes {
+        lines.push(format!(
+            "Time limit: prep and cooking together must take at most {} minutes",
+            minutes
+        ));
+    }
+    if !constraints.equipment.is_empty() {
+        let equipment: Vec<&str> = constraints
+            .equipment
+            .iter()
+            .map(|e| equipment_text(*e))
+            .collect();
+        lines.push(format!("Equipment: {}", equipment.join("; ")));
+    }
+    if let Some(difficulty) = constraints.difficulty {
+        lines.push(format!("Difficulty: {}", difficulty_text(difficulty)));
+    }
+    if let Some(servings) = constraints.servings {
+        lines.push(format!("Servings: exactly {}", servings));
+    }
+    if !constraints.excluded_ingredients.is_empty() {
+        lines.push(format!(
+            "Do not use any of these, not even as staples: {}",
+            constraints.excluded_ingredients.join(", ")
+        ));
+    }
+    lines
+}
+
+/// JSON schema of `GeneratedRecipe`, for the providers' structured output
+/// modes. Every property is required and nothing else is allowed, as strict
+/// modes demand; the optional numbers may be `null`.
+pub(super) fn recipe_schema() -> Value {
+    let nullable_integer = json!({ "type": ["integer", "null"], "minimum": 0 });
+    json!({
+        "type": "object",
+        "properties": {
+            "title": { "type": "string" },
+            "ingredients": { "type": "array", "items": { "type": "string" } },
+            "instructions": { "type": "array", "items": { "type": "string" } },
+            "prep_time_minutes": nullable_integer,
+            "cook_time_minutes": nullable_integer,
+            "servings": nullable_integer,
+        },
+        "required": [
+            "title",
+            "ingredients",
+            "instructions",
+            "prep_time_minutes",
+            "cook_time_minutes",
+            "servings",
+        ],
+        "additionalProperties": false,
+    })
+}
+
+/// JSON schema of a reply to `PromptSet::recipes`.
+pub(super) fn recipes_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "recipes": { "type": "array", "items": recipe_schema() },
+        },
+        "required": ["recipes"],
+        "additionalProperties": false,
+    })
+}
+
+/// JSON schema of a reply to `PromptSet::substitutes`.
+pub(super) fn substitutes_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "substitutes": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "ingredients": { "type": "array", "items": { "type": "string" } },
+                        "note": { "type": ["string", "null"] },
+                    },
+                    "required": ["ingredients", "note"],
+                    "additionalProperties": false,
+                },
+            },
+        },
+        "required": ["substitutes"],
+        "additionalProperties": false,
+    })
+}
+
+/// The JSON object in a reply, without any markdown fence or prose the model
+/// put around it. Returns the whole reply when there is no complete object.
+pub(super) fn extract_json(content: &str) -> &str {
+    let Some(start) = content.find('{') else {
+        return content;
+    };
+
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, c) in content[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &content[start..=start + offset];
+                }
+            }
+            _ => {}
+        }
+    }
+    content
+}
+
+/// Parses the model's reply into a recipe, or describes why it is not one.
+pub(super) fn parse_recipe(content: &str) -> Result<GeneratedRecipe, String> {
+    serde_json::from_str(extract_json(content)).map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+struct RecipeList {
+    recipes: Vec<GeneratedRecipe>,
+}
+
+/// Parses a reply to `PromptSet::recipes`, or describes why it does not
+/// hold any recipes.
+pub(super) fn parse_recipes(content: &str) -> Result<Vec<GeneratedRecipe>, String> {
+    let list: RecipeList =
+        serde_json::from_str(extract_json(content)).map_err(|e| e.to_string())?;
+    if list.recipes.is_empty() {
+        return Err("`recipes` is empty".to_string());
+    }
+    Ok(list.recipes)
+}
+
+#[derive(Deserialize)]
+struct SubstituteList {
+    substitutes: Vec<SuggestedSubstitute>,
+}
+
+/// Parses a reply to `PromptSet::substitutes`. Substitutes without any
+/// ingredient are dropped; no substitutes at all is a valid answer.
+pub(super) fn parse_substitutes(content: &str) -> Result<Vec<SuggestedSubstitute>, String> {
+    let list: SubstituteList =
+        serde_json::from_str(extract_json(content)).map_err(|e| e.to_string())?;
+    Ok(list
+        .substitutes
+        .into_iter()
+        .filter(|s| s.ingredients.iter().any(|line| !line.trim().is_empty()))
+        .collect())
+}
+
+/// Follow-up asking the model to correct a reply that could not be parsed.
+pub(super) fn repair_prompt(error: &str) -> String {
+    format!(
+        "Your reply could not be used: {}.\n\
+         Reply again with only the corrected JSON object, in exactly the format requested, \
+         with no markdown and no other text.",
+        error
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_substitutes_drops_empty_ones() {
+        let reply = r#"{"substitutes": [{"ingredients": ["1 cup oat milk", "1 tbsp lemon juice"], "note": null}, {"ingredients": [" "], "note": "nothing"}]}"#;
+
+        let substitutes = parse_substitutes(reply).unwrap();
+        assert_eq!(substitutes.len(), 1);
+        assert_eq!(substitutes[0].ingredients[0], "1 cup oat milk");
+        assert!(parse_substitutes(r#"{"substitutes": [{"note": "x"}]}"#).is_err());
+    }
+
+    #[test]
+    fn schema_matches_generated_recipe_fields() {
+        let recipe = GeneratedRecipe {
+            title: String::new(),
+            ingredients: Vec::new(),
+            instructions: Vec::new(),
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            servings: None,
+        };
+        let fields = serde_json::to_value(recipe).unwrap();
+        let fields: Vec<&str> = fields
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+
+        let schema = recipe_schema();
+        let properties: Vec<&str> = schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        required.sort();
+
+        assert_eq!(properties, fields);
+        assert_eq!(required, fields);
+    }
+
+    #[test]
+    fn recipes_schema_lists_recipes() {
+        let schema = recipes_schema();
+
+        assert_eq!(schema["properties"]["recipes"]["items"], recipe_schema());
+        assert_eq!(schema["required"], json!(["recipes"]));
+    }
+
+    #[test]
+    fn parse_recipes_reads_the_list() {
+        let reply = r#"{"recipes": [{"title": "Omelette", "ingredients": [], "instructions": []}, {"title": "Frittata", "ingredients": [], "instructions": []}]}"#;
+
+        let titles: Vec<String> = parse_recipes(reply)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.title)
+            .collect();
+        assert_eq!(titles, vec!["Omelette", "Frittata"]);
+        assert_eq!(
+            parse_recipes(r#"{"recipes": []}"#).unwrap_err(),
+            "`recipes` is empty"
+        );
+    }
+
+    #[test]
+    fn extracts_json_from_fences_and_prose() {
+        let reply = "Here you go:\n```json\n{\"title\": \"A {curly} \\\"title\\\"\", \"n\": {\"x\": 1}}\n```\nEnjoy!";
+
+        assert_eq!(
+            extract_json(reply),
+            r#"{"title": "A {curly} \"title\"", "n": {"x": 1}}"#
+        );
+        assert_eq!(extract_json("no json here"), "no json here");
+        assert_eq!(extract_json("{\"cut\": "), "{\"cut\": ");
+    }
+
+    #[test]
+    fn parse_recipe_tolerates_surrounding_text() {
+        let reply =
+            "```json\n{\"title\": \"Soup\", \"ingredients\": [], \"instructions\": []}\n```";
+
+        assert_eq!(parse_recipe(reply).unwrap().title, "Soup");
+        assert!(parse_recipe("{\"title\": 1}").is_err());
+    }
+}
@@ -0,0 +1,629 @@
+use std::path::Path;
+
+use super::prompt::constraint_lines;
+use crate::recipes::domain::{GeneratedRecipe, RecipeConstraints};
+use crate::shared::config::PromptConfig;
+
+/// Versions of the templates built into the binary, from `prompts`. `v2`
+/// puts the user's text in delimited sections the model is told to treat
+/// as data only.
+const BUILTIN_VERSIONS: &[&str] = &["v1", "v2"];
+
+/// One piece of a template: literal text, or a `{{name}}` placeholder.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    /// `own_line` placeholders fill a whole line, which is left out when
+    /// the value is empty.
+    Placeholder {
+        name: String,
+        own_line: bool,
+    },
+}
+
+/// A prompt with `{{name}}` placeholders. Values are inserted as they are,
+/// so text in them that looks like a placeholder is never expanded.
+#[derive(Debug, Clone)]
+struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Reads `text`, rejecting placeholders other than `variables`. `{{`
+    /// not followed by a name and `}}` is kept as literal text.
+    fn parse(text: &str, variables: &[&str]) -> Result<Self, String> {
+        let text = text.trim_end_matches(['\n', '\r']);
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let name = after.find("}}").map(|end| &after[..end]).filter(|name| {
+                !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            });
+            let Some(name) = name else {
+                literal.push_str(&rest[..start + 2]);
+                rest = after;
+                continue;
+            };
+            if !variables.contains(&name) {
+                return Err(format!(
+                    "unknown placeholder {{{{{}}}}}; expected one of: {}",
+                    name,
+                    variables.join(", ")
+                ));
+            }
+
+            literal.push_str(&rest[..start]);
+            rest = &after[name.len() + 2..];
+            let own_line = (literal.is_empty() || literal.ends_with('\n'))
+                && (rest.is_empty() || rest.starts_with('\n'));
+            if own_line {
+                rest = rest.strip_prefix('\n').unwrap_or(rest);
+            }
+            if !literal.is_empty() {
+                segments.push(Segment::Text(std::mem::take(&mut literal)));
+            }
+            segments.push(Segment::Placeholder {
+                name: name.to_string(),
+                own_line,
+            });
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Text(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// The template with each placeholder replaced by its value in `values`.
+    fn render(&self, values: &[(&str, &str)]) -> String {
+        let mut prompt = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => prompt.push_str(text),
+                Segment::Placeholder { name, own_line } => {
+                    let value = values
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map_or("", |(_, value)| value);
+                    prompt.push_str(value);
+                    if *own_line && !value.is_empty() {
+                        prompt.push('\n');
+                    }
+                }
+            }
+        }
+        // A template ending in a dropped line would otherwise end in a newline.
+        prompt.truncate(prompt.trim_end_matches('\n').len());
+        prompt
+    }
+}
+
+const RECIPE_VARIABLES: &[&str] = &["ingredients", "dietary_restrictions", "constraints"];
+const RECIPES_VARIABLES: &[&str] = &[
+    "recipe_prompt",
+    "count",
+    "ingredients",
+    "dietary_restrictions",
+    "constraints",
+];
+const REFINE_VARIABLES: &[&str] = &["recipe", "instruction"];
+const SUBSTITUTES_VARIABLES: &[&str] =
+    &["title", "ingredients", "ingredient", "dietary_restrictions"];
+
+/// One version of every prompt the client sends. Each is a file in the
+/// version's directory: `recipe.txt`, `recipes.txt`, `refine.txt` and
+/// `substitutes.txt`.
+#[derive(Debug, Clone)]
+pub(super) struct PromptSet {
+    /// The directory name, recorded with each generation.
+    pub version: String,
+    recipe: Template,
+    recipes: Template,
+    refine: Template,
+    substitutes: Template,
+}
+
+impl PromptSet {
+    fn parse(version: &str, read: impl Fn(&str) -> Result<String, String>) -> Result<Self, String> {
+        let template = |file: &str, variables: &[&str]| {
+            Template::parse(&read(file)?, variables)
+                .map_err(|e| format!("prompt template {}/{}: {}", version, file, e))
+        };
+        Ok(Self {
+            version: version.to_string(),
+            recipe: template("recipe.txt", RECIPE_VARIABLES)?,
+            recipes: template("recipes.txt", RECIPES_VARIABLES)?,
+            refine: template("refine.txt", REFINE_VARIABLES)?,
+            substitutes: template("substitutes.txt", SUBSTITUTES_VARIABLES)?,
+        })
+    }
+
+    /// The templates of `version` compiled into the binary, if it is one of
+    /// `BUILTIN_VERSIONS`.
+    pub fn builtin(version: &str) -> Option<Self> {
+        let files = match version {
+            "v1" => [
+                include_str!("../../../prompts/v1/recipe.txt"),
+                include_str!("../../../prompts/v1/recipes.txt"),
+                include_str!("../../../prompts/v1/refine.txt"),
+                include_str!("../../../prompts/v1/substitutes.txt"),
+            ],
+            "v2" => [
+                include_str!("../../../prompts/v2/recipe.txt"),
+                include_str!("../../../prompts/v2/recipes.txt"),
+                include_str!("../../../prompts/v2/refine.txt"),
+                include_str!("../../../prompts/v2/substitutes.txt"),
+            ],
+            _ => return None,
+        };
+        let prompts = Self::parse(version, |file| {
+            let index = ["recipe.txt", "recipes.txt", "refine.txt", "substitutes.txt"]
+                .iter()
+                .position(|name| *name == file)
+                .expect("every template file is built in");
+            Ok(files[index].to_string())
+        });
+        Some(prompts.expect("built-in prompt templates are valid"))
+    }
+
+    /// The templates in `dir/version`.
+    pub fn load(dir: &Path, version: &str) -> Result<Self, String> {
+        let version_dir = dir.join(version);
+        Self::parse(version, |file| {
+            let path = version_dir.join(file);
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read prompt template {}: {}", path.display(), e))
+        })
+    }
+
+    /// Prompt for one recipe. Variables: `ingredients`, comma-separated;
+    /// `dietary_restrictions`, a `Dietary restrictions: ...` line or empty;
+    /// and `constraints`, one line per constraint the user set or empty.
+    ///
+    /// Design choices of the built-in prompt:
+    /// 1. **JSON-only response**: We explicitly request "valid JSON only, no markdown" to ensure
+    ///    the response can be directly parsed by serde_json without stripping markdown code fences.
+    /// 2. **Exact schema provided**: Including the full JSON structure as an example ensures
+    ///    consistent field names and types across all responses, making parsing reliable.
+    /// 3. **Dietary restrictions inline**: Placed directly in the prompt context so the model
+    ///    considers them when selecting ingredients and cooking methods.
+    /// 4. **Use only listed ingredients**: The model must use primarily the provided ingredients,
+    ///    with only basic kitchen staples (salt, pepper, herbs, sugar, etc.) allowed.
+    /// 5. **LLM determines times and servings**: The model should calculate realistic prep_time_minutes,
+    ///    cook_time_minutes, and servings based on the recipe complexity and yield.
+    /// 6. **Other constraints one per line**: Cuisine, time limit, equipment, difficulty, servings and
+    ///    excluded ingredients each get their own line next to the dietary restrictions, and only
+    ///    when asked for, so an unconstrained prompt stays short.
+    pub fn recipe(
+        &self,
+        ingredients: &[String],
+        dietary_restrictions: &Option<Vec<String>>,
+        constraints: &RecipeConstraints,
+    ) -> String {
+        self.recipe
+            .render(&recipe_values(ingredients, dietary_restrictions, constraints).each())
+    }
+
+    /// Prompt asking for `count` different recipes in one reply, shaped like
+    /// `recipes_schema`. Asking in one request lets the model see what it has
+    /// already suggested, which separate requests would not. Takes the
+    /// `recipe` variables, plus `count` and `recipe_prompt`, the whole
+    /// single-recipe prompt.
+    pub fn recipes(
+        &self,
+        ingredients: &[String],
+        dietary_restrictions: &Option<Vec<String>>,
+        constraints: &RecipeConstraints,
+        count: usize,
+    ) -> String {
+        let values = recipe_values(ingredients, dietary_restrictions, constraints);
+        let recipe_prompt = self.recipe.render(&values.each());
+        let count = count.to_string();
+        let mut values = values.each();
+        values.extend([("recipe_prompt", recipe_prompt.as_str()), ("count", &count)]);
+        self.recipes.render(&values)
+    }
+
+    /// Prompt asking for `recipe` to be revised as the user's `instruction`
+    /// asks. `recipe` is the recipe as JSON, which also sets the reply's
+    /// format.
+    pub fn refine(&self, recipe: &GeneratedRecipe, instruction: &str) -> String {
+        let recipe = serde_json::to_string_pretty(recipe).expect("recipes serialize to JSON");
+        self.refine
+            .render(&[("recipe", &recipe), ("instruction", instruction)])
+    }
+
+    /// Prompt asking for replacements for `ingredient`, one of `recipe`'s
+    /// ingredient lines. Variables: `title`, `ingredients` one per line,
+    /// `ingredient`, and `dietary_restrictions`, a sentence asking to respect
+    /// them or empty.
+    pub fn substitutes(
+        &self,
+        recipe: &GeneratedRecipe,
+        ingredient: &str,
+        dietary_restrictions: &Option<Vec<String>>,
+    ) -> String {
+        let restrictions = dietary_restrictions
+            .as_ref()
+            .map(|r| {
+                format!(
+                    "The substitutes must respect these dietary restrictions: {}",
+                    r.join(", ")
+                )
+            })
+            .unwrap_or_default();
+        self.substitutes.render(&[
+            ("title", &recipe.title),
+            ("ingredients", &recipe.ingredients.join("\n")),
+            ("ingredient", ingredient),
+            ("dietary_restrictions", &restrictions),
+        ])
+    }
+}
+
+/// Values of the `recipe` template's variables.
+struct RecipeValues {
+    ingredients: String,
+    dietary_restrictions: String,
+    constraints: String,
+}
+
+impl RecipeValues {
+    fn each(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("ingredients", &self.ingredients),
+            ("dietary_restrictions", &self.dietary_restrictions),
+            ("constraints", &self.constraints),
+        ]
+    }
+}
+
+fn recipe_values(
+    ingredients: &[String],
+    dietary_restrictions: &Option<Vec<String>>,
+    constraints: &RecipeConstraints,
+) -> RecipeValues {
+    RecipeValues {
+        ingredients: ingredients.join(", "),
+        dietary_restrictions: dietary_restrictions
+            .as_ref()
+            .map(|r| format!("Dietary restrictions: {}", r.join(", ")))
+            .unwrap_or_default(),
+        constraints: constraint_lines(constraints).join("\n"),
+    }
+}
+
+/// The prompt templates in use: a main version, and optionally a second
+/// version tried on a percentage of requests to compare the two.
+pub(super) struct PromptTemplates {
+    main: PromptSet,
+    experiment: Option<(PromptSet, u8)>,
+}
+
+impl PromptTemplates {
+    /// Loads the configured versions from the templates directory, or uses
+    /// the built-in templates when no directory is set.
+    pub fn from_config(config: &PromptConfig) -> Result<Self, String> {
+        let load = |version: &str| match &config.templates_dir {
+            Some(dir) => PromptSet::load(dir, version),
+            None => PromptSet::builtin(version).ok_or_else(|| {
+                format!(
+                    "prompt template {} needs PROMPT_TEMPLATES_DIR; only {} are built in",
+                    version,
+                    BUILTIN_VERSIONS.join(" and ")
+                )
+            }),
+        };
+
+        let main = load(&config.version)?;
+        let experiment = match &config.experiment {
+            Some(version) if config.experiment_percent > 0 => {
+                Some((load(version)?, config.experiment_percent))
+            }
+            _ => None,
+        };
+        Ok(Self { main, experiment })
+    }
+
+    /// The versions in use and their split, e.g. `v1` or `v1+v2@10`.
+    pub fn version(&self) -> String {
+        match &self.experiment {
+            Some((experiment, percent)) => {
+                format!("{}+{}@{}", self.main.version, experiment.version, percent)
+            }
+            None => self.main.version.clone(),
+        }
+    }
+
+    /// The templates of `version`, as picked for a request by
+    /// [`choose`](Self::choose), or the main ones if it is not in use.
+    pub fn get(&self, version: &str) -> &PromptSet {
+        self.experiment
+            .as_ref()
+            .map(|(experiment, _)| experiment)
+            .filter(|experiment| experiment.version == version)
+            .unwrap_or(&self.main)
+    }
+
+    /// The templates for one request, picked at random according to the split.
+    pub fn choose(&self) -> &PromptSet {
+        self.choose_with(rand::random_range(0..100))
+    }
+
+    /// The templates for a request that rolled `roll`, from 0 to 99.
+    fn choose_with(&self, roll: u8) -> &PromptSet {
+        match &self.experiment {
+            Some((experiment, percent)) if roll < *percent => experiment,
+            _ => &self.main,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipes::domain::{Difficulty, Equipment};
+
+    fn builtin() -> PromptSet {
+        PromptSet::builtin("v1").unwrap()
+    }
+
+    #[test]
+    fn recipe_prompt_includes_ingredients() {
+        let ingredients = vec!["chicken".to_string(), "rice".to_string()];
+        let prompt = builtin().recipe(&ingredients, &None, &RecipeConstraints::default());
+
+        assert!(prompt.contains("chicken, rice"));
+    }
+
+    #[test]
+    fn recipe_prompt_includes_dietary_restrictions() {
+        let ingredients = vec!["tofu".to_string()];
+        let restrictions = Some(vec!["vegan".to_string(), "gluten-free".to_string()]);
+        let prompt = builtin().recipe(&ingredients, &restrictions, &RecipeConstraints::default());
+
+        assert!(prompt.contains("Dietary restrictions: vegan, gluten-free\n"));
+    }
+
+    #[test]
+    fn recipe_prompt_omits_restrictions_when_none() {
+        let ingredients = vec!["beef".to_string()];
+        let prompt = builtin().recipe(&ingredients, &None, &RecipeConstraints::default());
+
+        assert!(!prompt.contains("Dietary restrictions"));
+        assert!(prompt.contains("not in this list.\n\nIMPORTANT"));
+    }
+
+    #[test]
+    fn recipe_prompt_includes_requested_constraints() {
+        let ingredients = vec!["chicken".to_string()];
+        let constraints = RecipeConstraints {
+            cuisine: Some("Thai".to_string()),
+            max_total_minutes: Some(30),
+            equipment: vec![Equipment::NoOven, Equipment::OnePot],
+            difficulty: Some(Difficulty::Easy),
+            servings: Some(2),
+            excluded_ingredients: vec!["peanuts".to_string(), "cilantro".to_string()],
+        };
+        let prompt = builtin().recipe(&ingredients, &None, &constraints);
+
+        assert!(prompt.contains("Cuisine: Thai"));
+        assert!(prompt.contains("must take at most 30 minutes"));
+        assert!(
+            prompt.contains(
+                "Equipment: no oven is available; cook everything in a single pot or pan"
+            )
+        );
+        assert!(prompt.contains("Difficulty: easy"));
+        assert!(prompt.contains("Servings: exactly 2"));
+        assert!(prompt.contains("not even as staples: peanuts, cilantro"));
+    }
+
+    #[test]
+    fn recipe_prompt_omits_unset_constraints() {
+        let ingredients = vec!["beef".to_string()];
+        let prompt = builtin().recipe(&ingredients, &None, &RecipeConstraints::default());
+
+        for label in [
+            "Cuisine",
+            "Time limit",
+            "Equipment",
+            "Difficulty",
+            "Servings:",
+        ] {
+            assert!(!prompt.contains(label), "{}", label);
+        }
+    }
+
+    #[test]
+    fn recipe_prompt_requests_json_format() {
+        let ingredients = vec!["pasta".to_string()];
+        let prompt = builtin().recipe(&ingredients, &None, &RecipeConstraints::default());
+
+        assert!(prompt.contains("valid JSON"));
+        assert!(prompt.contains("\"title\""));
+        assert!(prompt.contains("\"ingredients\""));
+        assert!(prompt.contains("\"instructions\""));
+        assert!(prompt.ends_with('}'));
+    }
+
+    #[test]
+    fn refine_prompt_shows_recipe_and_instruction() {
+        let recipe = GeneratedRecipe {
+            title: "Tomato Soup".to_string(),
+            ingredients: vec!["4 tomatoes".to_string()],
+            instructions: vec!["Simmer".to_string()],
+            prep_time_minutes: Some(5),
+            cook_time_minutes: Some(20),
+            servings: Some(2),
+        };
+        let prompt = builtin().refine(&recipe, "make it spicier");
+
+        assert!(prompt.contains(r#""title": "Tomato Soup""#));
+        assert!(prompt.contains(
+            r#""ingredients": [
+    "4 tomatoes"
+  ]"#
+        ));
+        assert!(prompt.contains("Revise it as follows: make it spicier"));
+        assert!(prompt.contains("valid JSON"));
+    }
+
+    #[test]
+    fn substitutes_prompt_names_the_ingredient_and_restrictions() {
+        let recipe = GeneratedRecipe {
+            title: "Pancakes".to_string(),
+            ingredients: vec!["1 cup flour".to_string(), "1 cup buttermilk".to_string()],
+            instructions: vec!["Fry".to_string()],
+            prep_time_minutes: None,
+            cook_time_minutes: None,
+            servings: None,
+        };
+        let prompt = builtin().substitutes(
+            &recipe,
+            "1 cup buttermilk",
+            &Some(vec!["vegan".to_string()]),
+        );
+
+        assert!(prompt.contains("1 cup flour\n1 cup buttermilk"));
+        assert!(prompt.contains(r#"substitutes for "1 cup buttermilk""#));
+        assert!(prompt.contains("dietary restrictions: vegan\nRespond"));
+        assert!(
+            !builtin()
+                .substitutes(&recipe, "1 cup flour", &None)
+                .contains("dietary")
+        );
+    }
+
+    #[test]
+    fn recipes_prompt_asks_for_distinct_recipes() {
+        let ingredients = vec!["eggs".to_string()];
+        let prompts = builtin();
+        let prompt = prompts.recipes(&ingredients, &None, &RecipeConstraints::default(), 3);
+
+        assert!(prompt.starts_with(&prompts.recipe(
+            &ingredients,
+            &None,
+            &RecipeConstraints::default()
+        )));
+        assert!(prompt.contains("3 clearly different recipes"));
+        assert!(prompt.contains(r#"{"recipes": [...]}"#));
+    }
+
+    #[test]
+    fn v2_prompts_keep_user_text_in_data_sections() {
+        let prompts = PromptSet::builtin("v2").unwrap();
+        let ingredients = vec!["tofu".to_string(), "rice".to_string()];
+        let restrictions = Some(vec!["vegan".to_string()]);
+        let prompt = prompts.recipe(&ingredients, &restrictions, &RecipeConstraints::default());
+
+        assert!(prompt.contains("<ingredients>\ntofu, rice\n</ingredients>"));
+        assert!(prompt.contains("<requirements>\nDietary restrictions: vegan\n</requirements>"));
+        assert!(prompt.contains("never as instructions"));
+        assert!(PromptSet::builtin("v3").is_none());
+    }
+
+    #[test]
+    fn values_are_not_expanded_as_placeholders() {
+        let template = Template::parse("Make {{instruction}} now", REFINE_VARIABLES).unwrap();
+
+        assert_eq!(
+            template.render(&[("instruction", "{{recipe}}")]),
+            "Make {{recipe}} now"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_rejected_and_other_braces_kept() {
+        let error = Template::parse("Use {{ingredient}}", RECIPE_VARIABLES).unwrap_err();
+        assert!(error.contains("unknown placeholder {{ingredient}}"));
+
+        let template = Template::parse(r#"{{"a": {{count}}}}"#, RECIPES_VARIABLES).unwrap();
+        assert_eq!(template.render(&[("count", "2")]), r#"{{"a": 2}}"#);
+    }
+
+    #[test]
+    fn empty_placeholders_on_their_own_line_are_left_out() {
+        let template =
+            Template::parse("A\n{{count}}\nB {{recipe_prompt}}\n", RECIPES_VARIABLES).unwrap();
+
+        assert_eq!(template.render(&[]), "A\nB ");
+        assert_eq!(
+            template.render(&[("count", "2"), ("recipe_prompt", "x")]),
+            "A\n2\nB x"
+        );
+    }
+
+    #[test]
+    fn experiments_get_their_share_of_requests() {
+        let mut experiment = builtin();
+        experiment.version = "v2".to_string();
+        let templates = PromptTemplates {
+            main: builtin(),
+            experiment: Some((experiment, 10)),
+        };
+
+        assert_eq!(templates.version(), "v1+v2@10");
+        assert_eq!(templates.choose_with(0).version, "v2");
+        assert_eq!(templates.choose_with(9).version, "v2");
+        assert_eq!(templates.choose_with(10).version, "v1");
+        assert_eq!(templates.choose_with(99).version, "v1");
+    }
+
+    #[test]
+    fn finds_the_templates_picked_for_a_request() {
+        let mut experiment = builtin();
+        experiment.version = "v2".to_string();
+        let templates = PromptTemplates {
+            main: builtin(),
+            experiment: Some((experiment, 50)),
+        };
+
+        for _ in 0..20 {
+            let picked = templates.choose().version.clone();
+            assert_eq!(templates.get(&picked).version, picked);
+        }
+        assert_eq!(templates.get("v1").version, "v1");
+        assert_eq!(templates.get("v3").version, "v1");
+    }
+
+    #[test]
+    fn loads_versions_from_the_templates_directory() {
+        let dir = std::env::temp_dir().join(format!("prompts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("v2")).unwrap();
+        for file in ["recipes.txt", "refine.txt", "substitutes.txt"] {
+            std::fs::write(dir.join("v2").join(file), "{{title}}").unwrap();
+        }
+        std::fs::write(dir.join("v2/recipe.txt"), "Cook {{ingredients}}.").unwrap();
+        let config = PromptConfig {
+            templates_dir: Some(dir.clone()),
+            version: "v2".to_string(),
+            experiment: None,
+            experiment_percent: 0,
+        };
+
+        let error = PromptTemplates::from_config(&config).err().unwrap();
+        assert!(error.contains("v2/recipes.txt"), "{}", error);
+
+        std::fs::write(dir.join("v2/recipes.txt"), "{{recipe_prompt}} x{{count}}").unwrap();
+        std::fs::write(dir.join("v2/refine.txt"), "{{recipe}}").unwrap();
+        std::fs::write(dir.join("v2/substitutes.txt"), "{{title}}").unwrap();
+        let templates = PromptTemplates::from_config(&config).unwrap();
+        let prompt = templates.choose().recipes(
+            &["eggs".to_string()],
+            &None,
+            &RecipeConstraints::default(),
+            2,
+        );
+        assert_eq!(prompt, "Cook eggs. x2");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,8 +1,11 @@
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRequestParts, Request},
     http::{StatusCode, request::Parts},
+    middleware::Next,
+    response::Response,
 };
-use clerk_rs::validators::authorizer::ClerkJwt;
+use clerk_rs::validators::authorizer::{ClerkAuthorizer, ClerkJwt};
+use clerk_rs::validators::axum::AxumClerkRequest;
 use serde::Deserialize;
 use std::sync::OnceLock;
 
@@ -14,6 +17,7 @@ pub use clerk_rs::{
 
 static CLERK_CLIENT: OnceLock<Clerk> = OnceLock::new();
 static CLERK_SECRET_KEY: OnceLock<String> = OnceLock::new();
+static CLERK_AUTHORIZER: OnceLock<ClerkAuthorizer<MemoryCacheJwksProvider>> = OnceLock::new();
 
 pub fn init_clerk(secret_key: String) {
     CLERK_SECRET_KEY.set(secret_key.clone()).ok();
@@ -96,6 +100,24 @@ pub fn create_clerk_layer() -> ClerkLayer<MemoryCacheJwksProvider> {
     ClerkLayer::new(MemoryCacheJwksProvider::new(clerk.clone()), None, true)
 }
 
+/// Recognizes signed-in users on routes open to everyone. A valid session is
+/// made available to `AuthenticatedUser` as on protected routes; requests
+/// without one go through anonymously instead of being rejected.
+pub async fn identify_user(mut request: Request, next: Next) -> Response {
+    let authorizer = CLERK_AUTHORIZER.get_or_init(|| {
+        let clerk = get_clerk().expect("Clerk not initialized");
+        ClerkAuthorizer::new(MemoryCacheJwksProvider::new(clerk.clone()), true)
+    });
+
+    if let Ok(jwt) = authorizer
+        .authorize(&AxumClerkRequest::from_axum_request(&request))
+        .await
+    {
+        request.extensions_mut().insert(jwt);
+    }
+    next.run(request).await
+}
+
 #[derive(Debug, Clone)]
 pub struct AuthenticatedUser {
     pub user_id: String,
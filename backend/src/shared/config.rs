@@ -1,11 +1,358 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProvider {
+    /// OpenAI or any server exposing an OpenAI-compatible chat completions
+    /// API, such as llama.cpp's `llama-server` or vLLM.
+    OpenAi,
+    Anthropic,
+    Ollama,
+    /// A built-in stand-in that makes up recipes offline.
+    Local,
+}
+
+impl LlmProvider {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "openai" => Some(Self::OpenAi),
+            "anthropic" => Some(Self::Anthropic),
+            "ollama" => Some(Self::Ollama),
+            "local" => Some(Self::Local),
+            _ => None,
+        }
+    }
+
+    fn default_base_url(self) -> &'static str {
+        match self {
+            Self::OpenAi => "https://api.openai.com/v1",
+            Self::Anthropic => "https://api.anthropic.com/v1",
+            Self::Ollama => "http://localhost:11434",
+            Self::Local => "",
+        }
+    }
+
+    fn default_model(self) -> &'static str {
+        match self {
+            Self::OpenAi => "gpt-4o-mini",
+            Self::Anthropic => "claude-3-5-haiku-latest",
+            Self::Ollama => "llama3.1",
+            Self::Local => "stand-in",
+        }
+    }
+}
+
+pub struct LlmConfig {
+    pub provider: LlmProvider,
+    /// Base URL without the endpoint path, e.g. `http://localhost:8080/v1`.
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    /// Provider default when unset.
+    pub temperature: Option<f32>,
+    /// Per recipe; replies holding several get this much for each.
+    pub max_tokens: u32,
+    /// Limit on each attempt; for streamed replies, on the wait for each chunk.
+    pub timeout: Duration,
+    /// Extra attempts after a timeout, rate limit or server error.
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for each one after.
+    pub retry_base_delay: Duration,
+    /// Longest wait before a retry. A `Retry-After` beyond it is not waited for.
+    pub max_retry_delay: Duration,
+    /// Consecutive failed calls that open the circuit breaker.
+    pub breaker_threshold: u32,
+    /// How long an open circuit breaker fails calls without trying upstream.
+    pub breaker_cooldown: Duration,
+    /// Times the model is shown why its reply was not a valid recipe and
+    /// asked to correct it.
+    pub max_repairs: u32,
+    pub prompts: PromptConfig,
+    pub local: LocalLlmConfig,
+}
+
+impl LlmConfig {
+    fn from_env() -> Self {
+        let provider = non_empty_var("LLM_PROVIDER")
+            .map(|p| {
+                LlmProvider::parse(&p)
+                    .expect("LLM_PROVIDER must be openai, anthropic, ollama or local")
+            })
+            .unwrap_or(LlmProvider::OpenAi);
+        let base_url = non_empty_var("LLM_BASE_URL");
+
+        let api_key = non_empty_var("LLM_API_KEY").or_else(|| match provider {
+            LlmProvider::OpenAi => non_empty_var("OPENAI_API_KEY"),
+            LlmProvider::Anthropic => non_empty_var("ANTHROPIC_API_KEY"),
+            LlmProvider::Ollama | LlmProvider::Local => None,
+        });
+        // Hosted APIs need a key; self-hosted servers usually do not.
+        if api_key.is_none() && base_url.is_none() {
+            match provider {
+                LlmProvider::OpenAi => panic!("OPENAI_API_KEY must be set"),
+                LlmProvider::Anthropic => panic!("ANTHROPIC_API_KEY must be set"),
+                LlmProvider::Ollama | LlmProvider::Local => {}
+            }
+        }
+
+        Self {
+            provider,
+            base_url: base_url
+                .unwrap_or_else(|| provider.default_base_url().to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            api_key,
+            model: non_empty_var("LLM_MODEL")
+                .unwrap_or_else(|| provider.default_model().to_string()),
+            temperature: non_empty_var("LLM_TEMPERATURE")
+                .map(|t| t.parse().expect("LLM_TEMPERATURE must be a number")),
+            max_tokens: non_empty_var("LLM_MAX_TOKENS")
+                .map(|t| {
+                    t.parse()
+                        .expect("LLM_MAX_TOKENS must be a positive integer")
+                })
+                .unwrap_or(1024),
+            timeout: Duration::from_secs(number_var("LLM_TIMEOUT_SECS", 60)),
+            max_retries: number_var("LLM_MAX_RETRIES", 2),
+            retry_base_delay: Duration::from_millis(number_var("LLM_RETRY_BASE_DELAY_MS", 500)),
+            max_retry_delay: Duration::from_secs(number_var("LLM_MAX_RETRY_DELAY_SECS", 30)),
+            breaker_threshold: number_var("LLM_BREAKER_THRESHOLD", 5),
+            breaker_cooldown: Duration::from_secs(number_var("LLM_BREAKER_COOLDOWN_SECS", 30)),
+            max_repairs: number_var("LLM_MAX_REPAIRS", 2),
+            prompts: PromptConfig::from_env(),
+            local: LocalLlmConfig::from_env(),
+        }
+    }
+}
+
+/// Which prompt templates the client sends. Each version is a directory of
+/// templates; a second version can be tried on a share of requests.
+pub struct PromptConfig {
+    /// Directory holding one subdirectory per version. The built-in
+    /// templates are used when unset.
+    pub templates_dir: Option<PathBuf>,
+    pub version: String,
+    /// Version compared against `version` on some requests.
+    pub experiment: Option<String>,
+    /// Percentage of requests, 0 to 100, that use `experiment`.
+    pub experiment_percent: u8,
+}
+
+impl PromptConfig {
+    fn from_env() -> Self {
+        let experiment_percent = number_var("PROMPT_TEMPLATE_B_PERCENT", 0);
+        assert!(
+            experiment_percent <= 100,
+            "PROMPT_TEMPLATE_B_PERCENT must be between 0 and 100"
+        );
+        Self {
+            templates_dir: non_empty_var("PROMPT_TEMPLATES_DIR").map(PathBuf::from),
+            version: non_empty_var("PROMPT_TEMPLATE").unwrap_or_else(|| "v2".to_string()),
+            experiment: non_empty_var("PROMPT_TEMPLATE_B"),
+            experiment_percent,
+        }
+    }
+}
+
+/// A failure the local stand-in can be made to simulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatedFailure {
+    /// A reply that cannot be read as a recipe.
+    ParseError,
+    Outage,
+    Timeout,
+    RateLimit,
+}
+
+impl SimulatedFailure {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "parse_error" => Some(Self::ParseError),
+            "outage" => Some(Self::Outage),
+            "timeout" => Some(Self::Timeout),
+            "rate_limit" => Some(Self::RateLimit),
+            _ => None,
+        }
+    }
+}
+
+/// How the `local` provider behaves.
+pub struct LocalLlmConfig {
+    /// Delay before each reply, spread over the pieces of streamed ones.
+    pub latency: Duration,
+    /// Failure every call ends in, if any.
+    pub failure: Option<SimulatedFailure>,
+}
+
+impl LocalLlmConfig {
+    fn from_env() -> Self {
+        Self {
+            latency: Duration::from_millis(number_var("LOCAL_LLM_LATENCY_MS", 0)),
+            failure: non_empty_var("LOCAL_LLM_FAILURE")
+                .filter(|f| f.trim().to_lowercase() != "none")
+                .map(|f| {
+                    SimulatedFailure::parse(&f).expect(
+                        "LOCAL_LLM_FAILURE must be none, parse_error, outage, timeout or rate_limit",
+                    )
+                }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationCacheBackend {
+    Off,
+    /// Least recently used entries in this process, lost on restart.
+    Memory,
+    /// A table shared by every instance, surviving restarts.
+    Postgres,
+}
+
+impl GenerationCacheBackend {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "memory" => Some(Self::Memory),
+            "postgres" => Some(Self::Postgres),
+            _ => None,
+        }
+    }
+}
+
+pub struct GenerationCacheConfig {
+    pub backend: GenerationCacheBackend,
+    /// How long generated recipes are reused for identical requests.
+    pub ttl: Duration,
+    /// Most requests the memory backend remembers.
+    pub capacity: usize,
+}
+
+impl GenerationCacheConfig {
+    fn from_env() -> Self {
+        Self {
+            backend: non_empty_var("GENERATION_CACHE")
+                .map(|b| {
+                    GenerationCacheBackend::parse(&b)
+                        .expect("GENERATION_CACHE must be off, memory or postgres")
+                })
+                .unwrap_or(GenerationCacheBackend::Off),
+            ttl: Duration::from_secs(number_var("GENERATION_CACHE_TTL_SECS", 3600)),
+            capacity: number_var("GENERATION_CACHE_CAPACITY", 1000),
+        }
+    }
+}
+
+/// How many generations each requester may make per UTC day and month, 0
+/// meaning unlimited, and what the provider charges for tokens.
+pub struct UsageConfig {
+    pub user_daily_quota: u32,
+    pub user_monthly_quota: u32,
+    pub anonymous_daily_quota: u32,
+    pub anonymous_monthly_quota: u32,
+    /// US dollars per million prompt tokens.
+    pub prompt_price: f64,
+    /// US dollars per million completion tokens.
+    pub completion_price: f64,
+}
+
+impl UsageConfig {
+    fn from_env() -> Self {
+        Self {
+            user_daily_quota: number_var("GENERATION_QUOTA_DAILY", 50),
+            user_monthly_quota: number_var("GENERATION_QUOTA_MONTHLY", 1000),
+            anonymous_daily_quota: number_var("ANONYMOUS_GENERATION_QUOTA_DAILY", 5),
+            anonymous_monthly_quota: number_var("ANONYMOUS_GENERATION_QUOTA_MONTHLY", 50),
+            prompt_price: price_var("LLM_PROMPT_PRICE_PER_MILLION"),
+            completion_price: price_var("LLM_COMPLETION_PRICE_PER_MILLION"),
+        }
+    }
+}
+
+fn price_var(name: &str) -> f64 {
+    non_empty_var(name)
+        .map(|p| {
+            p.trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a number", name))
+        })
+        .unwrap_or(0.0)
+}
+
+/// A proxy whose forwarding headers are believed: one address, such as
+/// `10.0.0.5`, or a network, such as `172.16.0.0/12`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedProxy {
+    network: IpAddr,
+    prefix_len: u32,
+}
+
+impl TrustedProxy {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (address, prefix_len) = match value.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len.parse().ok()?)),
+            None => (value, None),
+        };
+        let network: IpAddr = address.parse().ok()?;
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = prefix_len.unwrap_or(bits);
+        (prefix_len <= bits).then_some(Self {
+            network,
+            prefix_len,
+        })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix_len).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix_len).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn number_var<T: FromStr>(name: &str, default: T) -> T {
+    non_empty_var(name)
+        .map(|v| {
+            v.trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a non-negative integer", name))
+        })
+        .unwrap_or(default)
+}
+
 pub struct AppConfig {
     pub port: u16,
-    pub openai_api_key: String,
+    pub llm: LlmConfig,
+    /// Most recipes one generation request may ask for, to bound its cost.
+    pub max_recipe_candidates: usize,
+    pub generation_cache: GenerationCacheConfig,
+    pub usage: UsageConfig,
+    /// How long signed-in users' generations are kept; `None` keeps them
+    /// forever.
+    pub generation_retention: Option<Duration>,
     pub cors_origin: String,
     pub database_url: String,
     pub clerk_secret_key: String,
     pub rate_limit_requests: u32,
     pub rate_limit_duration_secs: u64,
+    /// Proxies whose `X-Forwarded-For` and `X-Real-IP` headers name the
+    /// client. Requests from anyone else are told apart by their own address.
+    pub trusted_proxies: Vec<TrustedProxy>,
+    /// Key used to sign page cursors. A random key is used when unset.
+    pub cursor_secret: Option<String>,
 }
 
 impl AppConfig {
@@ -23,7 +370,14 @@ impl AppConfig {
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .expect("PORT must be a number"),
-            openai_api_key: std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY must be set"),
+            llm: LlmConfig::from_env(),
+            max_recipe_candidates: number_var("MAX_RECIPE_CANDIDATES", 3),
+            generation_cache: GenerationCacheConfig::from_env(),
+            usage: UsageConfig::from_env(),
+            generation_retention: match number_var("GENERATION_HISTORY_RETENTION_DAYS", 30) {
+                0 => None,
+                days => Some(Duration::from_secs(days * 24 * 60 * 60)),
+            },
             cors_origin: std::env::var("CORS_ORIGIN")
                 .unwrap_or_else(|_| "http://localhost:5173".to_string()),
             database_url,
@@ -37,6 +391,19 @@ impl AppConfig {
                 .unwrap_or_else(|_| "60".to_string())
                 .parse()
                 .unwrap_or(60),
+            trusted_proxies: non_empty_var("TRUSTED_PROXIES")
+                .map(|proxies| {
+                    proxies
+                        .split(',')
+                        .map(|proxy| {
+                            TrustedProxy::parse(proxy).expect(
+                                "TRUSTED_PROXIES must be a comma-separated list of IP addresses or networks",
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            cursor_secret: non_empty_var("CURSOR_SECRET"),
         }
     }
 }
@@ -1,10 +1,12 @@
 use axum::Router;
 
 use crate::shared::config::AppConfig;
+use crate::shared::middleware::client_ip::apply_client_ip;
 use crate::shared::middleware::cors::apply_cors;
 use crate::shared::middleware::rate_limit::{apply_rate_limit, create_rate_limiter};
 use crate::shared::middleware::tracing::apply_tracing;
 
+pub mod client_ip;
 pub mod cors;
 pub mod rate_limit;
 pub mod tracing;
@@ -17,6 +19,7 @@ where
         create_rate_limiter(config.rate_limit_requests, config.rate_limit_duration_secs);
 
     let router = apply_rate_limit(router, rate_limiter);
+    let router = apply_client_ip(router, &config.trusted_proxies);
     let router = apply_tracing(router);
     apply_cors(router, config)
 }
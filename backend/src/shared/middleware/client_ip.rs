@@ -0,0 +1,146 @@
+use axum::Router;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{Extensions, HeaderMap};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use crate::shared::config::TrustedProxy;
+
+/// The address a request came from, as resolved by [`apply_client_ip`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// The client's address: the one resolved by [`apply_client_ip`], or else
+/// the peer's. `None` when the server does not record peer addresses.
+pub fn client_ip(extensions: &Extensions) -> Option<IpAddr> {
+    if let Some(ClientIp(ip)) = extensions.get::<ClientIp>() {
+        return Some(*ip);
+    }
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// The client behind `peer`. That is `peer` itself unless it is a trusted
+/// proxy, in which case it is the nearest address in `X-Forwarded-For`, or
+/// else `X-Real-IP`, that is not a trusted proxy too. Headers from anyone
+/// else are ignored, as clients can put anything in them.
+fn resolve(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[TrustedProxy]) -> IpAddr {
+    let trusted = |ip: IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+    if !trusted(peer) {
+        return peer;
+    }
+
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+    if forwarded.is_empty() {
+        return headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(peer);
+    }
+
+    // Each proxy appends the address it got the request from, so the
+    // addresses are read from the nearest one back.
+    let mut client = peer;
+    for hop in forwarded.iter().rev() {
+        let Ok(ip) = hop.parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !trusted(ip) {
+            break;
+        }
+    }
+    client
+}
+
+async fn client_ip_middleware(
+    State(trusted_proxies): State<Arc<[TrustedProxy]>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if let Some(ConnectInfo(peer)) = req.extensions().get::<ConnectInfo<SocketAddr>>().copied() {
+        let client = resolve(peer.ip(), req.headers(), &trusted_proxies);
+        req.extensions_mut().insert(ClientIp(client));
+    }
+    next.run(req).await
+}
+
+/// Records the address each request came from as a [`ClientIp`], believing
+/// forwarding headers only from `trusted_proxies`.
+pub fn apply_client_ip<S>(router: Router<S>, trusted_proxies: &[TrustedProxy]) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(axum::middleware::from_fn_with_state(
+        Arc::from(trusted_proxies),
+        client_ip_middleware,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn ignores_forwarding_headers_from_untrusted_peers() {
+        let spoofed = headers(&[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "5.6.7.8")]);
+
+        assert_eq!(resolve(ip("203.0.113.9"), &spoofed, &[]), ip("203.0.113.9"));
+        assert_eq!(
+            resolve(
+                ip("203.0.113.9"),
+                &spoofed,
+                &[TrustedProxy::parse("10.0.0.0/8").unwrap()]
+            ),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn reads_the_nearest_untrusted_hop_behind_trusted_proxies() {
+        let trusted = [
+            TrustedProxy::parse("10.0.0.0/8").unwrap(),
+            TrustedProxy::parse("192.0.2.1").unwrap(),
+        ];
+        let forwarded = headers(&[("x-forwarded-for", "1.2.3.4, 198.51.100.7, 192.0.2.1")]);
+
+        assert_eq!(
+            resolve(ip("10.1.2.3"), &forwarded, &trusted),
+            ip("198.51.100.7")
+        );
+        assert_eq!(
+            resolve(
+                ip("10.1.2.3"),
+                &headers(&[("x-real-ip", "198.51.100.7")]),
+                &trusted
+            ),
+            ip("198.51.100.7")
+        );
+        assert_eq!(
+            resolve(ip("10.1.2.3"), &headers(&[]), &trusted),
+            ip("10.1.2.3")
+        );
+    }
+}
@@ -1,12 +1,12 @@
-use axum::extract::ConnectInfo;
-use axum::http::{HeaderMap, StatusCode};
+use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Router;
 use dashmap::DashMap;
-use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use crate::shared::middleware::client_ip::client_ip;
+
 #[derive(Clone)]
 pub struct RateLimiter {
     requests: Arc<DashMap<String, Vec<Instant>>>,
@@ -27,10 +27,7 @@ impl RateLimiter {
         let now = Instant::now();
         let window = Duration::from_secs(self.window_seconds);
 
-        let mut requests = self
-            .requests
-            .entry(key.to_string())
-            .or_insert_with(Vec::new);
+        let mut requests = self.requests.entry(key.to_string()).or_default();
 
         requests.retain(|&timestamp| now.duration_since(timestamp) < window);
 
@@ -42,31 +39,6 @@ impl RateLimiter {
 
         Ok(())
     }
-
-    fn get_client_ip(
-        headers: &HeaderMap,
-        connect_info: Option<&ConnectInfo<SocketAddr>>,
-    ) -> String {
-        if let Some(forwarded_for) = headers.get("x-forwarded-for") {
-            if let Ok(ip_str) = forwarded_for.to_str() {
-                if let Some(first_ip) = ip_str.split(',').next() {
-                    return first_ip.trim().to_string();
-                }
-            }
-        }
-
-        if let Some(real_ip) = headers.get("x-real-ip") {
-            if let Ok(ip_str) = real_ip.to_str() {
-                return ip_str.to_string();
-            }
-        }
-
-        if let Some(ConnectInfo(addr)) = connect_info {
-            return addr.ip().to_string();
-        }
-
-        "unknown".to_string()
-    }
 }
 
 async fn rate_limit_middleware(
@@ -74,8 +46,8 @@ async fn rate_limit_middleware(
     req: axum::extract::Request,
     next: axum::middleware::Next,
 ) -> Response {
-    let connect_info = req.extensions().get::<ConnectInfo<SocketAddr>>().cloned();
-    let client_ip = RateLimiter::get_client_ip(req.headers(), connect_info.as_ref());
+    let client_ip =
+        client_ip(req.extensions()).map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
 
     match rate_limiter.check_rate_limit(&client_ip) {
         Ok(_) => next.run(req).await,
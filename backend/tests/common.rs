@@ -1,13 +1,27 @@
 use std::sync::Arc;
 
 use axum::Router;
-use backend::recipes::adapters::create_router;
+use backend::recipes::adapters::{CursorCodec, create_router};
 use backend::recipes::application::{
-    CreateShareUseCase, DeleteShareUseCase, GenerateRecipeUseCase, GetRecipeUseCase,
-    ListOwnedRecipesUseCase, ListRecipeSharesUseCase, ListSharedRecipesUseCase, SaveRecipeUseCase,
+    AddRecipeToCollectionUseCase, CreateCollectionShareUseCase, CreateCollectionUseCase,
+    CreateShareUseCase, DeleteCollectionShareUseCase, DeleteCollectionUseCase, DeleteRecipeUseCase,
+    DeleteShareUseCase, DiffRecipeRevisionsUseCase, GenerateRecipeCandidatesUseCase,
+    GenerateRecipeUseCase, GetCollectionUseCase, GetGenerationUseCase, GetRecipeRevisionUseCase,
+    GetRecipeUseCase, GetUsageUseCase, ListCollectionSharesUseCase, ListCollectionsUseCase,
+    ListGenerationsUseCase, ListOwnedRecipesUseCase, ListRecipeRevisionsUseCase,
+    ListRecipeSharesUseCase, ListSharedCollectionsUseCase, ListSharedRecipesUseCase,
+    RecordGenerationUseCase, RecordUsageUseCase, RefineRecipeUseCase,
+    RemoveRecipeFromCollectionUseCase, RenameCollectionUseCase, RestoreRecipeRevisionUseCase,
+    SaveRecipeUseCase, SearchRecipesUseCase, SetRecipeTagsUseCase, StreamRecipeUseCase,
+    SuggestSubstitutesUseCase, UpdateRecipeUseCase,
+};
+use backend::recipes::domain::{QuotaPolicy, TokenPrices};
+use backend::recipes::infrastructure::{
+    GenerationCacheStore, PgCollectionRepository, PgGenerationRepository, PgRecipeRepository,
+    PgRecipeShareRepository, PgUsageRepository,
 };
-use backend::recipes::infrastructure::{PgRecipeRepository, PgRecipeShareRepository};
 use backend::shared::auth::init_clerk;
+use backend::shared::config::{GenerationCacheBackend, GenerationCacheConfig};
 use backend::shared::db::create_pool;
 use tower::ServiceBuilder;
 use tower_http::cors::CorsLayer;
@@ -15,20 +29,78 @@ use tower_http::cors::CorsLayer;
 pub struct MockLlmClient;
 
 impl backend::recipes::domain::LlmService for MockLlmClient {
+    fn version(&self) -> String {
+        "mock".to_string()
+    }
+
     async fn generate_recipe(
         &self,
-        _ingredients: Vec<String>,
+        ingredients: Vec<String>,
         _dietary_restrictions: Option<Vec<String>>,
+        constraints: backend::recipes::domain::RecipeConstraints,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
     ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
         Ok(backend::recipes::domain::GeneratedRecipe {
             title: "Mock Recipe".to_string(),
-            ingredients: vec!["mock ingredient".to_string()],
+            ingredients,
             instructions: vec!["mock instruction".to_string()],
             prep_time_minutes: Some(10),
             cook_time_minutes: Some(20),
-            servings: Some(4),
+            servings: constraints.servings.or(Some(4)),
         })
     }
+
+    async fn refine_recipe(
+        &self,
+        mut recipe: backend::recipes::domain::GeneratedRecipe,
+        instruction: String,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
+        recipe.title = format!("{} (refined)", recipe.title);
+        recipe.instructions.push(instruction);
+        Ok(recipe)
+    }
+
+    async fn suggest_substitutes(
+        &self,
+        _recipe: backend::recipes::domain::GeneratedRecipe,
+        ingredient: String,
+        _dietary_restrictions: Option<Vec<String>>,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<
+        Vec<backend::recipes::domain::SuggestedSubstitute>,
+        backend::recipes::domain::LlmError,
+    > {
+        Ok(vec![backend::recipes::domain::SuggestedSubstitute {
+            ingredients: vec![format!("mock substitute for {}", ingredient)],
+            note: None,
+        }])
+    }
+
+    async fn generate_recipes(
+        &self,
+        ingredients: Vec<String>,
+        _dietary_restrictions: Option<Vec<String>>,
+        constraints: backend::recipes::domain::RecipeConstraints,
+        count: usize,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<Vec<backend::recipes::domain::GeneratedRecipe>, backend::recipes::domain::LlmError>
+    {
+        Ok((1..=count)
+            .map(|n| backend::recipes::domain::GeneratedRecipe {
+                title: format!("Mock Recipe {}", n),
+                ingredients: ingredients.iter().map(|i| format!("{} {}", n, i)).collect(),
+                instructions: vec!["mock instruction".to_string()],
+                prep_time_minutes: Some(10),
+                cook_time_minutes: Some(20),
+                servings: constraints.servings.or(Some(4)),
+            })
+            .collect())
+    }
 }
 
 pub struct FailingLlmClient {
@@ -36,21 +108,165 @@ pub struct FailingLlmClient {
 }
 
 impl backend::recipes::domain::LlmService for FailingLlmClient {
+    fn version(&self) -> String {
+        "failing".to_string()
+    }
+
     async fn generate_recipe(
         &self,
         _ingredients: Vec<String>,
         _dietary_restrictions: Option<Vec<String>>,
+        _constraints: backend::recipes::domain::RecipeConstraints,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
+        Err(self.error.clone())
+    }
+
+    async fn refine_recipe(
+        &self,
+        _recipe: backend::recipes::domain::GeneratedRecipe,
+        _instruction: String,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
     ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
         Err(self.error.clone())
     }
+
+    async fn suggest_substitutes(
+        &self,
+        _recipe: backend::recipes::domain::GeneratedRecipe,
+        _ingredient: String,
+        _dietary_restrictions: Option<Vec<String>>,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<
+        Vec<backend::recipes::domain::SuggestedSubstitute>,
+        backend::recipes::domain::LlmError,
+    > {
+        Err(self.error.clone())
+    }
+}
+
+pub struct FixedLlmClient {
+    pub recipe: backend::recipes::domain::GeneratedRecipe,
+}
+
+impl backend::recipes::domain::LlmService for FixedLlmClient {
+    fn version(&self) -> String {
+        "fixed".to_string()
+    }
+
+    async fn generate_recipe(
+        &self,
+        _ingredients: Vec<String>,
+        _dietary_restrictions: Option<Vec<String>>,
+        _constraints: backend::recipes::domain::RecipeConstraints,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
+        Ok(self.recipe.clone())
+    }
+
+    async fn refine_recipe(
+        &self,
+        _recipe: backend::recipes::domain::GeneratedRecipe,
+        _instruction: String,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
+        Ok(self.recipe.clone())
+    }
+
+    async fn suggest_substitutes(
+        &self,
+        _recipe: backend::recipes::domain::GeneratedRecipe,
+        _ingredient: String,
+        _dietary_restrictions: Option<Vec<String>>,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<
+        Vec<backend::recipes::domain::SuggestedSubstitute>,
+        backend::recipes::domain::LlmError,
+    > {
+        Ok(Vec::new())
+    }
+}
+
+/// Numbers the recipes it generates, so tests can tell a new generation
+/// from a cached one, and reports 150 tokens for each.
+#[derive(Default)]
+pub struct CountingLlmClient {
+    pub calls: std::sync::atomic::AtomicUsize,
+}
+
+impl backend::recipes::domain::LlmService for CountingLlmClient {
+    fn version(&self) -> String {
+        "counting".to_string()
+    }
+
+    async fn generate_recipe(
+        &self,
+        ingredients: Vec<String>,
+        _dietary_restrictions: Option<Vec<String>>,
+        _constraints: backend::recipes::domain::RecipeConstraints,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
+        let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        usage.record(backend::recipes::domain::TokenUsage {
+            prompt_tokens: 100,
+            completion_tokens: 50,
+        });
+        Ok(backend::recipes::domain::GeneratedRecipe {
+            title: format!("Recipe {}", call),
+            ingredients,
+            instructions: vec!["mock instruction".to_string()],
+            prep_time_minutes: Some(10),
+            cook_time_minutes: Some(20),
+            servings: Some(4),
+        })
+    }
+
+    async fn refine_recipe(
+        &self,
+        recipe: backend::recipes::domain::GeneratedRecipe,
+        _instruction: String,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<backend::recipes::domain::GeneratedRecipe, backend::recipes::domain::LlmError> {
+        Ok(recipe)
+    }
+
+    async fn suggest_substitutes(
+        &self,
+        _recipe: backend::recipes::domain::GeneratedRecipe,
+        _ingredient: String,
+        _dietary_restrictions: Option<Vec<String>>,
+        _prompt: &backend::recipes::domain::PromptChoice,
+        _usage: &backend::recipes::domain::UsageMeter,
+    ) -> Result<
+        Vec<backend::recipes::domain::SuggestedSubstitute>,
+        backend::recipes::domain::LlmError,
+    > {
+        Ok(Vec::new())
+    }
 }
 
 pub async fn create_test_app() -> Router {
     create_test_app_with_llm(Arc::new(MockLlmClient)).await
 }
 
+/// A test app without generation quotas, as tests share one database.
 pub async fn create_test_app_with_llm<T: backend::recipes::domain::LlmService + 'static>(
     llm_client: Arc<T>,
+) -> Router {
+    create_test_app_with_quotas(llm_client, QuotaPolicy::default()).await
+}
+
+pub async fn create_test_app_with_quotas<T: backend::recipes::domain::LlmService + 'static>(
+    llm_client: Arc<T>,
+    quota_policy: QuotaPolicy,
 ) -> Router {
     init_clerk("sk_test_dummy_key_for_testing".to_string());
 
@@ -60,23 +276,108 @@ pub async fn create_test_app_with_llm<T: backend::recipes::domain::LlmService +
 
     let db_pool = create_pool(&database_url).await;
     let recipe_repository = Arc::new(PgRecipeRepository::new(db_pool.clone()));
-    let share_repository = Arc::new(PgRecipeShareRepository::new(db_pool));
+    let share_repository = Arc::new(PgRecipeShareRepository::new(db_pool.clone()));
+    let collection_repository = Arc::new(PgCollectionRepository::new(db_pool.clone()));
+    let usage_repository = Arc::new(PgUsageRepository::new(db_pool.clone()));
+    let generation_repository = Arc::new(PgGenerationRepository::new(
+        db_pool.clone(),
+        Some(std::time::Duration::from_secs(30 * 24 * 60 * 60)),
+    ));
+    let generation_cache = Arc::new(GenerationCacheStore::from_config(
+        &GenerationCacheConfig {
+            backend: GenerationCacheBackend::Memory,
+            ttl: std::time::Duration::from_secs(60),
+            capacity: 100,
+        },
+        db_pool,
+    ));
 
-    let generate_use_case = Arc::new(GenerateRecipeUseCase::new(llm_client));
+    let generate_use_case = Arc::new(GenerateRecipeUseCase::new(
+        llm_client.clone(),
+        generation_cache.clone(),
+    ));
+    let stream_use_case = Arc::new(StreamRecipeUseCase::new(
+        llm_client.clone(),
+        generation_cache.clone(),
+    ));
+    let candidates_use_case = Arc::new(GenerateRecipeCandidatesUseCase::new(
+        llm_client.clone(),
+        generation_cache,
+        3,
+    ));
+    let refine_use_case = Arc::new(RefineRecipeUseCase::new(llm_client.clone()));
+    let substitutes_use_case = Arc::new(SuggestSubstitutesUseCase::new(llm_client));
+    let usage_use_case = Arc::new(GetUsageUseCase::new(
+        usage_repository.clone(),
+        quota_policy,
+        TokenPrices {
+            prompt_per_million: 0.15,
+            completion_per_million: 0.6,
+        },
+    ));
+    let record_usage_use_case = Arc::new(RecordUsageUseCase::new(usage_repository, quota_policy));
+    let record_generation_use_case =
+        Arc::new(RecordGenerationUseCase::new(generation_repository.clone()));
+    let list_generations_use_case =
+        Arc::new(ListGenerationsUseCase::new(generation_repository.clone()));
+    let get_generation_use_case = Arc::new(GetGenerationUseCase::new(generation_repository));
     let save_use_case = Arc::new(SaveRecipeUseCase::new(recipe_repository.clone()));
     let get_use_case = Arc::new(GetRecipeUseCase::new(
         recipe_repository.clone(),
         share_repository.clone(),
     ));
+    let update_use_case = Arc::new(UpdateRecipeUseCase::new(recipe_repository.clone()));
+    let set_tags_use_case = Arc::new(SetRecipeTagsUseCase::new(recipe_repository.clone()));
+    let delete_use_case = Arc::new(DeleteRecipeUseCase::new(recipe_repository.clone()));
+    let list_revisions_use_case =
+        Arc::new(ListRecipeRevisionsUseCase::new(recipe_repository.clone()));
+    let get_revision_use_case = Arc::new(GetRecipeRevisionUseCase::new(recipe_repository.clone()));
+    let diff_revisions_use_case =
+        Arc::new(DiffRecipeRevisionsUseCase::new(recipe_repository.clone()));
+    let restore_revision_use_case =
+        Arc::new(RestoreRecipeRevisionUseCase::new(recipe_repository.clone()));
     let list_owned_use_case = Arc::new(ListOwnedRecipesUseCase::new(recipe_repository.clone()));
     let list_shared_use_case = Arc::new(ListSharedRecipesUseCase::new(recipe_repository.clone()));
-    let list_recipe_shares_use_case = Arc::new(ListRecipeSharesUseCase::new(share_repository.clone()));
+    let search_use_case = Arc::new(SearchRecipesUseCase::new(recipe_repository.clone()));
+    let list_recipe_shares_use_case =
+        Arc::new(ListRecipeSharesUseCase::new(share_repository.clone()));
     let create_share_use_case = Arc::new(CreateShareUseCase::new(
         recipe_repository.clone(),
         share_repository.clone(),
     ));
-    let delete_share_use_case =
-        Arc::new(DeleteShareUseCase::new(recipe_repository, share_repository));
+    let delete_share_use_case = Arc::new(DeleteShareUseCase::new(
+        recipe_repository.clone(),
+        share_repository.clone(),
+    ));
+    let create_collection_use_case =
+        Arc::new(CreateCollectionUseCase::new(collection_repository.clone()));
+    let rename_collection_use_case =
+        Arc::new(RenameCollectionUseCase::new(collection_repository.clone()));
+    let delete_collection_use_case =
+        Arc::new(DeleteCollectionUseCase::new(collection_repository.clone()));
+    let list_collections_use_case =
+        Arc::new(ListCollectionsUseCase::new(collection_repository.clone()));
+    let list_shared_collections_use_case = Arc::new(ListSharedCollectionsUseCase::new(
+        collection_repository.clone(),
+    ));
+    let get_collection_use_case =
+        Arc::new(GetCollectionUseCase::new(collection_repository.clone()));
+    let add_collection_recipe_use_case = Arc::new(AddRecipeToCollectionUseCase::new(
+        collection_repository.clone(),
+        recipe_repository,
+        share_repository,
+    ));
+    let remove_collection_recipe_use_case = Arc::new(RemoveRecipeFromCollectionUseCase::new(
+        collection_repository.clone(),
+    ));
+    let list_collection_shares_use_case = Arc::new(ListCollectionSharesUseCase::new(
+        collection_repository.clone(),
+    ));
+    let create_collection_share_use_case = Arc::new(CreateCollectionShareUseCase::new(
+        collection_repository.clone(),
+    ));
+    let delete_collection_share_use_case =
+        Arc::new(DeleteCollectionShareUseCase::new(collection_repository));
 
     let cors = CorsLayer::new()
         .allow_origin(
@@ -89,13 +390,42 @@ pub async fn create_test_app_with_llm<T: backend::recipes::domain::LlmService +
 
     create_router(
         generate_use_case,
+        stream_use_case,
+        candidates_use_case,
+        refine_use_case,
+        substitutes_use_case,
+        usage_use_case,
+        record_usage_use_case,
+        record_generation_use_case,
+        list_generations_use_case,
+        get_generation_use_case,
         save_use_case,
         get_use_case,
+        update_use_case,
+        set_tags_use_case,
+        delete_use_case,
+        list_revisions_use_case,
+        get_revision_use_case,
+        diff_revisions_use_case,
+        restore_revision_use_case,
         list_owned_use_case,
         list_shared_use_case,
+        search_use_case,
         list_recipe_shares_use_case,
         create_share_use_case,
         delete_share_use_case,
+        create_collection_use_case,
+        rename_collection_use_case,
+        delete_collection_use_case,
+        list_collections_use_case,
+        list_shared_collections_use_case,
+        get_collection_use_case,
+        add_collection_recipe_use_case,
+        remove_collection_recipe_use_case,
+        list_collection_shares_use_case,
+        create_collection_share_use_case,
+        delete_collection_share_use_case,
+        Arc::new(CursorCodec::new(b"test-cursor-secret")),
     )
     .layer(ServiceBuilder::new().layer(cors).into_inner())
 }
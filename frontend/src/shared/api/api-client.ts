@@ -37,6 +37,11 @@ axiosInstance.interceptors.request.use(
   }
 );
 
+export interface Page<T> {
+  items: T[];
+  nextCursor: string | null;
+}
+
 class APIClient<T> {
   endpoint: string;
 
@@ -50,6 +55,15 @@ class APIClient<T> {
       .then((res) => res.data);
   };
 
+  getPage = (cursor?: string, config?: AxiosRequestConfig) => {
+    return axiosInstance
+      .get<Page<T>>(this.endpoint, {
+        ...config,
+        params: { ...config?.params, cursor },
+      })
+      .then((res) => res.data);
+  };
+
   get = (id: number | string, config?: AxiosRequestConfig) => {
     return axiosInstance
       .get<T>(this.endpoint + "/" + id, config)
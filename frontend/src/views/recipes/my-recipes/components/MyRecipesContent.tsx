@@ -2,9 +2,17 @@ import { useMyRecipes } from "../hooks/useMyRecipes";
 import RecipeCard from "../../shared/components/RecipeCard";
 import LoadingState from "../../../../shared/components/ui/LoadingState";
 import ErrorState from "../../../../shared/components/ui/ErrorState";
+import PrimaryButton from "../../../../shared/components/ui/PrimaryButton";
 
 export default function MyRecipesContent() {
-  const { data: recipes, isLoading, error } = useMyRecipes();
+  const {
+    data,
+    isLoading,
+    error,
+    hasNextPage,
+    fetchNextPage,
+    isFetchingNextPage,
+  } = useMyRecipes();
 
   if (isLoading) {
     return <LoadingState message="Loading recipes..." />;
@@ -14,7 +22,7 @@ export default function MyRecipesContent() {
     return <ErrorState message="Failed to load recipes. Please try again." />;
   }
 
-  const recipesArray = Array.isArray(recipes) ? recipes : [];
+  const recipesArray = data?.pages.flatMap((page) => page.items) ?? [];
 
   if (recipesArray.length === 0) {
     return (
@@ -36,6 +44,16 @@ export default function MyRecipesContent() {
           showShareButton={true}
         />
       ))}
+      {hasNextPage && (
+        <div className="text-center">
+          <PrimaryButton
+            onClick={() => fetchNextPage()}
+            disabled={isFetchingNextPage}
+          >
+            {isFetchingNextPage ? "Loading..." : "Load more"}
+          </PrimaryButton>
+        </div>
+      )}
     </div>
   );
 }
@@ -1,10 +1,11 @@
-import { useQuery } from "@tanstack/react-query";
+import { useInfiniteQuery } from "@tanstack/react-query";
 import { recipesClient } from "../../generate-recipe/api";
-import type { Recipe } from "../../generate-recipe/types";
 
 export function useMyRecipes() {
-  return useQuery<Recipe[]>({
+  return useInfiniteQuery({
     queryKey: ["recipes", "my"],
-    queryFn: () => recipesClient.getAll(),
+    queryFn: ({ pageParam }) => recipesClient.getPage(pageParam),
+    initialPageParam: undefined as string | undefined,
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
   });
 }
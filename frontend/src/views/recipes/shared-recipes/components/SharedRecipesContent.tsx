@@ -2,9 +2,17 @@ import { useSharedRecipes } from "../hooks/useSharedRecipes";
 import RecipeCard from "../../shared/components/RecipeCard";
 import LoadingState from "../../../../shared/components/ui/LoadingState";
 import ErrorState from "../../../../shared/components/ui/ErrorState";
+import PrimaryButton from "../../../../shared/components/ui/PrimaryButton";
 
 export default function SharedRecipesContent() {
-  const { data: recipes, isLoading, error } = useSharedRecipes();
+  const {
+    data,
+    isLoading,
+    error,
+    hasNextPage,
+    fetchNextPage,
+    isFetchingNextPage,
+  } = useSharedRecipes();
 
   if (isLoading) {
     return <LoadingState message="Loading shared recipes..." />;
@@ -16,7 +24,7 @@ export default function SharedRecipesContent() {
     );
   }
 
-  const recipesArray = Array.isArray(recipes) ? recipes : [];
+  const recipesArray = data?.pages.flatMap((page) => page.items) ?? [];
 
   if (recipesArray.length === 0) {
     return (
@@ -39,6 +47,16 @@ export default function SharedRecipesContent() {
           showSharedBy={true}
         />
       ))}
+      {hasNextPage && (
+        <div className="text-center">
+          <PrimaryButton
+            onClick={() => fetchNextPage()}
+            disabled={isFetchingNextPage}
+          >
+            {isFetchingNextPage ? "Loading..." : "Load more"}
+          </PrimaryButton>
+        </div>
+      )}
     </div>
   );
 }
@@ -1,12 +1,14 @@
-import { useQuery } from "@tanstack/react-query";
+import { useInfiniteQuery } from "@tanstack/react-query";
 import APIClient from "../../../../shared/api/api-client";
 import type { Recipe } from "../../generate-recipe/types";
 
 const sharedRecipesClient = new APIClient<Recipe>("/api/recipes/shared");
 
 export function useSharedRecipes() {
-  return useQuery<Recipe[]>({
+  return useInfiniteQuery({
     queryKey: ["recipes", "shared"],
-    queryFn: () => sharedRecipesClient.getAll(),
+    queryFn: ({ pageParam }) => sharedRecipesClient.getPage(pageParam),
+    initialPageParam: undefined as string | undefined,
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
   });
 }